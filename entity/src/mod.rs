@@ -2,6 +2,18 @@
 
 pub mod prelude;
 
+pub mod api_key;
 pub mod category;
+pub mod login_attempt;
+pub mod oidc_login_state;
+pub mod password_history;
+pub mod password_reset_token;
 pub mod product;
+pub mod product_image;
+pub mod stored_file;
+pub mod used_challenge;
 pub mod user;
+pub mod user_identity;
+pub mod user_recovery_code;
+pub mod user_session;
+pub mod user_totp;
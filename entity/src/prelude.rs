@@ -1,5 +1,17 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.7
 
+pub use super::api_key::Entity as ApiKey;
 pub use super::category::Entity as Category;
+pub use super::login_attempt::Entity as LoginAttempt;
+pub use super::oidc_login_state::Entity as OidcLoginState;
+pub use super::password_history::Entity as PasswordHistory;
+pub use super::password_reset_token::Entity as PasswordResetToken;
 pub use super::product::Entity as Product;
+pub use super::product_image::Entity as ProductImage;
+pub use super::stored_file::Entity as StoredFile;
+pub use super::used_challenge::Entity as UsedChallenge;
 pub use super::user::Entity as User;
+pub use super::user_identity::Entity as UserIdentity;
+pub use super::user_recovery_code::Entity as UserRecoveryCode;
+pub use super::user_session::Entity as UserSession;
+pub use super::user_totp::Entity as UserTotp;
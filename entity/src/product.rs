@@ -30,6 +30,8 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     Category,
+    #[sea_orm(has_many = "super::product_image::Entity")]
+    ProductImage,
 }
 
 impl Related<super::category::Entity> for Entity {
@@ -38,4 +40,10 @@ impl Related<super::category::Entity> for Entity {
     }
 }
 
+impl Related<super::product_image::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProductImage.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
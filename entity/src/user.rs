@@ -16,6 +16,7 @@ pub struct Model {
     pub photo: String,
     pub created_at: DateTime,
     pub updated_at: DateTime,
+    pub token_version: i32,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
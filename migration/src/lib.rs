@@ -3,6 +3,17 @@ pub use sea_orm_migration::prelude::*;
 mod m20220101_000001_create_table;
 mod m20250331_090647_add_product_table;
 mod m20250401_083816_create_table_product;
+mod m20261019_090000_create_table_stored_file;
+mod m20261019_100000_create_table_product_image;
+mod m20261019_110000_add_visibility_to_stored_file;
+mod m20261019_120000_create_table_login_attempt;
+mod m20261019_130000_create_table_two_factor;
+mod m20261019_140000_create_table_password_history;
+mod m20261019_150000_create_table_password_reset_token;
+mod m20261019_160000_create_table_user_session;
+mod m20261019_170000_create_table_api_key;
+mod m20261019_180000_create_table_oidc;
+mod m20261019_190000_create_table_used_challenge;
 
 pub struct Migrator;
 
@@ -13,6 +24,17 @@ impl MigratorTrait for Migrator {
             Box::new(m20220101_000001_create_table::Migration),
             Box::new(m20250331_090647_add_product_table::Migration),
             Box::new(m20250401_083816_create_table_product::Migration),
+            Box::new(m20261019_090000_create_table_stored_file::Migration),
+            Box::new(m20261019_100000_create_table_product_image::Migration),
+            Box::new(m20261019_110000_add_visibility_to_stored_file::Migration),
+            Box::new(m20261019_120000_create_table_login_attempt::Migration),
+            Box::new(m20261019_130000_create_table_two_factor::Migration),
+            Box::new(m20261019_140000_create_table_password_history::Migration),
+            Box::new(m20261019_150000_create_table_password_reset_token::Migration),
+            Box::new(m20261019_160000_create_table_user_session::Migration),
+            Box::new(m20261019_170000_create_table_api_key::Migration),
+            Box::new(m20261019_180000_create_table_oidc::Migration),
+            Box::new(m20261019_190000_create_table_used_challenge::Migration),
         ]
     }
 }
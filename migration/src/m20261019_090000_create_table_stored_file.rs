@@ -0,0 +1,59 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(StoredFile::Table)
+                    .if_not_exists()
+                    .col(pk_auto(StoredFile::Id))
+                    .col(string(StoredFile::Namespace))
+                    .col(string_len(StoredFile::Hash, 64))
+                    .col(string(StoredFile::FileName))
+                    .col(string(StoredFile::OriginalName))
+                    .col(string(StoredFile::ContentType))
+                    .col(big_integer(StoredFile::Size))
+                    .col(date_time(StoredFile::CreatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_stored_file_namespace_hash")
+                    .table(StoredFile::Table)
+                    .col(StoredFile::Namespace)
+                    .col(StoredFile::Hash)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        manager
+            .drop_table(Table::drop().table(StoredFile::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum StoredFile {
+    Table,
+    Id,
+    Namespace,
+    Hash,
+    FileName,
+    OriginalName,
+    ContentType,
+    Size,
+    CreatedAt
+}
@@ -0,0 +1,99 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProductImage::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ProductImage::Id))
+                    .col(integer(ProductImage::ProductId))
+                    .col(string(ProductImage::FileName))
+                    .col(string(ProductImage::AltText))
+                    .col(integer(ProductImage::Position))
+                    .col(boolean(ProductImage::IsPrimary).default(false))
+                    .foreign_key(
+                        ForeignKey::create()
+                        .name("fk_product_image_product")
+                        .from(ProductImage::Table, ProductImage::ProductId)
+                        .to(Product::Table, Product::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                    )
+                    .col(date_time(ProductImage::CreatedAt).default(Expr::current_timestamp()))
+                    .col(date_time(ProductImage::UpdatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_product_image_product_id")
+                    .table(ProductImage::Table)
+                    .col(ProductImage::ProductId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Existing single images become the primary image of each gallery.
+        let backfill = Query::insert()
+            .into_table(ProductImage::Table)
+            .columns([
+                ProductImage::ProductId,
+                ProductImage::FileName,
+                ProductImage::AltText,
+                ProductImage::Position,
+                ProductImage::IsPrimary,
+            ])
+            .select_from(
+                Query::select()
+                    .column(Product::Id)
+                    .column(Product::Image)
+                    .column(Product::Name)
+                    .expr(Expr::val(0))
+                    .expr(Expr::val(true))
+                    .from(Product::Table)
+                    .and_where(Expr::col(Product::Image).ne(""))
+                    .to_owned(),
+            )
+            .map_err(|e| DbErr::Custom(e.to_string()))?
+            .to_owned();
+
+        manager.exec_stmt(backfill).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        manager
+            .drop_table(Table::drop().table(ProductImage::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Product {
+    Table,
+    Id,
+    Name,
+    Image,
+}
+
+#[derive(DeriveIden)]
+enum ProductImage {
+    Table,
+    Id,
+    ProductId,
+    FileName,
+    AltText,
+    Position,
+    IsPrimary,
+    CreatedAt,
+    UpdatedAt
+}
@@ -0,0 +1,57 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        // Failed logins per submitted username and per client IP. Rows are
+        // keyed by what was submitted, so unknown usernames are tracked too.
+        manager
+            .create_table(
+                Table::create()
+                    .table(LoginAttempt::Table)
+                    .if_not_exists()
+                    .col(pk_auto(LoginAttempt::Id))
+                    .col(string_len(LoginAttempt::Scope, 16))
+                    .col(string(LoginAttempt::Key))
+                    .col(integer(LoginAttempt::Failures).default(0))
+                    .col(date_time(LoginAttempt::LastFailureAt))
+                    .col(date_time_null(LoginAttempt::LockedUntil))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_login_attempt_scope_key")
+                    .table(LoginAttempt::Table)
+                    .col(LoginAttempt::Scope)
+                    .col(LoginAttempt::Key)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        manager
+            .drop_table(Table::drop().table(LoginAttempt::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum LoginAttempt {
+    Table,
+    Id,
+    Scope,
+    Key,
+    Failures,
+    LastFailureAt,
+    LockedUntil
+}
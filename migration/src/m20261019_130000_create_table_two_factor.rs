@@ -0,0 +1,106 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        // One TOTP secret per user. It only protects logins once `confirmed_at`
+        // is set; `last_used_step` stops a code from being replayed.
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserTotp::Table)
+                    .if_not_exists()
+                    .col(pk_auto(UserTotp::Id))
+                    .col(integer_uniq(UserTotp::UserId))
+                    .col(string(UserTotp::Secret))
+                    .col(date_time_null(UserTotp::ConfirmedAt))
+                    .col(big_integer_null(UserTotp::LastUsedStep))
+                    .foreign_key(
+                        ForeignKey::create()
+                        .name("fk_user_totp_user")
+                        .from(UserTotp::Table, UserTotp::UserId)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                    )
+                    .col(date_time(UserTotp::CreatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Recovery codes are stored as SHA-256 hex digests.
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserRecoveryCode::Table)
+                    .if_not_exists()
+                    .col(pk_auto(UserRecoveryCode::Id))
+                    .col(integer(UserRecoveryCode::UserId))
+                    .col(string_len(UserRecoveryCode::CodeHash, 64))
+                    .col(date_time_null(UserRecoveryCode::UsedAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                        .name("fk_user_recovery_code_user")
+                        .from(UserRecoveryCode::Table, UserRecoveryCode::UserId)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                    )
+                    .col(date_time(UserRecoveryCode::CreatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_user_recovery_code_user_id")
+                    .table(UserRecoveryCode::Table)
+                    .col(UserRecoveryCode::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        manager
+            .drop_table(Table::drop().table(UserRecoveryCode::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(UserTotp::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum UserTotp {
+    Table,
+    Id,
+    UserId,
+    Secret,
+    ConfirmedAt,
+    LastUsedStep,
+    CreatedAt
+}
+
+#[derive(DeriveIden)]
+enum UserRecoveryCode {
+    Table,
+    Id,
+    UserId,
+    CodeHash,
+    UsedAt,
+    CreatedAt
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id
+}
@@ -0,0 +1,65 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        // Hashes of passwords a user has replaced, newest last, so that the
+        // password policy can refuse to take them back.
+        manager
+            .create_table(
+                Table::create()
+                    .table(PasswordHistory::Table)
+                    .if_not_exists()
+                    .col(pk_auto(PasswordHistory::Id))
+                    .col(integer(PasswordHistory::UserId))
+                    .col(string(PasswordHistory::PasswordHash))
+                    .foreign_key(
+                        ForeignKey::create()
+                        .name("fk_password_history_user")
+                        .from(PasswordHistory::Table, PasswordHistory::UserId)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                    )
+                    .col(date_time(PasswordHistory::CreatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_password_history_user_id")
+                    .table(PasswordHistory::Table)
+                    .col(PasswordHistory::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        manager
+            .drop_table(Table::drop().table(PasswordHistory::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PasswordHistory {
+    Table,
+    Id,
+    UserId,
+    PasswordHash,
+    CreatedAt
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id
+}
@@ -0,0 +1,79 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        // Tokens carry the version they were issued at; bumping it revokes
+        // every session of the user.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(integer(User::TokenVersion).default(0))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Single-use reset tokens, stored as SHA-256 hex digests.
+        manager
+            .create_table(
+                Table::create()
+                    .table(PasswordResetToken::Table)
+                    .if_not_exists()
+                    .col(pk_auto(PasswordResetToken::Id))
+                    .col(integer(PasswordResetToken::UserId))
+                    .col(string_len_uniq(PasswordResetToken::TokenHash, 64))
+                    .col(date_time(PasswordResetToken::ExpiresAt))
+                    .col(date_time_null(PasswordResetToken::UsedAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                        .name("fk_password_reset_token_user")
+                        .from(PasswordResetToken::Table, PasswordResetToken::UserId)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                    )
+                    .col(date_time(PasswordResetToken::CreatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        manager
+            .drop_table(Table::drop().table(PasswordResetToken::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(User::TokenVersion)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PasswordResetToken {
+    Table,
+    Id,
+    UserId,
+    TokenHash,
+    ExpiresAt,
+    UsedAt,
+    CreatedAt
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+    TokenVersion
+}
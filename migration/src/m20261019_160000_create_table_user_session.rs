@@ -0,0 +1,73 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        // One row per login; tokens name theirs in the `sid` claim and stop
+        // working once it is deleted or expired.
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserSession::Table)
+                    .if_not_exists()
+                    .col(pk_auto(UserSession::Id))
+                    .col(integer(UserSession::UserId))
+                    .col(string_len_uniq(UserSession::Sid, 36))
+                    .col(string_len(UserSession::UserAgent, 255))
+                    .col(string_len_null(UserSession::Ip, 45))
+                    .col(date_time(UserSession::LastSeenAt))
+                    .col(date_time(UserSession::ExpiresAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                        .name("fk_user_session_user")
+                        .from(UserSession::Table, UserSession::UserId)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                    )
+                    .col(date_time(UserSession::CreatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_user_session_user_id")
+                    .table(UserSession::Table)
+                    .col(UserSession::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        manager
+            .drop_table(Table::drop().table(UserSession::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum UserSession {
+    Table,
+    Id,
+    UserId,
+    Sid,
+    UserAgent,
+    Ip,
+    LastSeenAt,
+    ExpiresAt,
+    CreatedAt
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id
+}
@@ -0,0 +1,66 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        // Keys of machine-to-machine integrations. Only a SHA-256 of the key
+        // is kept; `prefix` is its first characters, so it can be recognised
+        // in listings. `scopes` is comma separated.
+        manager
+            .create_table(
+                Table::create()
+                    .table(ApiKey::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ApiKey::Id))
+                    .col(integer(ApiKey::UserId))
+                    .col(string_len(ApiKey::Name, 100))
+                    .col(string_len(ApiKey::Prefix, 16))
+                    .col(string_len_uniq(ApiKey::KeyHash, 64))
+                    .col(string_len(ApiKey::Scopes, 255))
+                    .col(date_time_null(ApiKey::ExpiresAt))
+                    .col(date_time_null(ApiKey::LastUsedAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                        .name("fk_api_key_user")
+                        .from(ApiKey::Table, ApiKey::UserId)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                    )
+                    .col(date_time(ApiKey::CreatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        manager
+            .drop_table(Table::drop().table(ApiKey::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ApiKey {
+    Table,
+    Id,
+    UserId,
+    Name,
+    Prefix,
+    KeyHash,
+    Scopes,
+    ExpiresAt,
+    LastUsedAt,
+    CreatedAt
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id
+}
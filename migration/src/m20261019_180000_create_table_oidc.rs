@@ -0,0 +1,98 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        // Which local user an identity at an OIDC provider logs in as.
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserIdentity::Table)
+                    .if_not_exists()
+                    .col(pk_auto(UserIdentity::Id))
+                    .col(integer(UserIdentity::UserId))
+                    .col(string_len(UserIdentity::Issuer, 255))
+                    .col(string_len(UserIdentity::Subject, 255))
+                    .foreign_key(
+                        ForeignKey::create()
+                        .name("fk_user_identity_user")
+                        .from(UserIdentity::Table, UserIdentity::UserId)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                    )
+                    .col(date_time(UserIdentity::CreatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_user_identity_issuer_subject")
+                    .table(UserIdentity::Table)
+                    .col(UserIdentity::Issuer)
+                    .col(UserIdentity::Subject)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // Logins sent to the provider and not back yet, by their `state`.
+        // The PKCE verifier never leaves the server.
+        manager
+            .create_table(
+                Table::create()
+                    .table(OidcLoginState::Table)
+                    .if_not_exists()
+                    .col(pk_auto(OidcLoginState::Id))
+                    .col(string_len_uniq(OidcLoginState::State, 64))
+                    .col(string_len(OidcLoginState::CodeVerifier, 128))
+                    .col(string_len(OidcLoginState::Nonce, 64))
+                    .col(date_time(OidcLoginState::ExpiresAt))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        manager
+            .drop_table(Table::drop().table(OidcLoginState::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(UserIdentity::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum UserIdentity {
+    Table,
+    Id,
+    UserId,
+    Issuer,
+    Subject,
+    CreatedAt
+}
+
+#[derive(DeriveIden)]
+enum OidcLoginState {
+    Table,
+    Id,
+    State,
+    CodeVerifier,
+    Nonce,
+    ExpiresAt
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id
+}
@@ -0,0 +1,39 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        // `jti` of two-factor challenges already redeemed, kept until the
+        // challenge would have expired anyway.
+        manager
+            .create_table(
+                Table::create()
+                    .table(UsedChallenge::Table)
+                    .if_not_exists()
+                    .col(pk_auto(UsedChallenge::Id))
+                    .col(string_len_uniq(UsedChallenge::Jti, 64))
+                    .col(date_time(UsedChallenge::ExpiresAt))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        manager
+            .drop_table(Table::drop().table(UsedChallenge::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum UsedChallenge {
+    Table,
+    Id,
+    Jti,
+    ExpiresAt,
+}
@@ -0,0 +1,87 @@
+// Runs every migration up, down and up again on each supported backend.
+// SQLite always runs in memory; Postgres and MySQL run when
+// `TEST_DATABASE_URL` and `TEST_MYSQL_URL` point at a server.
+
+use migration::{ Migrator, MigratorTrait };
+use sea_orm_migration::sea_orm::{ ConnectOptions, ConnectionTrait, Database, DatabaseConnection, Statement };
+
+async fn count(db: &DatabaseConnection, table: &str) -> i64 {
+    let row = db
+        .query_one(Statement::from_string(db.get_database_backend(), format!("SELECT COUNT(*) AS n FROM {table}")))
+        .await
+        .unwrap()
+        .unwrap();
+
+    row.try_get::<i64>("", "n").unwrap()
+}
+
+async fn check_migrations(db: &DatabaseConnection) {
+    let total = Migrator::migrations().len();
+
+    // Stop before the product gallery so its backfill has a product to copy.
+    Migrator::up(db, Some(3)).await.expect("first migrations failed");
+
+    db.execute_unprepared("INSERT INTO category (name) VALUES ('Drinks')").await.unwrap();
+    db.execute_unprepared(
+        "INSERT INTO product (name, description, purchase_price, selling_price, stock, discount, image, category_id) \
+        VALUES ('Tea', 'Green tea', 1000, 1500, 10, 0, 'tea.png', 1), \
+        ('Coffee', 'Black coffee', 1000, 1500, 10, 0, '', 1)"
+    ).await.unwrap();
+
+    Migrator::up(db, None).await.expect("remaining migrations failed");
+
+    assert!(Migrator::get_pending_migrations(db).await.unwrap().is_empty());
+    assert_eq!(count(db, "product_image").await, 1);
+
+    Migrator::down(db, None).await.expect("rolling back failed");
+
+    assert_eq!(Migrator::get_pending_migrations(db).await.unwrap().len(), total);
+
+    Migrator::up(db, None).await.expect("migrating again failed");
+}
+
+#[tokio::test]
+async fn migrations_run_on_sqlite() {
+    let mut options = ConnectOptions::new("sqlite::memory:");
+    // Every pooled connection would otherwise get its own in-memory database.
+    options.max_connections(1).sqlx_logging(false);
+
+    check_migrations(&Database::connect(options).await.unwrap()).await;
+}
+
+#[tokio::test]
+async fn migrations_run_on_postgres() {
+    let Some(url) = std::env::var("TEST_DATABASE_URL").ok().filter(|d| !d.is_empty()) else {
+        return;
+    };
+
+    let schema = format!("test_{}", uuid::Uuid::new_v4().simple());
+    let admin = Database::connect(&url).await.unwrap();
+
+    admin.execute_unprepared(&format!("CREATE SCHEMA \"{schema}\"")).await.unwrap();
+
+    let mut options = ConnectOptions::new(url);
+    options.set_schema_search_path(schema.clone()).sqlx_logging(false);
+
+    check_migrations(&Database::connect(options).await.unwrap()).await;
+
+    admin.execute_unprepared(&format!("DROP SCHEMA \"{schema}\" CASCADE")).await.unwrap();
+}
+
+#[tokio::test]
+async fn migrations_run_on_mysql() {
+    let Some(url) = std::env::var("TEST_MYSQL_URL").ok().filter(|d| !d.is_empty()) else {
+        return;
+    };
+
+    // MySQL has no schemas inside a database, so the database is wiped first.
+    let mut options = ConnectOptions::new(url);
+    options.sqlx_logging(false);
+
+    let db = Database::connect(options).await.unwrap();
+
+    Migrator::fresh(&db).await.unwrap();
+    Migrator::reset(&db).await.unwrap();
+
+    check_migrations(&db).await;
+}
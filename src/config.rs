@@ -0,0 +1,701 @@
+use serde::Deserialize;
+use std::fmt;
+
+use crate::utils::file_buckets::{ default_buckets, FileBucket, FileBuckets };
+
+const DEFAULT_CONFIG_PATH: &str = "config/app.toml";
+
+#[derive(Debug)]
+pub enum ConfigError {
+	File(String, String),
+	Env(String, String),
+	Invalid(String)
+}
+
+impl fmt::Display for ConfigError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ConfigError::File(path, e) => write!(f, "failed to read config file {path}: {e}"),
+			ConfigError::Env(name, e) => write!(f, "invalid value for {name}: {e}"),
+			ConfigError::Invalid(e) => write!(f, "{e}")
+		}
+	}
+}
+
+impl std::error::Error for ConfigError {}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ServerConfig {
+	pub address: String,
+	// `*` (or an empty list) allows any origin.
+	pub cors_origins: Vec<String>,
+	// Take the client IP from `X-Forwarded-For` (set by a trusted proxy)
+	// instead of the socket's peer address.
+	pub trust_forwarded_for: bool,
+	// How many proxies in front of the server append to `X-Forwarded-For`.
+	// The client IP is the entry that many from the right; entries further
+	// left were sent by the client and can say anything.
+	pub trusted_proxies: usize,
+	// After SIGTERM/SIGINT, how long `/health/ready` fails while requests
+	// are still accepted, so load balancers stop routing here first.
+	pub shutdown_delay_seconds: u64,
+	// How long in-flight requests may take to finish once the listener closed.
+	pub shutdown_timeout_seconds: u64
+}
+
+impl Default for ServerConfig {
+	fn default() -> Self {
+		Self {
+			address: "localhost:3000".to_owned(),
+			cors_origins: vec!["*".to_owned()],
+			trust_forwarded_for: false,
+			trusted_proxies: 1,
+			shutdown_delay_seconds: 5,
+			shutdown_timeout_seconds: 30
+		}
+	}
+}
+
+impl ServerConfig {
+	// Trusted `X-Forwarded-For` entries, counted from the right; none unless
+	// `trust_forwarded_for` is set.
+	pub fn forwarded_hops(&self) -> usize {
+		if self.trust_forwarded_for { self.trusted_proxies } else { 0 }
+	}
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct DatabaseConfig {
+	pub url: String,
+	pub max_connections: u32,
+	// Apply pending migrations at boot instead of only reporting them.
+	pub auto_migrate: bool
+}
+
+impl Default for DatabaseConfig {
+	fn default() -> Self {
+		Self {
+			url: String::new(),
+			max_connections: 10,
+			auto_migrate: false
+		}
+	}
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JwtAlgorithm {
+	HS256,
+	RS256,
+	EdDSA
+}
+
+impl std::str::FromStr for JwtAlgorithm {
+	type Err = String;
+
+	fn from_str(val: &str) -> Result<Self, Self::Err> {
+		match val {
+			"HS256" => Ok(JwtAlgorithm::HS256),
+			"RS256" => Ok(JwtAlgorithm::RS256),
+			"EdDSA" => Ok(JwtAlgorithm::EdDSA),
+			_ => Err(format!("expected HS256, RS256 or EdDSA, got {val}"))
+		}
+	}
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct JwtConfig {
+	// RS256 and EdDSA sign with the newest private key in `keys_dir` and
+	// publish every key there at `/.well-known/jwks.json`. HS256 signs with
+	// `secret` and publishes nothing.
+	pub algorithm: JwtAlgorithm,
+	// Signs and verifies everything with HS256; otherwise only read for
+	// `accept_hs256_until`.
+	pub secret: String,
+	// After switching to an asymmetric algorithm, access tokens signed with
+	// `secret` stay valid until this instant (RFC 3339). Other tokens, such
+	// as 2FA challenges, never fall back to the secret.
+	pub accept_hs256_until: Option<chrono::DateTime<chrono::Utc>>,
+	// PEM private keys named `<kid>.pem`. Every replica must see the same
+	// directory (a shared volume, or the same keys provisioned to each), or
+	// each signs with a key the others reject. When it holds no key, the
+	// first is generated at boot under a lock file in the directory.
+	pub keys_dir: String,
+	// `rotate-jwt-key` keeps this many of the newest keys, so tokens signed
+	// by the previous `max_keys - 1` keys stay valid.
+	pub max_keys: usize,
+	// The `iss` and `aud` of every token; `auth_guard` rejects others.
+	pub issuer: String,
+	pub audience: String,
+	// Lifetime of tokens issued by `login`.
+	pub token_lifetime_seconds: u64,
+	// Lifetime of tokens reissued by `authenticated`.
+	pub refresh_lifetime_seconds: u64
+}
+
+impl Default for JwtConfig {
+	fn default() -> Self {
+		Self {
+			algorithm: JwtAlgorithm::EdDSA,
+			secret: String::new(),
+			accept_hs256_until: None,
+			keys_dir: "keys/jwt".to_owned(),
+			max_keys: 3,
+			issuer: "rust-axum-seaorm".to_owned(),
+			audience: "rust-axum-seaorm".to_owned(),
+			token_lifetime_seconds: 360000,
+			refresh_lifetime_seconds: 36000000
+		}
+	}
+}
+
+// Failed logins are counted per submitted username and per client IP. After
+// `free_attempts` failures each further attempt waits `backoff_base_seconds`,
+// doubling up to `backoff_max_seconds`; at `max_failures` (or
+// `ip_max_failures` for an IP) the key is locked for `lockout_seconds`.
+// Failures older than `window_seconds` are forgotten.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct LoginThrottleConfig {
+	pub free_attempts: u32,
+	pub backoff_base_seconds: u64,
+	pub backoff_max_seconds: u64,
+	pub max_failures: u32,
+	pub ip_max_failures: u32,
+	pub lockout_seconds: u64,
+	pub window_seconds: u64
+}
+
+impl Default for LoginThrottleConfig {
+	fn default() -> Self {
+		Self {
+			free_attempts: 3,
+			backoff_base_seconds: 1,
+			backoff_max_seconds: 60,
+			max_failures: 10,
+			ip_max_failures: 50,
+			lockout_seconds: 900,
+			window_seconds: 900
+		}
+	}
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PasswordAlgorithm {
+	Argon2id,
+	Bcrypt
+}
+
+impl std::str::FromStr for PasswordAlgorithm {
+	type Err = String;
+
+	fn from_str(val: &str) -> Result<Self, Self::Err> {
+		match val {
+			"argon2id" => Ok(PasswordAlgorithm::Argon2id),
+			"bcrypt" => Ok(PasswordAlgorithm::Bcrypt),
+			_ => Err(format!("expected argon2id or bcrypt, got {val}"))
+		}
+	}
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct PasswordConfig {
+	// Hashes made with another algorithm or other parameters are replaced at
+	// the user's next successful login.
+	pub algorithm: PasswordAlgorithm,
+	pub argon2_memory_kib: u32,
+	pub argon2_iterations: u32,
+	pub argon2_parallelism: u32,
+	pub bcrypt_cost: u32,
+	pub min_length: usize,
+	pub max_length: usize,
+	// A new password can't be any of the user's last `history_size`
+	// passwords, the current one included. 0 turns the check off.
+	pub history_size: u64,
+	// File of known breached passwords, one per line, compared without
+	// regard to case. Empty turns the check off.
+	pub breached_list_path: String
+}
+
+impl Default for PasswordConfig {
+	fn default() -> Self {
+		Self {
+			algorithm: PasswordAlgorithm::Argon2id,
+			argon2_memory_kib: 19456,
+			argon2_iterations: 2,
+			argon2_parallelism: 1,
+			bcrypt_cost: 12,
+			min_length: 8,
+			max_length: 128,
+			history_size: 5,
+			breached_list_path: String::new()
+		}
+	}
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct PasswordResetConfig {
+	// How long a reset token stays usable.
+	pub token_lifetime_seconds: u64
+}
+
+impl Default for PasswordResetConfig {
+	fn default() -> Self {
+		Self {
+			token_lifetime_seconds: 3600
+		}
+	}
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct TwoFactorConfig {
+	// Shown as the account's issuer by authenticator apps.
+	pub issuer: String,
+	// Users with these roles must enrol before they can finish a login, local
+	// or through OIDC.
+	pub required_roles: Vec<String>,
+	// Lifetime of the challenge token between the password and the code.
+	pub challenge_lifetime_seconds: u64,
+	pub recovery_codes: usize
+}
+
+impl Default for TwoFactorConfig {
+	fn default() -> Self {
+		Self {
+			issuer: "Rust Axum SeaORM".to_owned(),
+			required_roles: Vec::new(),
+			challenge_lifetime_seconds: 300,
+			recovery_codes: 10
+		}
+	}
+}
+
+// An IdP claim value and the local role it grants.
+#[derive(Deserialize, Clone, Debug)]
+pub struct OidcRoleMapping {
+	pub value: String,
+	pub role: String
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct OidcConfig {
+	// Adds login through an OpenID Connect provider next to local login.
+	pub enabled: bool,
+	// The provider's issuer URL; its discovery document is fetched from
+	// `{issuer}/.well-known/openid-configuration`.
+	pub issuer: String,
+	pub client_id: String,
+	// Empty for a public client, which relies on PKCE alone.
+	pub client_secret: String,
+	// Where the provider sends the browser back: `/api/auth/oidc/callback`,
+	// or a page that forwards its query string there.
+	pub redirect_uri: String,
+	pub scopes: Vec<String>,
+	// Username of provisioned accounts; an identity whose claim names an
+	// existing local user is refused rather than taken for that user.
+	pub username_claim: String,
+	// Create an account for an unknown identity instead of refusing it.
+	pub auto_provision: bool,
+	// Link an unknown identity to the local user whose username is its
+	// `email`, when the provider marks that as verified (`email_verified`).
+	pub link_existing_users: bool,
+	// Role of provisioned users that match no `role_mapping` entry.
+	pub default_role: String,
+	// Claim holding the user's groups or roles at the provider, a string or
+	// a list of strings.
+	pub role_claim: String,
+	// Checked in order at every login; the first value found in
+	// `role_claim` sets the user's role.
+	pub role_mapping: Vec<OidcRoleMapping>,
+	// How long the provider may take to send the browser back.
+	pub login_timeout_seconds: u64
+}
+
+impl Default for OidcConfig {
+	fn default() -> Self {
+		Self {
+			enabled: false,
+			issuer: String::new(),
+			client_id: String::new(),
+			client_secret: String::new(),
+			redirect_uri: String::new(),
+			scopes: vec!["openid".to_owned(), "profile".to_owned(), "email".to_owned()],
+			username_claim: "preferred_username".to_owned(),
+			auto_provision: false,
+			link_existing_users: false,
+			default_role: "cashier".to_owned(),
+			role_claim: "groups".to_owned(),
+			role_mapping: Vec::new(),
+			login_timeout_seconds: 600
+		}
+	}
+}
+
+// Token bucket: up to `burst` requests at once, refilled at `per_minute`.
+// A `burst` of 0 switches the group off.
+#[derive(Deserialize, Clone, Debug)]
+pub struct RateLimitRule {
+	pub burst: u32,
+	pub per_minute: u32
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct RateLimitConfig {
+	pub enabled: bool,
+	pub login: RateLimitRule,
+	pub search: RateLimitRule,
+	pub uploads: RateLimitRule,
+	pub writes: RateLimitRule
+}
+
+impl Default for RateLimitConfig {
+	fn default() -> Self {
+		Self {
+			enabled: true,
+			login: RateLimitRule { burst: 10, per_minute: 10 },
+			search: RateLimitRule { burst: 30, per_minute: 120 },
+			uploads: RateLimitRule { burst: 10, per_minute: 30 },
+			writes: RateLimitRule { burst: 30, per_minute: 120 }
+		}
+	}
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct UploadConfig {
+	// Parent directory of buckets that don't set their own `directory`.
+	pub root: String,
+	pub max_request_bytes: usize,
+	pub signing_secret: String,
+	pub signed_url_ttl_seconds: i64,
+	#[serde(rename = "bucket")]
+	pub buckets: Vec<FileBucket>
+}
+
+impl Default for UploadConfig {
+	fn default() -> Self {
+		Self {
+			root: "uploads".to_owned(),
+			max_request_bytes: 50 * 1024 * 1024,
+			signing_secret: String::new(),
+			signed_url_ttl_seconds: 900,
+			buckets: default_buckets()
+		}
+	}
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+	Pretty,
+	Json
+}
+
+impl std::str::FromStr for LogFormat {
+	type Err = String;
+
+	fn from_str(val: &str) -> Result<Self, Self::Err> {
+		match val {
+			"pretty" => Ok(LogFormat::Pretty),
+			"json" => Ok(LogFormat::Json),
+			_ => Err(format!("expected pretty or json, got {val}"))
+		}
+	}
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct LoggingConfig {
+	pub format: LogFormat,
+	// `tracing_subscriber::EnvFilter` directives, e.g. `info,sqlx::query=debug`.
+	pub filter: String,
+	// Statements slower than this are logged at `warn` with their timing.
+	pub slow_query_ms: u64
+}
+
+impl Default for LoggingConfig {
+	fn default() -> Self {
+		Self {
+			format: LogFormat::Pretty,
+			filter: "info,sqlx::postgres::notice=warn".to_owned(),
+			slow_query_ms: 1000
+		}
+	}
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct MetricsConfig {
+	// Serves the Prometheus exposition at `/metrics` when set. It shares the
+	// API listener, so only turn it on where that port isn't public.
+	pub enabled: bool,
+	// Products with less stock than this are counted by `products_low_stock`.
+	pub low_stock_threshold: i32,
+	// Scrapes within this many seconds of the last count reuse it.
+	pub low_stock_refresh_seconds: u64
+}
+
+impl Default for MetricsConfig {
+	fn default() -> Self {
+		Self {
+			enabled: false,
+			low_stock_threshold: 5,
+			low_stock_refresh_seconds: 60
+		}
+	}
+}
+
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct AppConfig {
+	pub server: ServerConfig,
+	pub database: DatabaseConfig,
+	pub jwt: JwtConfig,
+	pub login_throttle: LoginThrottleConfig,
+	pub password: PasswordConfig,
+	pub password_reset: PasswordResetConfig,
+	pub two_factor: TwoFactorConfig,
+	pub oidc: OidcConfig,
+	pub rate_limit: RateLimitConfig,
+	pub uploads: UploadConfig,
+	pub logging: LoggingConfig,
+	pub metrics: MetricsConfig
+}
+
+fn env_var(name: &str) -> Option<String> {
+	std::env::var(name).ok().filter(|val| !val.is_empty())
+}
+
+fn env_parse<T: std::str::FromStr>(name: &str, target: &mut T) -> Result<(), ConfigError>
+where T::Err: fmt::Display {
+	if let Some(val) = env_var(name) {
+		*target = val.parse::<T>().map_err(|e| ConfigError::Env(name.to_owned(), e.to_string()))?;
+	}
+
+	Ok(())
+}
+
+// Whether this build includes the driver for the scheme of `url`.
+fn driver_enabled(url: &str) -> bool {
+	match url.split_once(':').map(|d| d.0) {
+		Some("postgres" | "postgresql") => cfg!(feature = "postgres"),
+		Some("mysql") => cfg!(feature = "mysql"),
+		Some("sqlite") => cfg!(feature = "sqlite"),
+		_ => false
+	}
+}
+
+impl AppConfig {
+	// Layers built-in defaults, the TOML file at `APP_CONFIG` (default
+	// `config/app.toml`, optional unless set explicitly) and environment
+	// variables, then validates the result. `.env` is honoured when present.
+	pub fn load() -> Result<Self, ConfigError> {
+		dotenvy::dotenv().ok();
+
+		let mut config = match env_var("APP_CONFIG") {
+			Some(path) => Self::from_file(&path)?,
+			None if std::path::Path::new(DEFAULT_CONFIG_PATH).exists() => Self::from_file(DEFAULT_CONFIG_PATH)?,
+			None => Self::default()
+		};
+
+		config.apply_env()?;
+		config.validate()?;
+
+		Ok(config)
+	}
+
+	pub fn from_file(path: &str) -> Result<Self, ConfigError> {
+		let contents = std::fs::read_to_string(path)
+		.map_err(|e| ConfigError::File(path.to_owned(), e.to_string()))?;
+
+		toml::from_str(&contents).map_err(|e| ConfigError::File(path.to_owned(), e.to_string()))
+	}
+
+	fn apply_env(&mut self) -> Result<(), ConfigError> {
+		env_parse("SERVER_ADDRESS", &mut self.server.address)?;
+
+		if let Some(val) = env_var("CORS_ORIGINS") {
+			self.server.cors_origins = val.split(',').map(|d| d.trim().to_owned()).filter(|d| !d.is_empty()).collect();
+		}
+
+		env_parse("TRUST_FORWARDED_FOR", &mut self.server.trust_forwarded_for)?;
+		env_parse("TRUSTED_PROXIES", &mut self.server.trusted_proxies)?;
+		env_parse("SHUTDOWN_DELAY_SECONDS", &mut self.server.shutdown_delay_seconds)?;
+		env_parse("SHUTDOWN_TIMEOUT_SECONDS", &mut self.server.shutdown_timeout_seconds)?;
+
+		env_parse("DATABASE_URL", &mut self.database.url)?;
+		env_parse("DATABASE_MAX_CONNECTIONS", &mut self.database.max_connections)?;
+		env_parse("DATABASE_AUTO_MIGRATE", &mut self.database.auto_migrate)?;
+
+		env_parse("JWT_ALGORITHM", &mut self.jwt.algorithm)?;
+		env_parse("JWT_SECRET", &mut self.jwt.secret)?;
+
+		if let Some(val) = env_var("JWT_ACCEPT_HS256_UNTIL") {
+			self.jwt.accept_hs256_until = Some(val.parse().map_err(|e: chrono::ParseError| {
+				ConfigError::Env("JWT_ACCEPT_HS256_UNTIL".to_owned(), e.to_string())
+			})?);
+		}
+
+		env_parse("JWT_KEYS_DIR", &mut self.jwt.keys_dir)?;
+		env_parse("JWT_MAX_KEYS", &mut self.jwt.max_keys)?;
+		env_parse("JWT_ISSUER", &mut self.jwt.issuer)?;
+		env_parse("JWT_AUDIENCE", &mut self.jwt.audience)?;
+		env_parse("JWT_TOKEN_LIFETIME_SECONDS", &mut self.jwt.token_lifetime_seconds)?;
+		env_parse("JWT_REFRESH_LIFETIME_SECONDS", &mut self.jwt.refresh_lifetime_seconds)?;
+
+		env_parse("LOGIN_MAX_FAILURES", &mut self.login_throttle.max_failures)?;
+		env_parse("LOGIN_IP_MAX_FAILURES", &mut self.login_throttle.ip_max_failures)?;
+		env_parse("LOGIN_LOCKOUT_SECONDS", &mut self.login_throttle.lockout_seconds)?;
+
+		env_parse("PASSWORD_ALGORITHM", &mut self.password.algorithm)?;
+		env_parse("PASSWORD_MIN_LENGTH", &mut self.password.min_length)?;
+		env_parse("PASSWORD_HISTORY_SIZE", &mut self.password.history_size)?;
+		env_parse("PASSWORD_BREACHED_LIST", &mut self.password.breached_list_path)?;
+
+		env_parse("PASSWORD_RESET_TOKEN_LIFETIME_SECONDS", &mut self.password_reset.token_lifetime_seconds)?;
+
+		env_parse("TWO_FACTOR_ISSUER", &mut self.two_factor.issuer)?;
+
+		if let Some(val) = env_var("TWO_FACTOR_REQUIRED_ROLES") {
+			self.two_factor.required_roles = val.split(',').map(|d| d.trim().to_owned()).filter(|d| !d.is_empty()).collect();
+		}
+
+		env_parse("OIDC_ENABLED", &mut self.oidc.enabled)?;
+		env_parse("OIDC_ISSUER", &mut self.oidc.issuer)?;
+		env_parse("OIDC_CLIENT_ID", &mut self.oidc.client_id)?;
+		env_parse("OIDC_CLIENT_SECRET", &mut self.oidc.client_secret)?;
+		env_parse("OIDC_REDIRECT_URI", &mut self.oidc.redirect_uri)?;
+		env_parse("OIDC_AUTO_PROVISION", &mut self.oidc.auto_provision)?;
+
+		env_parse("RATE_LIMIT_ENABLED", &mut self.rate_limit.enabled)?;
+
+		env_parse("UPLOAD_ROOT", &mut self.uploads.root)?;
+		env_parse("UPLOAD_MAX_REQUEST_BYTES", &mut self.uploads.max_request_bytes)?;
+		env_parse("FILE_SIGNING_SECRET", &mut self.uploads.signing_secret)?;
+		env_parse("FILE_URL_TTL_SECONDS", &mut self.uploads.signed_url_ttl_seconds)?;
+
+		env_parse("LOG_FORMAT", &mut self.logging.format)?;
+		env_parse("RUST_LOG", &mut self.logging.filter)?;
+		env_parse("LOG_SLOW_QUERY_MS", &mut self.logging.slow_query_ms)?;
+
+		env_parse("METRICS_ENABLED", &mut self.metrics.enabled)?;
+		env_parse("METRICS_LOW_STOCK_THRESHOLD", &mut self.metrics.low_stock_threshold)?;
+		env_parse("METRICS_LOW_STOCK_REFRESH_SECONDS", &mut self.metrics.low_stock_refresh_seconds)?;
+
+		Ok(())
+	}
+
+	pub fn validate(&self) -> Result<(), ConfigError> {
+		if self.database.url.is_empty() {
+			return Err(ConfigError::Invalid("DATABASE_URL is not set.".to_owned()));
+		}
+
+		if !driver_enabled(&self.database.url) {
+			return Err(ConfigError::Invalid(
+				"DATABASE_URL needs a postgres, mysql or sqlite driver enabled as a cargo feature.".to_owned()
+			));
+		}
+
+		if self.jwt.algorithm == JwtAlgorithm::HS256 && self.jwt.secret.is_empty() {
+			return Err(ConfigError::Invalid("JWT_SECRET is not set.".to_owned()));
+		}
+
+		if self.jwt.accept_hs256_until.is_some() && self.jwt.secret.is_empty() {
+			return Err(ConfigError::Invalid("JWT_ACCEPT_HS256_UNTIL needs the JWT_SECRET those tokens were signed with.".to_owned()));
+		}
+
+		if self.jwt.algorithm != JwtAlgorithm::HS256 && self.jwt.keys_dir.is_empty() {
+			return Err(ConfigError::Invalid("JWT_KEYS_DIR is not set.".to_owned()));
+		}
+
+		if self.jwt.issuer.is_empty() || self.jwt.audience.is_empty() {
+			return Err(ConfigError::Invalid("jwt.issuer and jwt.audience must be set.".to_owned()));
+		}
+
+		if self.jwt.max_keys == 0 {
+			return Err(ConfigError::Invalid("jwt.max_keys must be at least 1.".to_owned()));
+		}
+
+		// Any bucket can hold private files, so their URLs always need a key of their own.
+		if self.uploads.signing_secret.is_empty() {
+			return Err(ConfigError::Invalid("FILE_SIGNING_SECRET is not set.".to_owned()));
+		}
+
+		if self.uploads.signing_secret == self.jwt.secret {
+			return Err(ConfigError::Invalid("FILE_SIGNING_SECRET must differ from JWT_SECRET.".to_owned()));
+		}
+
+		if self.server.trust_forwarded_for && self.server.trusted_proxies == 0 {
+			return Err(ConfigError::Invalid("server.trusted_proxies must be at least 1 to trust X-Forwarded-For.".to_owned()));
+		}
+
+		if self.database.max_connections == 0 {
+			return Err(ConfigError::Invalid("database.max_connections must be at least 1.".to_owned()));
+		}
+
+		if self.login_throttle.max_failures == 0 || self.login_throttle.ip_max_failures == 0 {
+			return Err(ConfigError::Invalid("login_throttle max failures must be at least 1.".to_owned()));
+		}
+
+		if self.password.argon2_iterations == 0
+		|| self.password.argon2_parallelism == 0
+		|| self.password.argon2_memory_kib < 8 * self.password.argon2_parallelism {
+			return Err(ConfigError::Invalid("password argon2 parameters are out of range.".to_owned()));
+		}
+
+		if !(4..=31).contains(&self.password.bcrypt_cost) {
+			return Err(ConfigError::Invalid("password.bcrypt_cost must be between 4 and 31.".to_owned()));
+		}
+
+		if self.password.min_length == 0 || self.password.min_length > self.password.max_length {
+			return Err(ConfigError::Invalid("password.min_length must be between 1 and password.max_length.".to_owned()));
+		}
+
+		if self.password_reset.token_lifetime_seconds == 0 {
+			return Err(ConfigError::Invalid("password_reset.token_lifetime_seconds must be positive.".to_owned()));
+		}
+
+		if self.two_factor.challenge_lifetime_seconds == 0 {
+			return Err(ConfigError::Invalid("two_factor.challenge_lifetime_seconds must be positive.".to_owned()));
+		}
+
+		if self.oidc.enabled {
+			if self.oidc.issuer.is_empty() || self.oidc.client_id.is_empty() || self.oidc.redirect_uri.is_empty() {
+				return Err(ConfigError::Invalid("OIDC_ISSUER, OIDC_CLIENT_ID and OIDC_REDIRECT_URI must be set.".to_owned()));
+			}
+
+			if !self.oidc.scopes.iter().any(|d| d == "openid") {
+				return Err(ConfigError::Invalid("oidc.scopes must include openid.".to_owned()));
+			}
+
+			if self.oidc.username_claim.is_empty() || self.oidc.login_timeout_seconds == 0 {
+				return Err(ConfigError::Invalid("oidc.username_claim and oidc.login_timeout_seconds must be set.".to_owned()));
+			}
+		}
+
+		if let Some(directory) = FileBuckets::from_config(&self.uploads).shared_directory() {
+			return Err(ConfigError::Invalid(format!("uploads buckets must not share a directory; {directory} is used by several.")));
+		}
+
+		if self.uploads.signed_url_ttl_seconds <= 0 {
+			return Err(ConfigError::Invalid("uploads.signed_url_ttl_seconds must be positive.".to_owned()));
+		}
+
+		let mut names: Vec<&str> = self.uploads.buckets.iter().map(|d| d.name.as_str()).collect();
+		names.sort();
+
+		if let Some(name) = names.windows(2).find(|d| d[0] == d[1]) {
+			return Err(ConfigError::Invalid(format!("file bucket {} is registered twice.", name[0])));
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,88 @@
+use axum::{
+	extract::{ Path, State }, http::StatusCode, Extension, Json
+};
+
+use serde_json::json;
+
+use crate::model::api_key_model::{ ApiKeyData, CreateApiKeyBody, CreatedApiKeyResponse };
+use crate::model::message_model::MessageResponse;
+use crate::model::user_model::JwtClaims;
+use crate::service::{ api_key_service::ApiKeyService, ServiceError };
+
+#[utoipa::path(
+	get,
+	path = "/api/api-keys",
+	tag = "api key",
+	security(("bearer_auth" = [])),
+	responses(
+		(status = 200, description = "Every API key, newest first, without the keys themselves", body = Vec<ApiKeyData>),
+		(status = 403, description = "Caller is not an admin", body = MessageResponse)
+	)
+)]
+pub async fn find_many(
+	State(api_keys): State<ApiKeyService>,
+	Extension(claims): Extension<JwtClaims>
+) -> Result<(StatusCode, String), ServiceError> {
+	if claims.role != "admin" {
+		return Err(ServiceError::Forbidden("Only admins can manage API keys.".to_owned()));
+	}
+
+	let query_find_many = api_keys.find_many().await?;
+
+	Ok((StatusCode::OK, json!(query_find_many).to_string()))
+}
+
+#[utoipa::path(
+	post,
+	path = "/api/api-keys",
+	tag = "api key",
+	security(("bearer_auth" = [])),
+	request_body = CreateApiKeyBody,
+	responses(
+		(status = 201, description = "Key created; it is shown only in this response", body = CreatedApiKeyResponse),
+		(status = 400, description = "Empty name, unknown scope or past expiry", body = MessageResponse),
+		(status = 403, description = "Caller is not an admin", body = MessageResponse)
+	)
+)]
+pub async fn create(
+	State(api_keys): State<ApiKeyService>,
+	Extension(claims): Extension<JwtClaims>,
+	Json(body): Json<CreateApiKeyBody>
+) -> Result<(StatusCode, String), ServiceError> {
+	if claims.role != "admin" {
+		return Err(ServiceError::Forbidden("Only admins can manage API keys.".to_owned()));
+	}
+
+	let created = api_keys.create(claims.sub, body).await?;
+
+	Ok((StatusCode::CREATED, json!(created).to_string()))
+}
+
+#[utoipa::path(
+	delete,
+	path = "/api/api-keys/{id}",
+	tag = "api key",
+	security(("bearer_auth" = [])),
+	params(("id" = i32, Path, description = "API key id")),
+	responses(
+		(status = 200, description = "Key revoked; requests using it are rejected from now on", body = MessageResponse),
+		(status = 403, description = "Caller is not an admin", body = MessageResponse),
+		(status = 404, body = MessageResponse)
+	)
+)]
+pub async fn delete(
+	State(api_keys): State<ApiKeyService>,
+	Extension(claims): Extension<JwtClaims>,
+	Path(id): Path<i32>
+) -> Result<(StatusCode, String), ServiceError> {
+	if claims.role != "admin" {
+		return Err(ServiceError::Forbidden("Only admins can manage API keys.".to_owned()));
+	}
+
+	api_keys.revoke(id).await?;
+
+	Ok((
+		StatusCode::OK,
+		json!({ "success": true, "message": "API key revoked." }).to_string()
+	))
+}
@@ -1,245 +1,174 @@
-use std::time::{SystemTime, UNIX_EPOCH};
-
 use axum::{
-	extract::State, http::{ HeaderMap, StatusCode }, response::IntoResponse, Json
+	extract::State, http::{ header, HeaderMap, HeaderName, StatusCode }, Extension, Json
 };
 
-use bcrypt::{ hash, verify, DEFAULT_COST };
-
-use sea_orm::{ActiveModelTrait, ActiveValue::Set, ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter };
 use serde_json::json;
-
-use jsonwebtoken::{ encode, decode, DecodingKey, EncodingKey, Header, Validation };
-
-use crate::model::auth_model::{ ChangePasswordBody, LoginBody };
-
-use entity::user;
-
-use crate::model::user_model::{ UserData, JwtClaims };
-
+use std::sync::Arc;
+
+use crate::model::auth_model::{ AuthTokenResponse, ChangePasswordBody, LoginBody, ProfileResponse, UnlockBody };
+use crate::model::two_factor_model::TwoFactorChallengeResponse;
+use crate::model::user_model::JwtClaims;
+use crate::model::message_model::MessageResponse;
+use crate::service::{ auth_service::{ AuthService, LoginOutcome }, user_service::UserService, ServiceError };
+use crate::utils::client_ip::{ ClientInfo, ClientIp };
+use crate::utils::jwt::JwtKeys;
+
+#[utoipa::path(
+	post,
+	path = "/api/auth/login",
+	tag = "auth",
+	request_body = LoginBody,
+	responses(
+		(status = 202, body = AuthTokenResponse),
+		(status = 200, description = "Password accepted; finish with `/api/auth/login/2fa`, or enrol first when `enrollment_required`", body = TwoFactorChallengeResponse),
+		(status = 401, body = MessageResponse),
+		(status = 429, description = "Too many failed attempts for the username or IP; see `Retry-After`", body = MessageResponse)
+	)
+)]
 pub async fn login(
-	State(db): State<DatabaseConnection>,
+	State(auth): State<AuthService>,
+	client: ClientInfo,
 	Json(body): Json<LoginBody>
-) -> impl IntoResponse {
-	let query_find_first = user::Entity::find().filter(
-		Condition::any().add(
-			user::Column::Username.eq(&body.username)
-		)
-	).one(&db).await.unwrap();
-
-	match query_find_first {
-	    Some(val) => {
-	    	let compared_password = verify(&body.password, &val.password).unwrap();
-
-	    	if compared_password == false {
-	    		(
-	    			StatusCode::UNAUTHORIZED,
-	    			json!({
-	    				"success": false,
-	    				"message": "INVALID USERNAME / PASSWORD"
-	    			}).to_string()
-	    		)
-	    	} else {
-			    let now = SystemTime::now()
-		        .duration_since(UNIX_EPOCH)
-		        .unwrap()
-		        .as_secs();
-
-		        let user_data = UserData {
-		        	id: val.id,
-		        	username: val.username,
-		        	password: String::from(""),
-		        	full_name: val.full_name,
-		        	address: val.address,
-		        	phone_number: val.phone_number,
-		        	photo: val.photo,
-		        	role: val.role,
-		        	created_at: val.created_at,
-		        	updated_at: val.updated_at
-		        };
-
-		        let jwt_claim = JwtClaims {
-		        	user_data: user_data.clone(),
-		        	exp: (now + 360000) as usize
-		        };
-
-	            dotenvy::dotenv().expect("Failed to load .env file.");
-
-				let jwt_secret = std::env::var("JWT_SECRET").unwrap();
-
-				let jwt_token = encode(&Header::default(), &jwt_claim, &EncodingKey::from_secret(jwt_secret.as_ref()))
-				.expect("Failed to Create Token");
-
-				(
-					StatusCode::ACCEPTED,
-					json!({ "success": true, "data": user_data.clone(), "token": jwt_token }).to_string()
-				)
-	    	}
-	    },
-	    None => (
-			StatusCode::UNAUTHORIZED,
-			json!({
-				"success": false,
-				"message": "INVALID USERNAME / PASSWORD"
+) -> Result<(StatusCode, String), ServiceError> {
+	Ok(login_response(auth.login(body, &client).await?))
+}
+
+// 202 with a token, or 200 with a challenge for the second factor.
+pub fn login_response(outcome: LoginOutcome) -> (StatusCode, String) {
+	match outcome {
+		LoginOutcome::Authenticated(authenticated) => (
+			StatusCode::ACCEPTED,
+			json!(AuthTokenResponse { success: true, data: authenticated.user, token: authenticated.token }).to_string()
+		),
+		LoginOutcome::Challenge { token, enrollment_required, expires_in } => (
+			StatusCode::OK,
+			json!(TwoFactorChallengeResponse {
+				success: true,
+				two_factor_required: true,
+				enrollment_required,
+				challenge_token: token,
+				expires_in
 			}).to_string()
 		)
 	}
 }
 
+#[utoipa::path(
+	post,
+	path = "/api/auth/authenticated",
+	tag = "auth",
+	security(("bearer_auth" = [])),
+	responses(
+		(status = 200, description = "A fresh token for the bearer's user", body = AuthTokenResponse),
+		(status = 401, body = MessageResponse)
+	)
+)]
 pub async fn authenticated(
+	State(auth): State<AuthService>,
 	headers: HeaderMap
-) -> impl IntoResponse {
-	let auth_header = headers.get("Authorization");
-
-    dotenvy::dotenv().expect("Failed to load .env file.");
-
-	let jwt_secret = std::env::var("JWT_SECRET").unwrap();
-
-	match auth_header {
-		Some(val) => {
-			let header_value = val.to_str();
-
-			match header_value {
-				Ok(val2) => {
-					let jwt_token = val2.strip_prefix("Bearer ");
-
-					match jwt_token {
-						Some(val3) => {
-							let now = SystemTime::now()
-							.duration_since(UNIX_EPOCH)
-							.unwrap()
-							.as_secs();
-
-							let decoded_jwt = decode::<JwtClaims>(
-								val3, 
-								&DecodingKey::from_secret(jwt_secret.as_ref()), 
-								&Validation::default()
-							);
-
-							match decoded_jwt {
-							    Ok(val4) => {
-							    	let jwt_claims = JwtClaims {
-							    		user_data: UserData { 
-							    			id: val4.claims.user_data.id, 
-							    			username: val4.claims.user_data.username, 
-							    			password: String::from(""), 
-							    			full_name: val4.claims.user_data.full_name, 
-							    			address: val4.claims.user_data.address, 
-							    			phone_number: val4.claims.user_data.phone_number, 
-							    			role: val4.claims.user_data.role, 
-							    			photo: val4.claims.user_data.photo, 
-							    			created_at: val4.claims.user_data.created_at, 
-							    			updated_at: val4.claims.user_data.updated_at 
-							    		},
-							    		exp: (now + 36000000) as usize
-							    	};
-
-
-			    	    			let new_jwt_token = encode(&Header::default(), &jwt_claims, &EncodingKey::from_secret(jwt_secret.as_ref()))
-									.expect("Failed to Create Token");
-
-									(
-										StatusCode::OK,
-										json!({ "success": true, "data": jwt_claims.user_data,  "token": new_jwt_token }).to_string()
-									)
-							    },
-							    Err(_) => {
-							    	(
-										StatusCode::UNAUTHORIZED,
-										json!({
-											"success": false,
-											"message": "INVALID CREDENTIALS."
-										}).to_string()
-									)
-							    }
-							}
-						},
-						None => (
-							StatusCode::UNAUTHORIZED,
-							json!({
-								"success": false,
-								"message": "Failed to Strip Bearer Prefix."
-							}).to_string()
-						)
-					}
-				},
-				Err(_) => (
-					StatusCode::UNAUTHORIZED,
-					json!({
-						"success": false,
-						"message": "INVALID CREDENTIALS."
-					}).to_string()
-				)
-			}
-		},
-		None =>  (
-			StatusCode::UNAUTHORIZED,
-			json!({
-				"success": false,
-				"message": "INVALID CREDENTIALS."
-			}).to_string()
-		)
-	}
+) -> Result<(StatusCode, String), ServiceError> {
+	let header_value = headers.get("Authorization")
+	.and_then(|val| val.to_str().ok())
+	.ok_or_else(|| ServiceError::Unauthorized("INVALID CREDENTIALS.".to_owned()))?;
+
+	let jwt_token = header_value.strip_prefix("Bearer ")
+	.ok_or_else(|| ServiceError::Unauthorized("Failed to Strip Bearer Prefix.".to_owned()))?;
+
+	let authenticated = auth.refresh(jwt_token).await?;
+
+	Ok((
+		StatusCode::OK,
+		json!(AuthTokenResponse { success: true, data: authenticated.user, token: authenticated.token }).to_string()
+	))
+}
+
+// Tokens only carry the id and role; the rest of the profile comes from here.
+#[utoipa::path(
+	get,
+	path = "/api/auth/me",
+	tag = "auth",
+	security(("bearer_auth" = [])),
+	responses(
+		(status = 200, description = "The bearer's current profile; the password is blank", body = ProfileResponse),
+		(status = 401, body = MessageResponse)
+	)
+)]
+pub async fn me(
+	State(users): State<UserService>,
+	Extension(claims): Extension<JwtClaims>
+) -> Result<(StatusCode, String), ServiceError> {
+	let user = users.find_first(claims.sub).await?;
+
+	Ok((StatusCode::OK, json!(ProfileResponse { success: true, data: user }).to_string()))
 }
 
+#[utoipa::path(
+	post,
+	path = "/api/auth/change-password",
+	tag = "auth",
+	request_body = ChangePasswordBody,
+	responses(
+		(status = 200, body = MessageResponse),
+		(status = 400, body = MessageResponse),
+		(status = 404, body = MessageResponse),
+		(status = 429, body = MessageResponse)
+	)
+)]
 pub async fn change_password(
-	State(db): State<DatabaseConnection>,
+	State(auth): State<AuthService>,
+	ClientIp(ip): ClientIp,
 	Json(body): Json<ChangePasswordBody>
-) -> impl IntoResponse {
-	let query_find_first = user::Entity::find().filter(
-		Condition::any().add(
-			user::Column::Id.eq(body.id)
-		)
-	).one(&db).await;
-
-	match query_find_first {
-		Ok(result) => {
-			match result {
-			    Some(val) => {
-			    	let compare_password = verify(body.old_password, &val.password);
-
-			    	match compare_password {
-			    		Ok(val2) => {
-			    			if val2 == true {
-			    				let new_password = hash(body.new_password, DEFAULT_COST).unwrap();
-
-			    				let mut user_model: user::ActiveModel = val.into();
-
-			    				user_model.password = Set(new_password.to_owned());
-			    				user_model.updated_at = Set(chrono::Utc::now().naive_utc());
-
-			    				match user_model.update(&db).await {
-			    					Ok(_) => (
-			    						StatusCode::OK,
-			    						json!({ "success": true, "message": "Your password has been updated." }).to_string()
-			    					),
-			    					Err(_) => (
-			    						StatusCode::BAD_REQUEST,
-			    						json!({ "success": false, "message": "Request Failed." }).to_string()
-			    					)
-			    				}
-			    			} else {
-			    				(
-			    					StatusCode::BAD_REQUEST,
-			    					json!({ "success": false, "message": "Old password didn't matc!!!" }).to_string()
-			    				)
-			    			}
-			    		},
-			    		Err(_) => (
-			    			StatusCode::BAD_REQUEST,
-			    			json!({ "success": false, "message": "Failed during validation." }).to_string()
-			    		)
-			    	}
-			    },
-			    None => (
-			    	StatusCode::NOT_FOUND,
-			    	json!({ "success": false, "message": "Data User tidak ditemukan" }).to_string()
-			    )
-			}
-		},
-		Err(_) => (
-			StatusCode::NOT_FOUND,
-			json!({ "success": false, "message": "Data User tidak ditemukan." }).to_string()
-		)
+) -> Result<(StatusCode, String), ServiceError> {
+	auth.change_password(body, ip).await?;
+
+	Ok((
+		StatusCode::OK,
+		json!({ "success": true, "message": "Your password has been updated." }).to_string()
+	))
+}
+
+#[utoipa::path(
+	post,
+	path = "/api/auth/unlock",
+	tag = "auth",
+	security(("bearer_auth" = [])),
+	request_body = UnlockBody,
+	responses(
+		(status = 200, description = "Failed login count and lockout cleared", body = MessageResponse),
+		(status = 403, description = "Caller is not an admin", body = MessageResponse)
+	)
+)]
+pub async fn unlock(
+	State(auth): State<AuthService>,
+	Extension(claims): Extension<JwtClaims>,
+	Json(body): Json<UnlockBody>
+) -> Result<(StatusCode, String), ServiceError> {
+	if claims.role != "admin" {
+		return Err(ServiceError::Forbidden("Only admins can unlock logins.".to_owned()));
 	}
-}
\ No newline at end of file
+
+	let cleared = auth.unlock(body).await?;
+
+	Ok((
+		StatusCode::OK,
+		json!({ "success": true, "message": format!("Cleared {cleared} login lock(s).") }).to_string()
+	))
+}
+
+// Public keys for verifying tokens elsewhere. Caches may keep them for a
+// while, so rotate well before retiring a key.
+#[utoipa::path(
+	get,
+	path = "/.well-known/jwks.json",
+	tag = "auth",
+	responses(
+		(status = 200, description = "JSON Web Key Set of every key that verifies tokens; empty with HS256")
+	)
+)]
+pub async fn jwks(State(jwt): State<Arc<JwtKeys>>) -> (StatusCode, [(HeaderName, &'static str); 2], String) {
+	(
+		StatusCode::OK,
+		[(header::CONTENT_TYPE, "application/json"), (header::CACHE_CONTROL, "public, max-age=300")],
+		json!(jwt.jwks()).to_string()
+	)
+}
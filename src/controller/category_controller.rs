@@ -1,181 +1,139 @@
 use axum::{
 	extract::{ Path, State }, 
 	http::StatusCode, 
-	response::IntoResponse, Json
+	Json
 };
 
-use sea_orm::sea_query::extension::postgres::PgExpr;
-use sea_orm::sea_query::Expr;
-
-use sea_orm::{ActiveModelTrait, ActiveValue::Set, Condition, DatabaseConnection, EntityTrait, QueryOrder,
-QueryFilter, ColumnTrait, PaginatorTrait, QuerySelect };
 use serde_json::json;
-use crate::model::{category_model::{ CategoryCreateBody, CategoryData, CategoryUpdateBody, CategoryPaginate }, pagination_model::PaginationBody};
-use entity::category;
-use crate::model::pagination_model::PaginationResponse;
-
-pub async fn find_many(State(db): State<DatabaseConnection>) -> impl IntoResponse {
-	let query_data: Vec<CategoryData> = category::Entity::find()
-	.order_by_asc(category::Column::Name).all(&db).await.unwrap().iter().into_iter().map(|d| CategoryData {
-		id: d.id,
-		name: d.name.clone(),
-		created_at: d.created_at,
-		updated_at: d.updated_at
-	}).collect();
+use crate::model::{category_model::{ CategoryCreateBody, CategoryData, CategoryPaginate, CategoryUpdateBody }, pagination_model::PaginationBody};
+use crate::model::message_model::MessageResponse;
+use crate::service::{ category_service::CategoryService, ServiceError };
+
+#[utoipa::path(
+	get,
+	path = "/api/category",
+	tag = "category",
+	security(("bearer_auth" = []), ("api_key" = ["category:read"])),
+	responses(
+		(status = 200, description = "All categories ordered by name", body = Vec<CategoryData>)
+	)
+)]
+pub async fn find_many(State(categories): State<CategoryService>) -> Result<(StatusCode, String), ServiceError> {
+	let query_data = categories.find_many().await?;
 
-	(
+	Ok((
 		StatusCode::OK,
 		json!(query_data).to_string()
-	)
+	))
 }
 
+#[utoipa::path(
+	post,
+	path = "/api/category/search-paginate",
+	tag = "category",
+	security(("bearer_auth" = []), ("api_key" = ["category:read"])),
+	request_body = PaginationBody,
+	responses(
+		(status = 200, description = "Case-insensitive name search, 10 per page", body = CategoryPaginate)
+	)
+)]
 pub async fn search_paginate(
-	State(db): State<DatabaseConnection>,
+	State(categories): State<CategoryService>,
 	Json(body): Json<PaginationBody>
-) -> impl IntoResponse {
-	const PAGE_TAKE: i64 = 10;
-
-	let page_offset = (&body.page - 1) * PAGE_TAKE;
+) -> Result<(StatusCode, String), ServiceError> {
+	let pagination_response = categories.search_paginate(body).await?;
 
-	let query_count = category::Entity::find().filter(
-		Condition::any().add(
-			category::Column::Name.contains(&body.term)
-		)
-	).count(&db).await.unwrap();
-
-	let query_search: Vec<CategoryData> = category::Entity::find().filter(
-		Condition::any().add(
-			Expr::col(category::Column::Name).ilike(format!("%{}%", body.term))
-		)
-	).order_by_asc(category::Column::Name).offset(page_offset as u64).limit(PAGE_TAKE as u64).all(&db).await.unwrap().iter().into_iter().map(|d| CategoryData {
-		id: d.id,
-		name: d.name.clone(),
-		created_at: d.created_at,
-		updated_at: d.updated_at
-	}).collect();
-
-	let total_page = ((query_count as f64 / PAGE_TAKE as f64) + 0.4).round() as i64;
-
-	let pagination_response = CategoryPaginate {
-		data: query_search,
-		paginate: PaginationResponse {
-			per_page: PAGE_TAKE,
-			total_page: total_page,
-			count: query_count as i64,
-			current_page: body.page
-		}
-	};
-
-	(
+	Ok((
 		StatusCode::OK,
 		json!(pagination_response).to_string()
-	)
+	))
 }
 
-pub async fn find_first(State(db): State<DatabaseConnection>, Path(id): Path<i32>) -> impl IntoResponse {
-	let query_find_first = category::Entity::find().filter(
-		Condition::all().add(
-			category::Column::Id.eq(id)
-		)
-	).one(&db).await.unwrap();
-
-	match query_find_first {
-		Some(val) => {
-			let category_data = CategoryData {
-				id: val.id,
-				name: val.name,
-				created_at: val.created_at,
-				updated_at: val.updated_at
-			};
+#[utoipa::path(
+	get,
+	path = "/api/category/{id}",
+	tag = "category",
+	security(("bearer_auth" = []), ("api_key" = ["category:read"])),
+	params(("id" = i32, Path, description = "Category id")),
+	responses(
+		(status = 200, body = CategoryData),
+		(status = 404, body = MessageResponse)
+	)
+)]
+pub async fn find_first(
+	State(categories): State<CategoryService>,
+	Path(id): Path<i32>
+) -> Result<(StatusCode, String), ServiceError> {
+	let category_data = categories.find_first(id).await?;
 
-			(
-				StatusCode::OK,
-				json!(category_data).to_string()
-			)
-		},
-		None => (
-			StatusCode::NOT_FOUND,
-			json!({ "message": "Data Not Found!!!" }).to_string()
-		)
-	}
+	Ok((
+		StatusCode::OK,
+		json!(category_data).to_string()
+	))
 }
 
-pub async fn create(State(db): State<DatabaseConnection>,
+#[utoipa::path(
+	post,
+	path = "/api/category",
+	tag = "category",
+	security(("bearer_auth" = []), ("api_key" = ["category:write"])),
+	request_body = CategoryCreateBody,
+	responses(
+		(status = 202, body = MessageResponse)
+	)
+)]
+pub async fn create(State(categories): State<CategoryService>,
 	Json(body): Json<CategoryCreateBody>
-) -> impl IntoResponse {
-	let category_data = category::ActiveModel {
-		name: Set(body.name.to_owned()),
-		..Default::default()
-	};
+) -> Result<(StatusCode, String), ServiceError> {
+	categories.create(body).await?;
 
-	match category_data.insert(&db).await {
-	    Ok(_) => {
-	    	(StatusCode::ACCEPTED, json!({
-	    		"success": true,
-	    		"message": "Category Data was Created"
-	    	}).to_string())
-	    },
-	    Err(e) => {
-	    	(StatusCode::INTERNAL_SERVER_ERROR, json!({
-	    		"success": false,
-	    		"message": e.to_string()
-	    	}).to_string())
-	    }
-	}
+	Ok((StatusCode::ACCEPTED, json!({
+		"success": true,
+		"message": "Category Data was Created"
+	}).to_string()))
 }
 
-pub async fn update(State(db): State<DatabaseConnection>,
+#[utoipa::path(
+	put,
+	path = "/api/category/{id}",
+	tag = "category",
+	security(("bearer_auth" = []), ("api_key" = ["category:write"])),
+	params(("id" = i32, Path, description = "Category id")),
+	request_body = CategoryUpdateBody,
+	responses(
+		(status = 200, body = MessageResponse),
+		(status = 404, body = MessageResponse)
+	)
+)]
+pub async fn update(State(categories): State<CategoryService>,
 	Path(id): Path<i32>, Json(body): Json<CategoryUpdateBody>
-) -> impl IntoResponse {
-	let update_category_data = category::Entity::find_by_id(id).one(&db).await.unwrap();
-
-	match update_category_data {
-	    Some(val) => {
-	    	let mut category_model: category::ActiveModel = val.into();
+) -> Result<(StatusCode, String), ServiceError> {
+	categories.update(id, body).await?;
 
-	    	category_model.name = Set(body.name.unwrap().to_owned());
-	    	category_model.updated_at = Set(chrono::Utc::now().naive_utc()); // I want to use Current NaiveDateTime
-
-	    	match category_model.update(&db).await {
-	    	    Ok(_) => (
-		    		StatusCode::OK,
-		    		json!({ "success": true, "message": "Category Data was Updated." }).to_string()
-		    	),
-	    	    Err(_) => (
-	    	    	StatusCode::BAD_REQUEST,
-	    	    	json!({ "success": false, "message": "Failed to Update Category Data." }).to_string()
-	    	    )
-	    	}
-	    },
-	    None => (
-	    	StatusCode::NOT_FOUND,
-	    	json!({ "success": false, "message": "Data Not Found!!!"}).to_string()
-	    )
-	}
+	Ok((
+		StatusCode::OK,
+		json!({ "success": true, "message": "Category Data was Updated." }).to_string()
+	))
 }
 
-pub async fn delete(State(db): State<DatabaseConnection>,
+#[utoipa::path(
+	delete,
+	path = "/api/category/{id}",
+	tag = "category",
+	security(("bearer_auth" = []), ("api_key" = ["category:write"])),
+	params(("id" = i32, Path, description = "Category id")),
+	responses(
+		(status = 200, body = MessageResponse),
+		(status = 404, body = MessageResponse)
+	)
+)]
+pub async fn delete(State(categories): State<CategoryService>,
 	Path(id): Path<i32>
-) -> impl IntoResponse {
-	let query_delete_result = category::Entity::delete_by_id(id).exec(&db).await;
+) -> Result<(StatusCode, String), ServiceError> {
+	categories.delete(id).await?;
 
-	match query_delete_result {
-		Ok(val) => {
-			if val.rows_affected == 0 {
-				(
-					StatusCode::NOT_FOUND,
-					json!({ "success": false, "message": "Data Not Found!!!." }).to_string()
-				)
-			} else {
-				(
-					StatusCode::OK,
-					json!({ "success": true, "message": "Category Data was Deleted." }).to_string()
-				)
-			}
-		},
-		Err(e) => (
-			StatusCode::INTERNAL_SERVER_ERROR,
-			json!({ "success": false, "message": e.to_string() }).to_string()
-		)
-	}
-}
\ No newline at end of file
+	Ok((
+		StatusCode::OK,
+		json!({ "success": true, "message": "Category Data was Deleted." }).to_string()
+	))
+}
@@ -1,177 +1,275 @@
 use axum:: {
-	extract::{ Multipart, Path },
-	http::{ StatusCode },
-	response::{ IntoResponse, Response }
+	extract::{ Multipart, Path, Query, State },
+	http::{ Extensions, StatusCode },
+	response::{ IntoResponse, Response },
+	Json
 };
 use reqwest::header;
 use serde_json::json;
-use std::{ fs, path::PathBuf };
-use tokio::{ fs::File, io::{ AsyncReadExt, AsyncWriteExt } };
+use std::collections::HashMap;
 
-// User Files Controller
-pub async fn upload_user_image(mut multipart: Multipart) -> impl IntoResponse {
-	let upload_dir = "uploads/user";
-
-	fs::create_dir_all(upload_dir).unwrap();
-
-	let field = multipart.next_field().await.unwrap();
-
-	match field {
-		Some(mut val) => {
-			let file_name = val.file_name().map(|name| name.to_string());
-
-			match file_name {
-				Some(val2) => {
-					let ext = val2.split(".").last().unwrap_or("");
-					let timestamp = chrono::Utc::now().timestamp();
-
-					let new_file_name = if ext == "jfif" {
-						format!("{timestamp}_{}", val2.split(".").next().unwrap())
-					} else {
-						format!("{timestamp}_{val2}")
-					};
-
-					let file_path = format!("{}/{}", upload_dir, new_file_name);
-
-					let mut file = File::create(&file_path).await.unwrap();
-
-					while let Some(chunk) = val.chunk().await.unwrap() {
-						file.write_all(&chunk).await.unwrap();
-					}
-
-					(
-						StatusCode::OK,
-						json!({ "file_name": new_file_name, "file_extension": ext }).to_string()
-					)
-				},
-				None => (
-					StatusCode::BAD_REQUEST,
-					"Something wrong happened".to_string()
-				)
-			}
-		},
-		None => (
-			StatusCode::BAD_REQUEST,
-			"No File to Upload!!!".to_string()
-		)
+use crate::model::file_model::{
+	BatchUploadResponse, FileUploadForm, FileUploadResult, FileVisibilityBody, SignedFileUrl, SignedUrlQuery
+};
+use crate::model::api_key_model::ApiKeyPrincipal;
+use crate::model::message_model::MessageResponse;
+use crate::model::user_model::JwtClaims;
+use crate::service::{ file_service::FileService, ServiceError };
+use crate::utils::file_store::served_inline;
+
+fn failed_result(field_name: String, original_name: String, message: String) -> FileUploadResult {
+	FileUploadResult {
+		field_name,
+		original_name,
+		success: false,
+		message: Some(message),
+		file: None,
+		image: None
 	}
 }
 
-pub async fn get_user_image(Path(filename): Path<String>) -> Response {
-	let file_path = PathBuf::from(format!("uploads/user/{}", filename));
-
-	if file_path.exists() {
-		match File::open(&file_path).await {
-		    Ok(mut file) => {
-		    	let mut contents = Vec::new();
-
-		    	if file.read_to_end(&mut contents).await.is_ok() {
-		    		return Response::builder()
-		    		.status(StatusCode::OK)
-		    		.header(header::CONTENT_TYPE, "image/*")
-		    		.body(axum::body::Body::from(contents))
-		    		.unwrap();
-		    	}
-		    },
-		    Err(_) => {}
-		}
+// Stored files carry no owner, so handing out access to private ones or
+// changing who may see them is kept to admins. API keys got here with the
+// `file:write` scope, which only admins grant.
+fn require_file_admin(extensions: &Extensions) -> Result<(), ServiceError> {
+	match extensions.get::<JwtClaims>() {
+		Some(claims) if claims.role != "admin" => {
+			Err(ServiceError::Forbidden("Only admins can sign files or change their visibility.".to_owned()))
+		},
+		_ => Ok(())
 	}
-
-	(StatusCode::NOT_FOUND, "File Not Found.").into_response()
 }
 
-pub async fn delete_user_image(Path(filename): Path<String>) -> impl IntoResponse {
-	let file_path = format!("uploads/user/{}", filename);
-
-	if filename != "default_user.png" && std::path::Path::new(&file_path).exists() {
-		fs::remove_file(file_path).unwrap();
-
-		(StatusCode::OK, "Files was Deleted.")
-	} else {
-		(StatusCode::NOT_FOUND, "File not found or cannot deleted the default file.")
-	}
+// Attaching to a gallery edits the product, which `file:write` alone doesn't allow.
+fn may_attach_to_products(extensions: &Extensions) -> bool {
+	extensions.get::<ApiKeyPrincipal>().is_none_or(|principal| principal.scopes.iter().any(|d| d == "product:write"))
 }
 
-// Product Files Controller
-pub async fn upload_product_image(mut multipart: Multipart) -> impl IntoResponse {
-	let upload_dir = "uploads/product";
+// Processes every multipart field in arrival order. Plain form fields
+// (`product_id`, `alt_text`, `is_primary`, `visibility`) apply to the files that follow them,
+// so clients must send them before the files they describe. A failing file is
+// reported in the result list without aborting the rest of the batch.
+// API keys need `product:write` as well for files sent after a `product_id`.
+#[utoipa::path(
+	post,
+	path = "/api/files/{bucket}",
+	tag = "files",
+	security(("bearer_auth" = []), ("api_key" = ["file:write"])),
+	params(("bucket" = String, Path, description = "File bucket, e.g. `product` or `user`")),
+	request_body(content = FileUploadForm, content_type = "multipart/form-data"),
+	responses(
+		(status = 200, description = "Every file was stored", body = BatchUploadResponse),
+		(status = 207, description = "Some files failed; see `results`", body = BatchUploadResponse),
+		(status = 400, description = "No file was stored", body = BatchUploadResponse),
+		(status = 404, description = "Unknown bucket", body = MessageResponse)
+	)
+)]
+pub async fn upload(
+	State(files): State<FileService>,
+	Path(bucket): Path<String>,
+	extensions: Extensions,
+	mut multipart: Multipart
+) -> Result<(StatusCode, String), ServiceError> {
+	let bucket = files.bucket(&bucket)?;
+	let may_attach = may_attach_to_products(&extensions);
+
+	let mut form: HashMap<String, String> = HashMap::new();
+	let mut results: Vec<FileUploadResult> = Vec::new();
+
+	loop {
+		let field = match multipart.next_field().await {
+			Ok(Some(field)) => field,
+			Ok(None) => break,
+			Err(e) => {
+				results.push(failed_result(String::new(), String::new(), e.to_string()));
+				break;
+			}
+		};
+
+		let field_name = field.name().unwrap_or("").to_string();
+
+		let original_name = match field.file_name() {
+			Some(name) => name.to_string(),
+			None => {
+				match field.text().await {
+					Ok(value) => {
+						form.insert(field_name, value);
+					},
+					Err(e) => {
+						results.push(failed_result(field_name, String::new(), e.to_string()));
+						break;
+					}
+				}
 
-	fs::create_dir_all(upload_dir).unwrap();
+				continue;
+			}
+		};
 
-	let field = multipart.next_field().await.unwrap();
+		if !may_attach && bucket.name == "product" && form.contains_key("product_id") {
+			results.push(failed_result(field_name, original_name, "The API key lacks the product:write scope.".to_owned()));
+			continue;
+		}
 
-	match field {
-		Some(mut val) => {
-			let file_name = val.file_name().map(|name| name.to_string());
+		match files.upload(bucket, &form, &original_name, field).await {
+			Ok((file, image)) => results.push(FileUploadResult {
+				field_name,
+				original_name,
+				success: true,
+				message: None,
+				file: Some(file),
+				image
+			}),
+			Err(e) => results.push(failed_result(field_name, original_name, e.message().to_owned()))
+		}
+	}
 
-			match file_name {
-				Some(val2) => {
-					let ext = val2.split(".").last().unwrap_or("");
-					let timestamp = chrono::Utc::now().timestamp();
+	if results.is_empty() {
+		return Err(ServiceError::BadRequest("No File to Upload!!!".to_owned()));
+	}
 
-					let new_file_name = if ext == "jfif" {
-						format!("{timestamp}_{}", val2.split(".").next().unwrap())
-					} else {
-						format!("{timestamp}_{val2}")
-					};
+	let uploaded = results.iter().filter(|d| d.success).count();
+	let failed = results.len() - uploaded;
 
-					let file_path = format!("{}/{}", upload_dir, new_file_name);
+	let status = if failed == 0 {
+		StatusCode::OK
+	} else if uploaded == 0 {
+		StatusCode::BAD_REQUEST
+	} else {
+		StatusCode::MULTI_STATUS
+	};
 
-					let mut file = File::create(&file_path).await.unwrap();
+	let response = BatchUploadResponse {
+		success: failed == 0,
+		uploaded,
+		failed,
+		results
+	};
 
-					while let Some(chunk) = val.chunk().await.unwrap() {
-						file.write_all(&chunk).await.unwrap();
-					}
+	Ok((status, json!(response).to_string()))
+}
 
-					(
-						StatusCode::OK,
-						json!({ "file_name": new_file_name, "file_extension": ext }).to_string()
-					)
-				},
-				None => (
-					StatusCode::BAD_REQUEST,
-					"Something wrong happened".to_string()
-				)
-			}
-		},
-		None => (
-			StatusCode::BAD_REQUEST,
-			"No File to Upload!!!".to_string()
-		)
-	}
+#[utoipa::path(
+	delete,
+	path = "/api/files/{bucket}/delete/{filename}",
+	tag = "files",
+	security(("bearer_auth" = []), ("api_key" = ["file:write"])),
+	params(
+		("bucket" = String, Path, description = "File bucket, e.g. `product` or `user`"),
+		("filename" = String, Path, description = "Stored file name")
+	),
+	responses(
+		(status = 200, body = MessageResponse),
+		(status = 404, body = MessageResponse),
+		(status = 409, description = "The file is still used by a product, gallery image or user photo", body = MessageResponse)
+	)
+)]
+pub async fn delete(
+	State(files): State<FileService>,
+	Path((bucket, filename)): Path<(String, String)>
+) -> Result<(StatusCode, String), ServiceError> {
+	files.delete(files.bucket(&bucket)?, &filename).await?;
+
+	Ok((
+		StatusCode::OK,
+		json!({ "success": true, "message": "Files was Deleted." }).to_string()
+	))
 }
 
-pub async fn get_product_image(Path(filename): Path<String>) -> Response {
-	let file_path = PathBuf::from(format!("uploads/product/{}", filename));
-
-	if file_path.exists() {
-		match File::open(&file_path).await {
-		    Ok(mut file) => {
-		    	let mut contents = Vec::new();
-
-		    	if file.read_to_end(&mut contents).await.is_ok() {
-		    		return Response::builder()
-		    		.status(StatusCode::OK)
-		    		.header(header::CONTENT_TYPE, "image/*")
-		    		.body(axum::body::Body::from(contents))
-		    		.unwrap();
-		    	}
-		    },
-		    Err(_) => {}
-		}
+#[utoipa::path(
+	get,
+	path = "/api/files/{bucket}/image/{filename}",
+	tag = "files",
+	params(
+		("bucket" = String, Path, description = "File bucket, e.g. `product` or `user`"),
+		("filename" = String, Path, description = "Stored file name"),
+		SignedUrlQuery
+	),
+	responses(
+		(status = 200, description = "File contents, or the bucket placeholder when missing", content_type = "application/octet-stream", body = Vec<u8>),
+		(status = 403, description = "Private file without a valid signature", body = MessageResponse),
+		(status = 404, description = "No such file or placeholder")
+	)
+)]
+pub async fn get(
+	State(files): State<FileService>,
+	Path((bucket, filename)): Path<(String, String)>,
+	Query(query): Query<SignedUrlQuery>
+) -> Response {
+	let bucket = match files.bucket(&bucket) {
+		Ok(val) => val,
+		Err(e) => return e.into_response()
+	};
+
+	match files.open(bucket, &filename, query.expires, query.signature.as_deref()).await {
+		Ok(file) => Response::builder()
+		.status(StatusCode::OK)
+		.header(header::CONTENT_DISPOSITION, if served_inline(&file.content_type) { "inline" } else { "attachment" })
+		.header(header::CONTENT_TYPE, file.content_type)
+		.header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+		.header(header::CONTENT_SECURITY_POLICY, "sandbox")
+		.body(axum::body::Body::from(file.contents))
+		.unwrap(),
+		Err(ServiceError::NotFound(_)) => (StatusCode::NOT_FOUND, "File Not Found.").into_response(),
+		Err(e) => e.into_response()
 	}
-
-	(StatusCode::NOT_FOUND, "File Not Found.").into_response()
 }
 
-pub async fn delete_product_image(Path(filename): Path<String>) -> impl IntoResponse {
-	let file_path = format!("uploads/product/{}", filename);
-
-	if filename != "default_user.png" && std::path::Path::new(&file_path).exists() {
-		fs::remove_file(file_path).unwrap();
+#[utoipa::path(
+	post,
+	path = "/api/files/{bucket}/sign/{filename}",
+	tag = "files",
+	security(("bearer_auth" = []), ("api_key" = ["file:write"])),
+	params(
+		("bucket" = String, Path, description = "File bucket, e.g. `product` or `user`"),
+		("filename" = String, Path, description = "Stored file name")
+	),
+	responses(
+		(status = 200, body = SignedFileUrl),
+		(status = 403, description = "Only admins and API keys with `file:write` may sign", body = MessageResponse),
+		(status = 404, body = MessageResponse)
+	)
+)]
+pub async fn sign(
+	State(files): State<FileService>,
+	extensions: Extensions,
+	Path((bucket, filename)): Path<(String, String)>
+) -> Result<(StatusCode, String), ServiceError> {
+	require_file_admin(&extensions)?;
+
+	let signed = files.sign(files.bucket(&bucket)?, &filename).await?;
+
+	Ok((StatusCode::OK, json!(signed).to_string()))
+}
 
-		(StatusCode::OK, "Files was Deleted.")
-	} else {
-		(StatusCode::NOT_FOUND, "File not found or cannot deleted the default file.")
-	}
-}
\ No newline at end of file
+#[utoipa::path(
+	put,
+	path = "/api/files/{bucket}/visibility/{filename}",
+	tag = "files",
+	security(("bearer_auth" = []), ("api_key" = ["file:write"])),
+	params(
+		("bucket" = String, Path, description = "File bucket, e.g. `product` or `user`"),
+		("filename" = String, Path, description = "Stored file name")
+	),
+	request_body = FileVisibilityBody,
+	responses(
+		(status = 200, body = MessageResponse),
+		(status = 403, description = "Only admins and API keys with `file:write` may change visibility", body = MessageResponse),
+		(status = 404, body = MessageResponse),
+		(status = 409, description = "Several records use the file", body = MessageResponse)
+	)
+)]
+pub async fn update_visibility(
+	State(files): State<FileService>,
+	extensions: Extensions,
+	Path((bucket, filename)): Path<(String, String)>,
+	Json(body): Json<FileVisibilityBody>
+) -> Result<(StatusCode, String), ServiceError> {
+	require_file_admin(&extensions)?;
+
+	files.update_visibility(files.bucket(&bucket)?, &filename, body.is_private).await?;
+
+	Ok((
+		StatusCode::OK,
+		json!({ "success": true, "message": "File Visibility was Updated." }).to_string()
+	))
+}
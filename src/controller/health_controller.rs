@@ -0,0 +1,44 @@
+use axum::{
+	extract::State, 
+	http::StatusCode
+};
+
+use serde_json::json;
+use crate::model::health_model::{ LivenessResponse, ReadinessResponse };
+use crate::service::health_service::HealthService;
+
+#[utoipa::path(
+	get,
+	path = "/health/live",
+	tag = "health",
+	responses(
+		(status = 200, description = "The process is up and serving requests", body = LivenessResponse)
+	)
+)]
+pub async fn live() -> (StatusCode, String) {
+	(
+		StatusCode::OK,
+		json!(LivenessResponse { status: "ok".to_owned() }).to_string()
+	)
+}
+
+#[utoipa::path(
+	get,
+	path = "/health/ready",
+	tag = "health",
+	responses(
+		(status = 200, description = "Database reachable and schema up to date", body = ReadinessResponse),
+		(status = 503, description = "Not ready, or shutting down", body = ReadinessResponse)
+	)
+)]
+pub async fn ready(State(health): State<HealthService>) -> (StatusCode, String) {
+	let report = health.readiness().await;
+
+	let status = if report.status == "ready" {
+		StatusCode::OK
+	} else {
+		StatusCode::SERVICE_UNAVAILABLE
+	};
+
+	(status, json!(report).to_string())
+}
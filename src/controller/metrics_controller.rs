@@ -0,0 +1,13 @@
+use axum::{
+	extract::State, http::header, response::IntoResponse
+};
+
+use crate::service::metrics_service::MetricsService;
+
+// Prometheus text exposition; not part of the OpenAPI document.
+pub async fn render(State(metrics): State<MetricsService>) -> impl IntoResponse {
+	(
+		[(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+		metrics.render().await
+	)
+}
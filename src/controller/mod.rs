@@ -1,5 +1,14 @@
+pub mod api_key_controller;
 pub mod category_controller;
 pub mod product_controller;
+pub mod product_image_controller;
 pub mod user_controller;
 pub mod auth_controller;
-pub mod files_controller;
\ No newline at end of file
+pub mod files_controller;
+pub mod health_controller;
+pub mod metrics_controller;
+pub mod oidc_controller;
+pub mod password_reset_controller;
+pub mod session_controller;
+pub mod two_factor_controller;
+pub mod response;
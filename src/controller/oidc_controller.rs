@@ -0,0 +1,70 @@
+use axum::{
+	extract::{ Query, State }, http::{ header, HeaderMap, HeaderName, StatusCode }
+};
+
+use serde_json::json;
+
+use crate::controller::auth_controller::login_response;
+use crate::model::auth_model::AuthTokenResponse;
+use crate::model::message_model::MessageResponse;
+use crate::model::oidc_model::{ OidcCallbackQuery, OidcLoginResponse };
+use crate::model::two_factor_model::TwoFactorChallengeResponse;
+use crate::service::{ oidc_service::{ OidcService, STATE_COOKIE }, ServiceError };
+use crate::utils::client_ip::ClientInfo;
+
+// The value of the named cookie sent with the request.
+fn cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+	headers.get_all(header::COOKIE).iter()
+	.filter_map(|d| d.to_str().ok())
+	.flat_map(|d| d.split(';'))
+	.filter_map(|d| d.trim().split_once('='))
+	.find(|d| d.0 == name)
+	.map(|d| d.1)
+}
+
+#[utoipa::path(
+	get,
+	path = "/api/auth/oidc/login",
+	tag = "auth",
+	responses(
+		(status = 303, description = "Redirect to the identity provider's login page; sets the `oidc_state` cookie the callback needs", body = OidcLoginResponse),
+		(status = 404, description = "OIDC login is not enabled", body = MessageResponse)
+	)
+)]
+pub async fn login(
+	State(oidc): State<OidcService>
+) -> Result<(StatusCode, [(HeaderName, String); 2], String), ServiceError> {
+	let (authorization_url, state) = oidc.begin().await?;
+
+	Ok((
+		StatusCode::SEE_OTHER,
+		[(header::LOCATION, authorization_url.clone()), (header::SET_COOKIE, oidc.state_cookie(&state))],
+		json!(OidcLoginResponse { success: true, authorization_url }).to_string()
+	))
+}
+
+#[utoipa::path(
+	get,
+	path = "/api/auth/oidc/callback",
+	tag = "auth",
+	params(OidcCallbackQuery),
+	responses(
+		(status = 202, description = "Logged in as the local user linked to the identity", body = AuthTokenResponse),
+		(status = 200, description = "The user owes the local second factor; finish as after `/api/auth/login`", body = TwoFactorChallengeResponse),
+		(status = 400, description = "Missing, unknown or expired `state`, or not the one in this browser's `oidc_state` cookie", body = MessageResponse),
+		(status = 401, description = "The provider refused the login or its ID token is invalid", body = MessageResponse),
+		(status = 403, description = "No local account for the identity and provisioning is off", body = MessageResponse)
+	)
+)]
+pub async fn callback(
+	State(oidc): State<OidcService>,
+	client: ClientInfo,
+	headers: HeaderMap,
+	Query(query): Query<OidcCallbackQuery>
+) -> Result<(StatusCode, [(HeaderName, String); 1], String), ServiceError> {
+	let outcome = oidc.complete(query, cookie(&headers, STATE_COOKIE), &client).await?;
+	let (status, body) = login_response(outcome);
+
+	// The state is used up either way.
+	Ok((status, [(header::SET_COOKIE, format!("{STATE_COOKIE}=; Path=/api/auth/oidc; Max-Age=0"))], body))
+}
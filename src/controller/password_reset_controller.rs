@@ -0,0 +1,82 @@
+use axum::{
+	extract::State, http::StatusCode, Extension, Json
+};
+
+use serde_json::json;
+
+use crate::model::auth_model::{ ForgotPasswordBody, IssueResetTokenBody, ResetPasswordBody, ResetTokenResponse };
+use crate::model::message_model::MessageResponse;
+use crate::model::user_model::JwtClaims;
+use crate::service::{ password_reset_service::PasswordResetService, ServiceError };
+
+#[utoipa::path(
+	post,
+	path = "/api/auth/password-reset",
+	tag = "auth",
+	security(("bearer_auth" = [])),
+	request_body = IssueResetTokenBody,
+	responses(
+		(status = 201, description = "Single-use reset token for the user", body = ResetTokenResponse),
+		(status = 403, description = "Caller is not an admin", body = MessageResponse),
+		(status = 404, body = MessageResponse)
+	)
+)]
+pub async fn issue(
+	State(resets): State<PasswordResetService>,
+	Extension(claims): Extension<JwtClaims>,
+	Json(body): Json<IssueResetTokenBody>
+) -> Result<(StatusCode, String), ServiceError> {
+	if claims.role != "admin" {
+		return Err(ServiceError::Forbidden("Only admins can issue password resets.".to_owned()));
+	}
+
+	let (token, expires_at) = resets.issue(body.user_id).await?;
+
+	Ok((
+		StatusCode::CREATED,
+		json!(ResetTokenResponse { success: true, token, expires_at }).to_string()
+	))
+}
+
+#[utoipa::path(
+	post,
+	path = "/api/auth/forgot-password",
+	tag = "auth",
+	request_body = ForgotPasswordBody,
+	responses(
+		(status = 202, description = "Same answer whether or not the username exists", body = MessageResponse)
+	)
+)]
+pub async fn forgot_password(
+	State(resets): State<PasswordResetService>,
+	Json(body): Json<ForgotPasswordBody>
+) -> Result<(StatusCode, String), ServiceError> {
+	resets.request(&body.username).await?;
+
+	Ok((
+		StatusCode::ACCEPTED,
+		json!({ "success": true, "message": "If the account exists, a reset token is on its way." }).to_string()
+	))
+}
+
+#[utoipa::path(
+	post,
+	path = "/api/auth/reset-password",
+	tag = "auth",
+	request_body = ResetPasswordBody,
+	responses(
+		(status = 200, description = "Password replaced and all sessions of the user revoked", body = MessageResponse),
+		(status = 400, description = "Invalid, used or expired token, or a password the policy rejects", body = MessageResponse)
+	)
+)]
+pub async fn reset_password(
+	State(resets): State<PasswordResetService>,
+	Json(body): Json<ResetPasswordBody>
+) -> Result<(StatusCode, String), ServiceError> {
+	resets.reset(body).await?;
+
+	Ok((
+		StatusCode::OK,
+		json!({ "success": true, "message": "Your password has been reset; log in again." }).to_string()
+	))
+}
@@ -0,0 +1,134 @@
+use axum::{
+	extract::{ Path, State },
+	http::StatusCode,
+	Json
+};
+
+use serde_json::json;
+
+use crate::model::product_image_model::{ ProductImageCreateBody, ProductImageData, ProductImageReorderBody };
+use crate::model::message_model::MessageResponse;
+use crate::service::{ product_image_service::ProductImageService, ServiceError };
+
+#[utoipa::path(
+	get,
+	path = "/api/product/{id}/images",
+	tag = "product image",
+	security(("bearer_auth" = []), ("api_key" = ["product:read"])),
+	params(("id" = i32, Path, description = "Product id")),
+	responses(
+		(status = 200, description = "Gallery in display order", body = Vec<ProductImageData>),
+		(status = 404, body = MessageResponse)
+	)
+)]
+pub async fn find_many(
+	State(product_images): State<ProductImageService>,
+	Path(product_id): Path<i32>
+) -> Result<(StatusCode, String), ServiceError> {
+	let data = product_images.find_many(product_id).await?;
+
+	Ok((StatusCode::OK, json!(data).to_string()))
+}
+
+#[utoipa::path(
+	post,
+	path = "/api/product/{id}/images",
+	tag = "product image",
+	security(("bearer_auth" = []), ("api_key" = ["product:write"])),
+	params(("id" = i32, Path, description = "Product id")),
+	request_body = ProductImageCreateBody,
+	responses(
+		(status = 202, description = "The added image is returned in `data`", body = MessageResponse),
+		(status = 404, body = MessageResponse),
+		(status = 422, description = "`file_name` was not uploaded to the product bucket", body = MessageResponse)
+	)
+)]
+pub async fn create(
+	State(product_images): State<ProductImageService>,
+	Path(product_id): Path<i32>,
+	Json(body): Json<ProductImageCreateBody>
+) -> Result<(StatusCode, String), ServiceError> {
+	let image = product_images.create(product_id, body).await?;
+
+	Ok((
+		StatusCode::ACCEPTED,
+		json!({ "success": true, "message": "Product Image was Added.", "data": image }).to_string()
+	))
+}
+
+#[utoipa::path(
+	put,
+	path = "/api/product/{id}/images/reorder",
+	tag = "product image",
+	security(("bearer_auth" = []), ("api_key" = ["product:write"])),
+	params(("id" = i32, Path, description = "Product id")),
+	request_body = ProductImageReorderBody,
+	responses(
+		(status = 200, body = MessageResponse),
+		(status = 400, description = "`image_ids` doesn't list every image exactly once", body = MessageResponse)
+	)
+)]
+pub async fn reorder(
+	State(product_images): State<ProductImageService>,
+	Path(product_id): Path<i32>,
+	Json(body): Json<ProductImageReorderBody>
+) -> Result<(StatusCode, String), ServiceError> {
+	product_images.reorder(product_id, body).await?;
+
+	Ok((
+		StatusCode::OK,
+		json!({ "success": true, "message": "Product Images were Reordered." }).to_string()
+	))
+}
+
+#[utoipa::path(
+	put,
+	path = "/api/product/{id}/images/{image_id}/primary",
+	tag = "product image",
+	security(("bearer_auth" = []), ("api_key" = ["product:write"])),
+	params(
+		("id" = i32, Path, description = "Product id"),
+		("image_id" = i32, Path, description = "Product image id")
+	),
+	responses(
+		(status = 200, body = MessageResponse),
+		(status = 404, body = MessageResponse)
+	)
+)]
+pub async fn set_primary_image(
+	State(product_images): State<ProductImageService>,
+	Path((product_id, image_id)): Path<(i32, i32)>
+) -> Result<(StatusCode, String), ServiceError> {
+	product_images.set_primary(product_id, image_id).await?;
+
+	Ok((
+		StatusCode::OK,
+		json!({ "success": true, "message": "Primary Product Image was Updated." }).to_string()
+	))
+}
+
+#[utoipa::path(
+	delete,
+	path = "/api/product/{id}/images/{image_id}",
+	tag = "product image",
+	security(("bearer_auth" = []), ("api_key" = ["product:write"])),
+	params(
+		("id" = i32, Path, description = "Product id"),
+		("image_id" = i32, Path, description = "Product image id")
+	),
+	responses(
+		(status = 200, body = MessageResponse),
+		(status = 404, body = MessageResponse)
+	)
+)]
+pub async fn delete(
+	State(product_images): State<ProductImageService>,
+	Path((product_id, image_id)): Path<(i32, i32)>
+) -> Result<(StatusCode, String), ServiceError> {
+	product_images.delete(product_id, image_id).await?;
+
+	Ok((
+		StatusCode::OK,
+		json!({ "success": true, "message": "Product Image was Deleted." }).to_string()
+	))
+}
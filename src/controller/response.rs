@@ -0,0 +1,33 @@
+use axum::{
+	http::{ header, StatusCode },
+	response::{ IntoResponse, Response }
+};
+use serde_json::json;
+
+use crate::service::ServiceError;
+
+impl IntoResponse for ServiceError {
+	fn into_response(self) -> Response {
+		let status = match self {
+			ServiceError::BadRequest(_) => StatusCode::BAD_REQUEST,
+			ServiceError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+			ServiceError::Forbidden(_) => StatusCode::FORBIDDEN,
+			ServiceError::NotFound(_) => StatusCode::NOT_FOUND,
+			ServiceError::Conflict(_) => StatusCode::CONFLICT,
+			ServiceError::UnprocessableEntity(_) => StatusCode::UNPROCESSABLE_ENTITY,
+			ServiceError::TooManyRequests(_, _) => StatusCode::TOO_MANY_REQUESTS,
+			ServiceError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR
+		};
+
+		let mut response = (
+			status,
+			json!({ "success": false, "message": self.message() }).to_string()
+		).into_response();
+
+		if let ServiceError::TooManyRequests(_, retry_after) = self {
+			response.headers_mut().insert(header::RETRY_AFTER, retry_after.into());
+		}
+
+		response
+	}
+}
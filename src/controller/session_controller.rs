@@ -0,0 +1,106 @@
+use axum::{
+	extract::{ Path, Query, State }, http::StatusCode, Extension
+};
+
+use serde_json::json;
+
+use crate::model::message_model::MessageResponse;
+use crate::model::session_model::{ RevokeSessionsQuery, SessionData };
+use crate::model::user_model::JwtClaims;
+use crate::service::{ session_service::SessionService, ServiceError };
+
+#[utoipa::path(
+	get,
+	path = "/api/auth/sessions",
+	tag = "auth",
+	security(("bearer_auth" = [])),
+	responses(
+		(status = 200, description = "The bearer's active sessions, most recently used first", body = Vec<SessionData>),
+		(status = 401, body = MessageResponse)
+	)
+)]
+pub async fn find_many(
+	State(sessions): State<SessionService>,
+	Extension(claims): Extension<JwtClaims>
+) -> Result<(StatusCode, String), ServiceError> {
+	let query_find_many = sessions.list(claims.sub, &claims.sid).await?;
+
+	Ok((StatusCode::OK, json!(query_find_many).to_string()))
+}
+
+#[utoipa::path(
+	delete,
+	path = "/api/auth/sessions",
+	tag = "auth",
+	security(("bearer_auth" = [])),
+	params(RevokeSessionsQuery),
+	responses(
+		(status = 200, description = "Every session of the bearer ended, the current one too unless `except_current`", body = MessageResponse),
+		(status = 401, body = MessageResponse)
+	)
+)]
+pub async fn delete_many(
+	State(sessions): State<SessionService>,
+	Extension(claims): Extension<JwtClaims>,
+	Query(query): Query<RevokeSessionsQuery>
+) -> Result<(StatusCode, String), ServiceError> {
+	let except_sid = query.except_current.then_some(claims.sid.as_str());
+	let revoked = sessions.revoke_all(claims.sub, except_sid).await?;
+
+	Ok((
+		StatusCode::OK,
+		json!({ "success": true, "message": format!("{revoked} session(s) revoked.") }).to_string()
+	))
+}
+
+#[utoipa::path(
+	delete,
+	path = "/api/auth/sessions/{id}",
+	tag = "auth",
+	security(("bearer_auth" = [])),
+	params(("id" = i32, Path, description = "Session id")),
+	responses(
+		(status = 200, body = MessageResponse),
+		(status = 404, description = "No such session of the bearer", body = MessageResponse)
+	)
+)]
+pub async fn delete(
+	State(sessions): State<SessionService>,
+	Extension(claims): Extension<JwtClaims>,
+	Path(id): Path<i32>
+) -> Result<(StatusCode, String), ServiceError> {
+	sessions.revoke(claims.sub, id).await?;
+
+	Ok((
+		StatusCode::OK,
+		json!({ "success": true, "message": "Session revoked." }).to_string()
+	))
+}
+
+#[utoipa::path(
+	delete,
+	path = "/api/user/{id}/sessions",
+	tag = "user",
+	security(("bearer_auth" = [])),
+	params(("id" = i32, Path, description = "User id")),
+	responses(
+		(status = 200, description = "Every session of the user ended", body = MessageResponse),
+		(status = 403, description = "Caller is not an admin", body = MessageResponse)
+	)
+)]
+pub async fn delete_for_user(
+	State(sessions): State<SessionService>,
+	Extension(claims): Extension<JwtClaims>,
+	Path(id): Path<i32>
+) -> Result<(StatusCode, String), ServiceError> {
+	if claims.role != "admin" {
+		return Err(ServiceError::Forbidden("Only admins can end other users' sessions.".to_owned()));
+	}
+
+	let revoked = sessions.revoke_all(id, None).await?;
+
+	Ok((
+		StatusCode::OK,
+		json!({ "success": true, "message": format!("{revoked} session(s) revoked.") }).to_string()
+	))
+}
@@ -0,0 +1,155 @@
+use axum::{
+	extract::State, http::{ HeaderMap, StatusCode }, Extension, Json
+};
+
+use serde_json::json;
+
+use crate::model::auth_model::AuthTokenResponse;
+use crate::model::message_model::MessageResponse;
+use crate::model::two_factor_model::{
+	RecoveryCodesResponse, TwoFactorCodeBody, TwoFactorEnrollResponse, TwoFactorLoginBody
+};
+use crate::model::user_model::JwtClaims;
+use crate::service::{ auth_service::AuthService, two_factor_service::TwoFactorService, ServiceError };
+use crate::utils::client_ip::ClientInfo;
+
+// Enrolment isn't behind `auth_guard`, which would turn away an `enroll`
+// challenge token, so it reads the bearer itself.
+fn bearer_token(headers: &HeaderMap) -> Result<&str, ServiceError> {
+	headers.get("Authorization")
+	.and_then(|val| val.to_str().ok())
+	.and_then(|val| val.strip_prefix("Bearer "))
+	.ok_or_else(|| ServiceError::Unauthorized("Unauthorized".to_owned()))
+}
+
+#[utoipa::path(
+	post,
+	path = "/api/auth/login/2fa",
+	tag = "auth",
+	request_body = TwoFactorLoginBody,
+	responses(
+		(status = 202, body = AuthTokenResponse),
+		(status = 401, description = "Bad code or expired challenge", body = MessageResponse),
+		(status = 429, body = MessageResponse)
+	)
+)]
+pub async fn verify_login(
+	State(auth): State<AuthService>,
+	client: ClientInfo,
+	Json(body): Json<TwoFactorLoginBody>
+) -> Result<(StatusCode, String), ServiceError> {
+	let authenticated = auth.complete_two_factor(body, &client).await?;
+
+	Ok((
+		StatusCode::ACCEPTED,
+		json!(AuthTokenResponse { success: true, data: authenticated.user, token: authenticated.token }).to_string()
+	))
+}
+
+#[utoipa::path(
+	post,
+	path = "/api/auth/2fa/enroll",
+	tag = "auth",
+	security(("bearer_auth" = [])),
+	responses(
+		(status = 200, description = "New unconfirmed secret; replaces any earlier unconfirmed one", body = TwoFactorEnrollResponse),
+		(status = 400, description = "2FA is already on", body = MessageResponse),
+		(status = 401, body = MessageResponse)
+	)
+)]
+pub async fn enroll(
+	State(auth): State<AuthService>,
+	State(two_factor): State<TwoFactorService>,
+	headers: HeaderMap
+) -> Result<(StatusCode, String), ServiceError> {
+	let subject = auth.two_factor_subject(bearer_token(&headers)?).await?;
+	let (secret, otpauth_uri) = two_factor.enroll(subject.user.id, &subject.user.username).await?;
+
+	Ok((
+		StatusCode::OK,
+		json!(TwoFactorEnrollResponse { success: true, secret, otpauth_uri }).to_string()
+	))
+}
+
+#[utoipa::path(
+	post,
+	path = "/api/auth/2fa/confirm",
+	tag = "auth",
+	security(("bearer_auth" = [])),
+	request_body = TwoFactorCodeBody,
+	responses(
+		(status = 200, description = "2FA is on. With an `enroll` challenge the login is finished too", body = RecoveryCodesResponse),
+		(status = 400, body = MessageResponse),
+		(status = 401, body = MessageResponse)
+	)
+)]
+pub async fn confirm(
+	State(auth): State<AuthService>,
+	State(two_factor): State<TwoFactorService>,
+	client: ClientInfo,
+	headers: HeaderMap,
+	Json(body): Json<TwoFactorCodeBody>
+) -> Result<(StatusCode, String), ServiceError> {
+	let subject = auth.two_factor_subject(bearer_token(&headers)?).await?;
+	let recovery_codes = two_factor.confirm(subject.user.id, &body.code).await?;
+	let authenticated = auth.complete_enrollment(subject, &client).await?;
+
+	Ok((
+		StatusCode::OK,
+		json!(RecoveryCodesResponse {
+			success: true,
+			recovery_codes,
+			token: authenticated.as_ref().map(|d| d.token.clone()),
+			data: authenticated.map(|d| d.user)
+		}).to_string()
+	))
+}
+
+#[utoipa::path(
+	post,
+	path = "/api/auth/2fa/disable",
+	tag = "auth",
+	security(("bearer_auth" = [])),
+	request_body = TwoFactorCodeBody,
+	responses(
+		(status = 200, body = MessageResponse),
+		(status = 400, body = MessageResponse),
+		(status = 403, description = "The caller's role requires 2FA", body = MessageResponse)
+	)
+)]
+pub async fn disable(
+	State(two_factor): State<TwoFactorService>,
+	Extension(claims): Extension<JwtClaims>,
+	Json(body): Json<TwoFactorCodeBody>
+) -> Result<(StatusCode, String), ServiceError> {
+	two_factor.disable(claims.sub, &claims.role, &body.code).await?;
+
+	Ok((
+		StatusCode::OK,
+		json!({ "success": true, "message": "Two-factor authentication is off." }).to_string()
+	))
+}
+
+#[utoipa::path(
+	post,
+	path = "/api/auth/2fa/recovery-codes",
+	tag = "auth",
+	security(("bearer_auth" = [])),
+	request_body = TwoFactorCodeBody,
+	responses(
+		(status = 200, description = "A new set of recovery codes; the old ones stop working", body = RecoveryCodesResponse),
+		(status = 400, body = MessageResponse)
+	)
+)]
+pub async fn recovery_codes(
+	State(two_factor): State<TwoFactorService>,
+	Extension(claims): Extension<JwtClaims>,
+	Json(body): Json<TwoFactorCodeBody>
+) -> Result<(StatusCode, String), ServiceError> {
+	let recovery_codes = two_factor.regenerate_recovery_codes(claims.sub, &body.code).await?;
+
+	Ok((
+		StatusCode::OK,
+		json!(RecoveryCodesResponse { success: true, recovery_codes, token: None, data: None }).to_string()
+	))
+}
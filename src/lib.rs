@@ -0,0 +1,219 @@
+use axum::{extract::DefaultBodyLimit, http::{HeaderName, HeaderValue}, middleware, routing::get, Extension, Json, Router};
+use tower_http::{
+    cors::{ AllowOrigin, Any, CorsLayer },
+    request_id::{ MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer },
+    trace::TraceLayer
+};
+use utoipa::OpenApi;
+use utoipa_axum::{ router::OpenApiRouter, routes };
+use utoipa_redoc::{ Redoc, Servable };
+
+pub mod config;
+pub mod model;
+pub mod repository;
+pub mod service;
+pub mod controller;
+pub mod migrate;
+pub mod openapi;
+pub mod state;
+pub mod telemetry;
+pub mod utils;
+
+use controller::{
+    api_key_controller,
+    category_controller, 
+    product_controller, 
+    product_image_controller, 
+    user_controller, 
+    auth_controller, 
+    files_controller,
+    health_controller,
+    metrics_controller,
+    oidc_controller,
+    password_reset_controller,
+    session_controller,
+    two_factor_controller
+};
+
+use openapi::ApiDoc;
+use state::AppState;
+use utils::{
+    api_scope::ApiScope,
+    rate_limit::{ rate_limit, RateLimitGroup },
+    request_metrics,
+    router_gurard::auth_guard
+};
+
+// Builds the complete HTTP application. Used by the binary and by the
+// integration tests, which drive it in-process.
+pub fn app(state: AppState) -> Router {
+    let config = state.config.clone();
+
+    let cors = if config.server.cors_origins.is_empty() || config.server.cors_origins.iter().any(|d| d == "*") {
+        CorsLayer::new().allow_origin(Any)
+    } else {
+        let origins: Vec<HeaderValue> = config.server.cors_origins.iter()
+        .map(|d| d.parse().unwrap_or_else(|_| panic!("Invalid CORS origin {d}")))
+        .collect();
+
+        CorsLayer::new().allow_origin(AllowOrigin::list(origins))
+    };
+
+    // A client supplied `X-Request-Id` is kept, otherwise one is generated.
+    let request_id_header = HeaderName::from_static(telemetry::REQUEST_ID_HEADER);
+
+    // Rate limit of a route group. Layered inside `auth_guard` so that
+    // authenticated requests are counted per user.
+    let limit = |group: RateLimitGroup| middleware::from_fn_with_state(state.rate_limits.group(group), rate_limit);
+
+    // Scope an API key needs for a route group. Layered outside `auth_guard`,
+    // which rejects API keys on groups without one. A scope missing from
+    // `SCOPES` could never be granted, so it is caught here.
+    let scope = |scope: ApiScope| {
+        assert!(scope.is_known(), "{scope:?} needs a scope missing from api_scope::SCOPES");
+
+        Extension(scope)
+    };
+
+    // Handlers sharing a path are registered together in one `routes!`.
+    let category_router = OpenApiRouter::new()
+    .routes(routes!(category_controller::find_many, category_controller::create))
+    .routes(routes!(category_controller::find_first, category_controller::update, category_controller::delete))
+    .route_layer(limit(RateLimitGroup::Writes))
+    .route_layer(middleware::from_fn_with_state(state.clone(), auth_guard))
+    .route_layer(scope(ApiScope::of("category")));
+
+    // Searches are POSTed but only read, so each gets its own group.
+    let category_search_router = OpenApiRouter::new()
+    .routes(routes!(category_controller::search_paginate))
+    .route_layer(limit(RateLimitGroup::Search))
+    .route_layer(middleware::from_fn_with_state(state.clone(), auth_guard))
+    .route_layer(scope(ApiScope::read_only("category")));
+
+    let product_search_router = OpenApiRouter::new()
+    .routes(routes!(product_controller::search_paginate))
+    .route_layer(limit(RateLimitGroup::Search))
+    .route_layer(middleware::from_fn_with_state(state.clone(), auth_guard))
+    .route_layer(scope(ApiScope::read_only("product")));
+
+    // All of these check a password or a code, so they share the login budget.
+    let login_router = OpenApiRouter::new()
+    .routes(routes!(auth_controller::login))
+    .routes(routes!(auth_controller::change_password))
+    .routes(routes!(two_factor_controller::verify_login))
+    .routes(routes!(two_factor_controller::enroll))
+    .routes(routes!(two_factor_controller::confirm))
+    .routes(routes!(password_reset_controller::forgot_password))
+    .routes(routes!(password_reset_controller::reset_password))
+    .routes(routes!(oidc_controller::login))
+    .routes(routes!(oidc_controller::callback))
+    .route_layer(limit(RateLimitGroup::Login));
+
+    let auth_router = OpenApiRouter::new()
+    .routes(routes!(auth_controller::authenticated));
+
+    let profile_router = OpenApiRouter::new()
+    .routes(routes!(auth_controller::me))
+    .routes(routes!(session_controller::find_many, session_controller::delete_many))
+    .routes(routes!(session_controller::delete))
+    .route_layer(middleware::from_fn_with_state(state.clone(), auth_guard));
+
+    let two_factor_router = OpenApiRouter::new()
+    .routes(routes!(two_factor_controller::disable))
+    .routes(routes!(two_factor_controller::recovery_codes))
+    .route_layer(limit(RateLimitGroup::Login))
+    .route_layer(middleware::from_fn_with_state(state.clone(), auth_guard));
+
+    let auth_admin_router = OpenApiRouter::new()
+    .routes(routes!(auth_controller::unlock))
+    .routes(routes!(password_reset_controller::issue))
+    .routes(routes!(session_controller::delete_for_user))
+    .routes(routes!(api_key_controller::find_many, api_key_controller::create))
+    .routes(routes!(api_key_controller::delete))
+    .route_layer(limit(RateLimitGroup::Writes))
+    .route_layer(middleware::from_fn_with_state(state.clone(), auth_guard));
+
+    let product_router = OpenApiRouter::new()
+    .routes(routes!(product_controller::create))
+    .routes(routes!(product_controller::update, product_controller::delete))
+    .routes(routes!(product_image_controller::find_many, product_image_controller::create))
+    .routes(routes!(product_image_controller::reorder))
+    .routes(routes!(product_image_controller::set_primary_image))
+    .routes(routes!(product_image_controller::delete))
+    .route_layer(limit(RateLimitGroup::Writes))
+    .route_layer(middleware::from_fn_with_state(state.clone(), auth_guard))
+    .route_layer(scope(ApiScope::of("product")));
+
+    let user_router = OpenApiRouter::new()
+    .routes(routes!(user_controller::find_many))
+    .routes(routes!(user_controller::create))
+    .routes(routes!(user_controller::update, user_controller::delete))
+    .route_layer(limit(RateLimitGroup::Writes))
+    .route_layer(middleware::from_fn_with_state(state.clone(), auth_guard));
+
+    let get_file_router = OpenApiRouter::new()
+    .routes(routes!(files_controller::get));
+
+    let upload_router = OpenApiRouter::new()
+    .routes(routes!(files_controller::upload))
+    .layer(DefaultBodyLimit::max(config.uploads.max_request_bytes))
+    .route_layer(limit(RateLimitGroup::Uploads))
+    .route_layer(middleware::from_fn_with_state(state.clone(), auth_guard))
+    .route_layer(scope(ApiScope::write_only("file")));
+
+    let file_router = OpenApiRouter::new()
+    .routes(routes!(files_controller::delete))
+    .routes(routes!(files_controller::sign))
+    .routes(routes!(files_controller::update_visibility))
+    .route_layer(limit(RateLimitGroup::Writes))
+    .route_layer(middleware::from_fn_with_state(state.clone(), auth_guard))
+    .route_layer(scope(ApiScope::write_only("file")));
+
+    let health_router = OpenApiRouter::new()
+    .routes(routes!(health_controller::live))
+    .routes(routes!(health_controller::ready));
+
+    let jwks_router = OpenApiRouter::new()
+    .routes(routes!(auth_controller::jwks));
+
+    let (app_router, api) = OpenApiRouter::with_openapi(ApiDoc::openapi())
+    .route("/api", get(|| async { "Hello World" }))
+    .merge(category_router)
+    .merge(category_search_router)
+    .merge(product_search_router)
+    .merge(user_router)
+    .merge(login_router)
+    .merge(auth_router)
+    .merge(profile_router)
+    .merge(auth_admin_router)
+    .merge(two_factor_router)
+    .merge(product_router)
+    .merge(get_file_router)
+    .merge(upload_router)
+    .merge(file_router)
+    .merge(health_router)
+    .merge(jwks_router)
+    .split_for_parts();
+
+    let mut app_router = app_router
+    .merge(Redoc::with_url("/api/docs", api.clone()))
+    .route("/api/openapi.json", get(move || {
+        let api = api.clone();
+
+        async move { Json(api) }
+    }));
+
+    if config.metrics.enabled {
+        app_router = app_router
+        .route("/metrics", get(metrics_controller::render))
+        .layer(middleware::from_fn(request_metrics::track_requests));
+    }
+
+    app_router
+    .layer(cors)
+    .layer(middleware::from_fn(telemetry::request_id_in_errors))
+    .layer(PropagateRequestIdLayer::new(request_id_header.clone()))
+    .layer(TraceLayer::new_for_http().make_span_with(telemetry::make_span).on_response(telemetry::on_response))
+    .layer(SetRequestIdLayer::new(request_id_header, MakeRequestUuid))
+    .with_state(state)
+}
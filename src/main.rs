@@ -1,91 +1,110 @@
-use sea_orm::{Database, DatabaseConnection};
-use tokio::net::TcpListener;
-use axum::{middleware, routing::{delete, get, post, put}, Router};
-use tower_http::cors::{ Any, CorsLayer };
+use sea_orm::{ConnectOptions, Database};
+use std::{ net::SocketAddr, sync::Arc, time::Duration };
+use tokio::{ net::TcpListener, signal, sync::Notify };
 
-mod model;
-mod controller;
-mod utils;
-
-use controller::{
-    category_controller, 
-    product_controller, 
-    user_controller, 
-    auth_controller, 
-    files_controller 
-};
-
-use utils::router_gurard::auth_guard;
+use rust_axum_seaorm::{ app, config::AppConfig, migrate, state::AppState, telemetry, utils::jwt };
 
 #[tokio::main]
 async fn main() {
-    dotenvy::dotenv().expect("Failed to load .env file.");
+    let config = AppConfig::load().unwrap_or_else(|e| panic!("Invalid configuration: {e}"));
+
+    telemetry::init(&config.logging);
+
+    // `rotate-jwt-key` adds a signing key to `jwt.keys_dir` and exits. Running
+    // replicas pick it up when restarted; until then they keep verifying with
+    // the keys they loaded, so restart them all before the next rotation.
+    if std::env::args().nth(1).as_deref() == Some("rotate-jwt-key") {
+        let rotated = jwt::rotate(&config.jwt).unwrap_or_else(|e| panic!("JWT key rotation failed: {e}"));
+
+        tracing::info!(kid = %rotated.kid, retired = ?rotated.retired, "JWT signing key rotated");
+        return;
+    }
+
+    // Statements are logged at debug (target `sqlx::query`) with their timing,
+    // and at warn once they exceed `logging.slow_query_ms`.
+    let mut connect_options = ConnectOptions::new(config.database.url.clone());
+    connect_options
+    .max_connections(config.database.max_connections)
+    .sqlx_logging(true)
+    .sqlx_logging_level(log::LevelFilter::Debug)
+    .sqlx_slow_statements_logging_settings(
+        log::LevelFilter::Warn,
+        Duration::from_millis(config.logging.slow_query_ms)
+    );
+
+    let db = Database::connect(connect_options).await.expect("Failed to Connect to the Database");
+
+    migrate::prepare(&db, &config.database).await
+    .unwrap_or_else(|e| panic!("Database schema check failed: {e}"));
+
+    let listener = TcpListener::bind(&config.server.address)
+    .await.expect("Couldn't create TCP Listener.");
 
-    let server_address = std::env::var("SERVER_ADDRESS").unwrap_or("localhost:3000".to_owned());
-    let database_url = std::env::var("DATABASE_URL").expect("Database Url from .env file not found.");
+    tracing::info!(address = %listener.local_addr().unwrap(), "listening");
+
+    let shutdown_delay = Duration::from_secs(config.server.shutdown_delay_seconds);
+    let drain_timeout = Duration::from_secs(config.server.shutdown_timeout_seconds);
+    let state = AppState::new(db.clone(), config);
+    let app_router = app(state.clone());
+
+    // On SIGTERM/SIGINT readiness starts failing while requests are still
+    // served for `shutdown_delay`, giving load balancers time to notice. Then
+    // the listener stops accepting and in-flight requests get `drain_timeout`
+    // to finish before they are dropped.
+    let shutdown_started = Arc::new(Notify::new());
+
+    // The peer address is the client IP for login throttling unless
+    // `server.trust_forwarded_for` is set.
+    let server = axum::serve(listener, app_router.into_make_service_with_connect_info::<SocketAddr>())
+    .with_graceful_shutdown({
+        let shutdown_started = shutdown_started.clone();
+
+        async move {
+            shutdown_signal().await;
+            state.health.begin_shutdown();
+            tokio::time::sleep(shutdown_delay).await;
+
+            tracing::info!("closing the listener, draining requests");
+            shutdown_started.notify_one();
+        }
+    });
+
+    let drain_deadline = async {
+        shutdown_started.notified().await;
+        tokio::time::sleep(drain_timeout).await;
+    };
+
+    tokio::select! {
+        result = server => result.expect("Error while serving the server."),
+        _ = drain_deadline => tracing::warn!(timeout_seconds = drain_timeout.as_secs(), "drain timed out, dropping in-flight requests")
+    }
+
+    if let Err(e) = db.close().await {
+        tracing::warn!(error = %e, "failed to close the database pool");
+    }
+
+    tracing::info!("shut down");
+}
 
-    let db: DatabaseConnection = Database::connect(database_url).await.expect("Failed to Connect to the Database");
-    // let db = Arc::new(db); // Use Arc to allow cloning in handlers
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        signal::ctrl_c().await.expect("Failed to listen for SIGINT");
+    };
 
-    let listener = TcpListener::bind(server_address)
-    .await.expect("Couldn't create TCP Listener.");
+    #[cfg(unix)]
+    let terminate = async {
+        signal::unix::signal(signal::unix::SignalKind::terminate())
+        .expect("Failed to listen for SIGTERM")
+        .recv().await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {}
+    }
 
-    print!("Listening on {} ", listener.local_addr().unwrap());
-
-    let cors = CorsLayer::new().allow_origin(Any);
-
-    let category_router = Router::new()
-    .route("/api/category/search-paginate", post(category_controller::search_paginate))
-    .route("/api/category", get(category_controller::find_many))
-    .route("/api/category/{id}", get(category_controller::find_first))
-    .route("/api/category", post(category_controller::create))
-    .route("/api/category/{id}", put(category_controller::update))
-    .route("/api/category/{id}", delete(category_controller::delete))
-    .route_layer(middleware::from_fn(auth_guard));
-
-    let login_router = Router::new()
-    .route("/api/auth/login", post(auth_controller::login));
-
-    let auth_router = Router::new()
-    .route("/api/auth/authenticated", post(auth_controller::authenticated))
-    .route("/api/auth/change-password", post(auth_controller::change_password));
-
-    let product_router = Router::new()
-    .route("/api/product/search", post(product_controller::search_paginate))
-    .route("/api/product", post(product_controller::create))
-    .route("/api/product/{id}", put(product_controller::update))
-    .route("/api/product/{id}", delete(product_controller::delete))
-    .route_layer(middleware::from_fn(auth_guard));
-
-    let user_router = Router::new()
-    .route("/api/user/many", get(user_controller::find_many))
-    .route("/api/user", post(user_controller::create))
-    .route("/api/user/{id}", put(user_controller::update))
-    .route("/api/user/{id}", delete(user_controller::delete))
-    .route_layer(middleware::from_fn(auth_guard));
-
-    let get_file_router = Router::new()
-    .route("/api/files/user/image/{filename}", get(files_controller::get_user_image))
-    .route("/api/files/product/image/{filename}", get(files_controller::get_product_image));
-
-    let file_router = Router::new()
-    .route("/api/files/user", post(files_controller::upload_user_image))
-    .route("/api/files/user/delete/{filename}", delete(files_controller::delete_user_image))
-    .route("/api/files/product", post(files_controller::upload_product_image))
-    .route("/api/files/product/delete/{filename}", delete(files_controller::delete_product_image));
-
-    let app_router = Router::new()
-    .route("/api", get(|| async { "Hello World" }))
-    .merge(category_router)
-    .merge(user_router)
-    .merge(login_router)
-    .merge(auth_router)
-    .merge(product_router)
-    .merge(get_file_router)
-    .merge(file_router)
-    .layer(cors)
-    .with_state(db);
-
-
-    axum::serve(listener, app_router).await.expect("Error while serving the server.");
+    tracing::info!("shutdown signal received, no longer ready");
 }
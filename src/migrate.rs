@@ -0,0 +1,145 @@
+use migration::{ Migrator, MigratorTrait };
+use sea_orm::{
+	ConnectOptions, ConnectionTrait, Database, DatabaseBackend, DatabaseConnection, DbErr, Statement
+};
+use std::fmt;
+
+use crate::config::DatabaseConfig;
+
+// Key of the Postgres advisory lock (and name of the MySQL named lock) held
+// while the schema is checked and migrated.
+const LOCK_KEY: i64 = 0x7275_7374_5f61_786d;
+const LOCK_NAME: &str = "rust_axum_seaorm.migrate";
+const MYSQL_LOCK_TIMEOUT_SECONDS: u32 = 600;
+
+#[derive(Debug)]
+pub enum MigrateError {
+	// Applied migrations this build doesn't know about, i.e. a newer release
+	// already migrated the database.
+	SchemaAhead(Vec<String>),
+	Database(DbErr)
+}
+
+impl fmt::Display for MigrateError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			MigrateError::SchemaAhead(names) => write!(
+				f,
+				"the database schema is ahead of this build, unknown migrations: {}",
+				names.join(", ")
+			),
+			MigrateError::Database(e) => write!(f, "{e}")
+		}
+	}
+}
+
+impl std::error::Error for MigrateError {}
+
+impl From<DbErr> for MigrateError {
+	fn from(e: DbErr) -> Self {
+		MigrateError::Database(e)
+	}
+}
+
+// Checks the schema at boot and, with `database.auto_migrate`, applies pending
+// migrations. Runs under a database-wide lock so replicas starting together
+// migrate one at a time; the others find nothing left to do. Returns the
+// migrations applied by this call.
+pub async fn prepare(db: &DatabaseConnection, config: &DatabaseConfig) -> Result<Vec<String>, MigrateError> {
+	let lock = MigrationLock::acquire(db.get_database_backend(), &config.url).await?;
+	let result = check_and_apply(db, config.auto_migrate).await;
+
+	lock.release().await;
+
+	result
+}
+
+async fn check_and_apply(db: &DatabaseConnection, auto_migrate: bool) -> Result<Vec<String>, MigrateError> {
+	let known: Vec<String> = Migrator::migrations().iter().map(|d| d.name().to_owned()).collect();
+
+	let unknown: Vec<String> = Migrator::get_migration_models(db).await?.into_iter()
+	.map(|d| d.version)
+	.filter(|d| !known.contains(d))
+	.collect();
+
+	if !unknown.is_empty() {
+		return Err(MigrateError::SchemaAhead(unknown));
+	}
+
+	let pending: Vec<String> = Migrator::get_pending_migrations(db).await?.iter()
+	.map(|d| d.name().to_owned())
+	.collect();
+
+	let applied = if pending.is_empty() {
+		Vec::new()
+	} else if auto_migrate {
+		tracing::info!(migrations = ?pending, "applying pending migrations");
+		Migrator::up(db, None).await?;
+		pending
+	} else {
+		// Readiness reports the pending count until someone runs the migrations.
+		tracing::warn!(migrations = ?pending, "pending migrations; set database.auto_migrate or run the migration binary");
+		Vec::new()
+	};
+
+	let schema: Vec<String> = Migrator::get_migration_models(db).await?.into_iter()
+	.map(|d| d.version)
+	.collect();
+
+	tracing::info!(migrations = ?schema, "database schema");
+
+	Ok(applied)
+}
+
+// Session-scoped lock on a dedicated single connection, so the lock and its
+// release are guaranteed to run in the same session. SQLite serialises
+// writers on its own and needs none.
+struct MigrationLock {
+	conn: Option<DatabaseConnection>
+}
+
+impl MigrationLock {
+	async fn acquire(backend: DatabaseBackend, url: &str) -> Result<Self, DbErr> {
+		let statement = match backend {
+			DatabaseBackend::Postgres => format!("SELECT pg_advisory_lock({LOCK_KEY})"),
+			DatabaseBackend::MySql => format!("SELECT GET_LOCK('{LOCK_NAME}', {MYSQL_LOCK_TIMEOUT_SECONDS})"),
+			DatabaseBackend::Sqlite => return Ok(Self { conn: None })
+		};
+
+		let mut options = ConnectOptions::new(url.to_owned());
+		options.max_connections(1).min_connections(1).sqlx_logging(false);
+
+		let conn = Database::connect(options).await?;
+
+		tracing::info!("waiting for the migration lock");
+
+		let row = conn.query_one(Statement::from_string(backend, statement)).await?;
+
+		// `GET_LOCK` returns 0 on timeout instead of failing.
+		if backend == DatabaseBackend::MySql {
+			let acquired: Option<i64> = row.and_then(|d| d.try_get_by_index(0).ok());
+
+			if acquired != Some(1) {
+				return Err(DbErr::Custom("timed out waiting for the migration lock".to_owned()));
+			}
+		}
+
+		Ok(Self { conn: Some(conn) })
+	}
+
+	async fn release(self) {
+		let Some(conn) = self.conn else {
+			return;
+		};
+
+		let backend = conn.get_database_backend();
+		let statement = match backend {
+			DatabaseBackend::Postgres => format!("SELECT pg_advisory_unlock({LOCK_KEY})"),
+			_ => format!("SELECT RELEASE_LOCK('{LOCK_NAME}')")
+		};
+
+		// Closing the session releases the lock as well, so a failure here is harmless.
+		let _ = conn.execute(Statement::from_string(backend, statement)).await;
+		let _ = conn.close().await;
+	}
+}
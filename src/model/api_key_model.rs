@@ -0,0 +1,59 @@
+use serde::{ Deserialize, Serialize };
+use utoipa::ToSchema;
+
+use entity::api_key;
+
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({ "name": "Warehouse sync", "scopes": ["product:read", "product:write"], "expires_at": "2027-01-01T00:00:00" }))]
+pub struct CreateApiKeyBody {
+	pub name: String,
+	pub scopes: Vec<String>,
+	// Never expires when left out.
+	pub expires_at: Option<chrono::NaiveDateTime>
+}
+
+// An API key as listed; the key itself is only returned on creation.
+#[derive(Serialize, ToSchema)]
+pub struct ApiKeyData {
+	pub id: i32,
+	pub name: String,
+	// First characters of the key, to tell keys apart.
+	pub prefix: String,
+	pub scopes: Vec<String>,
+	// The admin who created it.
+	pub user_id: i32,
+	pub expires_at: Option<chrono::NaiveDateTime>,
+	// Updated at most once a minute.
+	pub last_used_at: Option<chrono::NaiveDateTime>,
+	pub created_at: chrono::NaiveDateTime
+}
+
+impl From<api_key::Model> for ApiKeyData {
+	fn from(d: api_key::Model) -> Self {
+		Self {
+			scopes: d.scopes.split(',').map(str::to_owned).collect(),
+			id: d.id,
+			name: d.name,
+			prefix: d.prefix,
+			user_id: d.user_id,
+			expires_at: d.expires_at,
+			last_used_at: d.last_used_at,
+			created_at: d.created_at
+		}
+	}
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CreatedApiKeyResponse {
+	pub success: bool,
+	// Shown once; only its hash is stored.
+	pub key: String,
+	pub data: ApiKeyData
+}
+
+// Who made a request authenticated with `X-Api-Key`, in place of `JwtClaims`.
+#[derive(Clone, Debug)]
+pub struct ApiKeyPrincipal {
+	pub key_id: i32,
+	pub scopes: Vec<String>
+}
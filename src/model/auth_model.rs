@@ -1,14 +1,68 @@
-use serde::Deserialize;
+use serde::{ Deserialize, Serialize };
+use utoipa::ToSchema;
 
-#[derive(Deserialize)]
+use crate::model::user_model::UserData;
+
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({ "username": "admin", "password": "secret" }))]
 pub struct LoginBody {
 	pub username: String,
 	pub password: String
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({ "id": 1, "old_password": "secret", "new_password": "correct-horse-battery" }))]
 pub struct ChangePasswordBody {
 	pub id: i32,
 	pub old_password: String,
 	pub new_password: String
-}
\ No newline at end of file
+}
+
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({ "username": "admin", "ip": "203.0.113.7" }))]
+pub struct UnlockBody {
+	pub username: Option<String>,
+	pub ip: Option<String>
+}
+
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({ "user_id": 2 }))]
+pub struct IssueResetTokenBody {
+	pub user_id: i32
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ResetTokenResponse {
+	pub success: bool,
+	// Shown once; hand it to the user over a trusted channel.
+	pub token: String,
+	pub expires_at: chrono::NaiveDateTime
+}
+
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({ "username": "kasir" }))]
+pub struct ForgotPasswordBody {
+	pub username: String
+}
+
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({ "token": "3f9c0a...", "new_password": "correct-horse-battery" }))]
+pub struct ResetPasswordBody {
+	pub token: String,
+	pub new_password: String
+}
+
+// Returned by `login` and `authenticated`.
+#[derive(Serialize, ToSchema)]
+pub struct AuthTokenResponse {
+	pub success: bool,
+	pub data: UserData,
+	pub token: String
+}
+
+// Returned by `me`: the bearer's profile as currently stored.
+#[derive(Serialize, ToSchema)]
+pub struct ProfileResponse {
+	pub success: bool,
+	pub data: UserData
+}
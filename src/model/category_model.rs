@@ -1,7 +1,9 @@
 use serde::{ Deserialize, Serialize };
+use utoipa::ToSchema;
 use crate::model::pagination_model::{ PaginationResponse };
+use entity::category;
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct CategoryData {
 	pub id: i32,
 	pub name: String,
@@ -9,18 +11,31 @@ pub struct CategoryData {
 	pub updated_at: chrono::NaiveDateTime
 }
 
-#[derive(Serialize)]
+impl From<category::Model> for CategoryData {
+	fn from(d: category::Model) -> Self {
+		Self {
+			id: d.id,
+			name: d.name,
+			created_at: d.created_at,
+			updated_at: d.updated_at
+		}
+	}
+}
+
+#[derive(Serialize, ToSchema)]
 pub struct CategoryPaginate {
 	pub data: Vec<CategoryData>,
 	pub paginate: PaginationResponse
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({ "name": "Drinks" }))]
 pub struct CategoryCreateBody {
 	pub name: String
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({ "name": "Beverages" }))]
 pub struct CategoryUpdateBody {
 	pub name: Option<String>
 }
\ No newline at end of file
@@ -0,0 +1,69 @@
+use serde::{ Deserialize, Serialize };
+use utoipa::{ IntoParams, ToSchema };
+use crate::model::product_image_model::ProductImageData;
+
+#[derive(Serialize, ToSchema)]
+pub struct FileUploadResponse {
+	pub id: i32,
+	pub hash: String,
+	pub file_name: String,
+	pub file_extension: String,
+	pub content_type: String,
+	pub size: i64,
+	pub url: String,
+	pub is_private: bool,
+	pub duplicate: bool
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct FileUploadResult {
+	pub field_name: String,
+	pub original_name: String,
+	pub success: bool,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub message: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub file: Option<FileUploadResponse>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub image: Option<ProductImageData>
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct BatchUploadResponse {
+	pub success: bool,
+	pub uploaded: usize,
+	pub failed: usize,
+	pub results: Vec<FileUploadResult>
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SignedFileUrl {
+	pub url: String,
+	pub expires_at: i64
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct SignedUrlQuery {
+	pub expires: Option<i64>,
+	pub signature: Option<String>
+}
+
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({ "is_private": true }))]
+pub struct FileVisibilityBody {
+	pub is_private: bool
+}
+
+// Documents the multipart body of an upload; form fields apply to the files sent after them.
+#[allow(dead_code)]
+#[derive(ToSchema)]
+pub struct FileUploadForm {
+	// Product bucket only: adds each following file to this product's gallery.
+	pub product_id: Option<i32>,
+	pub alt_text: Option<String>,
+	pub is_primary: Option<bool>,
+	// `private` stores the following files behind signed URLs.
+	pub visibility: Option<String>,
+	#[schema(value_type = Vec<String>, format = Binary)]
+	pub file: Vec<Vec<u8>>
+}
@@ -0,0 +1,19 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+#[schema(example = json!({ "status": "ok" }))]
+pub struct LivenessResponse {
+	pub status: String
+}
+
+// `status` is `ready` only when every check passes and no shutdown is under way.
+#[derive(Serialize, ToSchema)]
+#[schema(example = json!({ "status": "ready", "database": "ok", "pending_migrations": 0, "shutting_down": false }))]
+pub struct ReadinessResponse {
+	pub status: String,
+	pub database: String,
+	// `null` when the migration table couldn't be read.
+	pub pending_migrations: Option<usize>,
+	pub shutting_down: bool
+}
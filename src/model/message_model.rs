@@ -0,0 +1,10 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+// Shape of every plain success or error reply.
+#[derive(Serialize, ToSchema)]
+#[schema(example = json!({ "success": false, "message": "Data Not Found!!!" }))]
+pub struct MessageResponse {
+	pub success: bool,
+	pub message: String
+}
@@ -1,5 +1,13 @@
 pub mod category_model;
 pub mod pagination_model;
 pub mod product_model;
+pub mod product_image_model;
 pub mod user_model;
-pub mod auth_model;
\ No newline at end of file
+pub mod auth_model;
+pub mod file_model;
+pub mod message_model;
+pub mod health_model;
+pub mod two_factor_model;
+pub mod session_model;
+pub mod api_key_model;
+pub mod oidc_model;
@@ -0,0 +1,19 @@
+use serde::{ Deserialize, Serialize };
+use utoipa::{ IntoParams, ToSchema };
+
+// What the identity provider appends to `redirect_uri`: `code` and `state`
+// on success, `error` when the user or the provider cancelled the login.
+#[derive(Deserialize, IntoParams)]
+pub struct OidcCallbackQuery {
+	pub code: Option<String>,
+	pub state: Option<String>,
+	pub error: Option<String>,
+	pub error_description: Option<String>
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct OidcLoginResponse {
+	pub success: bool,
+	// Also in `Location`; open it in the browser.
+	pub authorization_url: String
+}
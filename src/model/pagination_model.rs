@@ -1,6 +1,7 @@
 use serde::{ Deserialize, Serialize };
+use utoipa::ToSchema;
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct PaginationResponse {
 	pub per_page: i64,
 	pub total_page: i64,
@@ -8,7 +9,19 @@ pub struct PaginationResponse {
 	pub current_page: i64
 }
 
-#[derive(Deserialize)]
+impl PaginationResponse {
+	pub fn new(count: u64, current_page: i64, per_page: i64) -> Self {
+		Self {
+			per_page,
+			total_page: ((count as f64 / per_page as f64) + 0.4).round() as i64,
+			count: count as i64,
+			current_page
+		}
+	}
+}
+
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({ "term": "tea", "page": 1 }))]
 pub struct PaginationBody {
 	pub term: String,
 	pub page: i64
@@ -0,0 +1,29 @@
+use serde::{ Deserialize, Serialize };
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+pub struct ProductImageData {
+	pub id: i32,
+	pub product_id: i32,
+	pub file_name: String,
+	pub url: String,
+	pub alt_text: String,
+	pub position: i32,
+	pub is_primary: bool,
+	pub created_at: chrono::NaiveDateTime,
+	pub updated_at: chrono::NaiveDateTime
+}
+
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({ "file_name": "3bb6219eb5b1e04fcf0c9e40ead6012f7282252590ade3dece71d79a0d7cc762.png", "alt_text": "Front", "is_primary": true }))]
+pub struct ProductImageCreateBody {
+	pub file_name: String,
+	pub alt_text: Option<String>,
+	pub is_primary: Option<bool>
+}
+
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({ "image_ids": [3, 1, 2] }))]
+pub struct ProductImageReorderBody {
+	pub image_ids: Vec<i32>
+}
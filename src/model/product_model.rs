@@ -1,8 +1,9 @@
 use serde::{ Deserialize, Serialize };
+use utoipa::ToSchema;
 use crate::model::category_model::CategoryData;
 use crate::model::pagination_model::PaginationResponse;
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct ProductWithCategoryData {
 	pub id: i32,
 	pub name: String,
@@ -14,32 +15,29 @@ pub struct ProductWithCategoryData {
 	pub image: String,
 	pub category_id: i32,
 	pub category: CategoryData,
+	pub primary_image_url: Option<String>,
+	pub image_count: i64,
 	pub created_at: chrono::NaiveDateTime,
 	pub updated_at: chrono::NaiveDateTime
 }
 
-#[derive(Serialize)]
-pub struct ProductData {
-	pub id: i32,
-	pub name: String,
-	pub description: String,
-	pub purchase_price: i32,
-	pub selling_price: i32,
-	pub stock: i32,
-	pub discount: i32,
-	pub image: String,
-	pub category_id: i32,
-	pub created_at: chrono::NaiveDateTime,
-	pub updated_at: chrono::NaiveDateTime
-}
-
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct ProductPaginate {
 	pub data: Vec<ProductWithCategoryData>,
 	pub paginate: PaginationResponse
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({
+	"name": "Green Tea",
+	"description": "Bottled green tea, 350 ml",
+	"purchase_price": 3000,
+	"selling_price": 4500,
+	"stock": 24,
+	"discount": 0,
+	"image": "",
+	"category_id": 1
+}))]
 pub struct ProductCreateDto {
 	pub name: String,
 	pub description: String,
@@ -51,7 +49,8 @@ pub struct ProductCreateDto {
 	pub category_id: i32
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({ "stock": 12, "selling_price": 5000 }))]
 pub struct ProductUpdateDto {
 	pub name: Option<String>,
 	pub description: Option<String>,
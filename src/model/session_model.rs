@@ -0,0 +1,39 @@
+use serde::{ Deserialize, Serialize };
+use utoipa::{ IntoParams, ToSchema };
+
+use entity::user_session;
+
+// One of the user's active logins.
+#[derive(Serialize, ToSchema)]
+pub struct SessionData {
+	pub id: i32,
+	pub user_agent: String,
+	pub ip: Option<String>,
+	pub created_at: chrono::NaiveDateTime,
+	// Updated at most once a minute.
+	pub last_seen_at: chrono::NaiveDateTime,
+	pub expires_at: chrono::NaiveDateTime,
+	// The session of the token making the request.
+	pub current: bool
+}
+
+impl SessionData {
+	pub fn from_model(d: user_session::Model, current_sid: &str) -> Self {
+		Self {
+			current: d.sid == current_sid,
+			id: d.id,
+			user_agent: d.user_agent,
+			ip: d.ip,
+			created_at: d.created_at,
+			last_seen_at: d.last_seen_at,
+			expires_at: d.expires_at
+		}
+	}
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct RevokeSessionsQuery {
+	// Keep the caller's own session, i.e. log out everywhere else.
+	#[serde(default)]
+	pub except_current: bool
+}
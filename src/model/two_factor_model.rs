@@ -0,0 +1,73 @@
+use serde::{ Deserialize, Serialize };
+use utoipa::ToSchema;
+
+use crate::model::user_model::UserData;
+
+// Claims of the short-lived token handed out between the password and the
+// second factor. Its own `aud` keeps `auth_guard` from accepting it.
+#[derive(Serialize, Deserialize)]
+pub struct ChallengeClaims {
+	#[serde(with = "crate::model::user_model::user_id_string")]
+	pub sub: i32,
+	// `login` to finish a login with a code, `enroll` to set up 2FA first.
+	pub purpose: String,
+	pub exp: usize,
+	pub iss: String,
+	pub aud: String,
+	// Recorded once the challenge is redeemed, so it can't be replayed.
+	pub jti: String
+}
+
+// Returned by `login` instead of a token when a second factor is needed.
+#[derive(Serialize, ToSchema)]
+#[schema(example = json!({
+	"success": true,
+	"two_factor_required": true,
+	"enrollment_required": false,
+	"challenge_token": "eyJ0eXAiOiJKV1QiLCJhbGciOiJIUzI1NiJ9...",
+	"expires_in": 300
+}))]
+pub struct TwoFactorChallengeResponse {
+	pub success: bool,
+	pub two_factor_required: bool,
+	// The role requires 2FA and none is set up: enrol and confirm with the
+	// challenge token, which also finishes the login.
+	pub enrollment_required: bool,
+	pub challenge_token: String,
+	pub expires_in: u64
+}
+
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({ "challenge_token": "eyJ0eXAiOiJKV1QiLCJhbGciOiJIUzI1NiJ9...", "code": "123456" }))]
+pub struct TwoFactorLoginBody {
+	pub challenge_token: String,
+	// A current TOTP code or an unused recovery code.
+	pub code: String
+}
+
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({ "code": "123456" }))]
+pub struct TwoFactorCodeBody {
+	pub code: String
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct TwoFactorEnrollResponse {
+	pub success: bool,
+	// Base32, for manual entry.
+	pub secret: String,
+	// For a QR code.
+	pub otpauth_uri: String
+}
+
+// Recovery codes are only ever shown here, once.
+#[derive(Serialize, ToSchema)]
+pub struct RecoveryCodesResponse {
+	pub success: bool,
+	pub recovery_codes: Vec<String>,
+	// Set when the confirmation finished an enrolment-required login.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub token: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub data: Option<UserData>
+}
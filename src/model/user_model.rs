@@ -1,5 +1,8 @@
 use serde::{ Deserialize, Serialize };
-#[derive(Serialize, Deserialize, Clone)]
+use utoipa::ToSchema;
+use entity::user;
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
 pub struct UserData {
 	pub id: i32,
 	pub username: String,
@@ -13,7 +16,34 @@ pub struct UserData {
 	pub updated_at: chrono::NaiveDateTime
 }
 
-#[derive(Deserialize)]
+// The password hash never leaves the server.
+impl From<user::Model> for UserData {
+	fn from(d: user::Model) -> Self {
+		Self {
+			id: d.id,
+			username: d.username,
+			password: String::new(),
+			full_name: d.full_name,
+			address: d.address,
+			phone_number: d.phone_number,
+			role: d.role,
+			photo: d.photo,
+			created_at: d.created_at,
+			updated_at: d.updated_at
+		}
+	}
+}
+
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({
+	"username": "kasir",
+	"password": "kasir-secret-1",
+	"full_name": "Kasir Satu",
+	"address": "Jl. Merdeka 1",
+	"phone_number": "081234567890",
+	"role": "cashier",
+	"photo": ""
+}))]
 pub struct UserCreateBody {
 	pub username: String,
 	pub password: String,
@@ -24,10 +54,13 @@ pub struct UserCreateBody {
 	pub photo: String
 }
 
-#[derive(Deserialize)]
+// Passwords change through change-password or a reset, never here; a body
+// that still sends one is rejected rather than silently ignored.
+#[derive(Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+#[schema(example = json!({ "full_name": "Kasir Dua", "role": "cashier" }))]
 pub struct UserUpdateBody {
 	pub username: Option<String>,
-	pub password: Option<String>,
 	pub full_name: Option<String>,
 	pub address: Option<String>,
 	pub phone_number: Option<String>,
@@ -35,8 +68,35 @@ pub struct UserUpdateBody {
 	pub photo: Option<String>
 }
 
-#[derive(Serialize, Deserialize)]
+// Only what authorisation needs; the profile is at `/api/auth/me`.
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
 pub struct JwtClaims {
-	pub user_data: UserData,
+	// The user id, as a string like RFC 7519 asks.
+	#[serde(with = "user_id_string")]
+	#[schema(value_type = String)]
+	pub sub: i32,
+	pub iat: usize,
 	pub exp: usize,
+	pub jti: String,
+	pub iss: String,
+	pub aud: String,
+	// The role at issue; `auth_guard` replaces it with the current one.
+	pub role: String,
+	// The user's `token_version` at issue; a token from before the last
+	// revocation (e.g. a password reset) is rejected.
+	pub token_version: i32,
+	// The server-side session; revoking it ends the token.
+	pub sid: String
+}
+
+pub(crate) mod user_id_string {
+	use serde::{ Deserialize, Deserializer, Serializer };
+
+	pub fn serialize<S: Serializer>(id: &i32, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.collect_str(id)
+	}
+
+	pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i32, D::Error> {
+		String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+	}
 }
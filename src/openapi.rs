@@ -0,0 +1,56 @@
+use utoipa::{
+	openapi::{ security::{ ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme }, OpenApi as OpenApiDocument },
+	Modify, OpenApi
+};
+
+use crate::model::message_model::MessageResponse;
+use crate::model::user_model::JwtClaims;
+
+// Document metadata and shared components. The paths themselves are collected
+// from the route table in `app()`, so a route can't be served undocumented by accident.
+#[derive(OpenApi)]
+#[openapi(
+	info(
+		title = "Rust Axum SeaORM",
+		description = "Point-of-sale backend: categories, products with image galleries, users and file storage."
+	),
+	modifiers(&BearerAuth, &ApiKeyAuth),
+	components(schemas(JwtClaims, MessageResponse)),
+	tags(
+		(name = "auth", description = "Login, two-factor authentication and token refresh"),
+		(name = "category", description = "Product categories"),
+		(name = "product", description = "Products and their search"),
+		(name = "product image", description = "Ordered image galleries of products"),
+		(name = "user", description = "User accounts"),
+		(name = "api key", description = "Scoped keys of machine-to-machine integrations"),
+		(name = "files", description = "Uploads, downloads and signed URLs of file buckets"),
+		(name = "health", description = "Liveness and readiness probes")
+	)
+)]
+pub struct ApiDoc;
+
+// `Authorization: Bearer <token>` with a token from `/api/auth/login`.
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+	fn modify(&self, openapi: &mut OpenApiDocument) {
+		let components = openapi.components.get_or_insert_with(Default::default);
+
+		components.add_security_scheme(
+			"bearer_auth",
+			SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).bearer_format("JWT").build())
+		);
+	}
+}
+
+// `X-Api-Key: <key>` with a key from `/api/api-keys`. Accepted only by the
+// operations listing it, with the scope they require.
+struct ApiKeyAuth;
+
+impl Modify for ApiKeyAuth {
+	fn modify(&self, openapi: &mut OpenApiDocument) {
+		let components = openapi.components.get_or_insert_with(Default::default);
+
+		components.add_security_scheme("api_key", SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-Api-Key"))));
+	}
+}
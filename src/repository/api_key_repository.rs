@@ -0,0 +1,90 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sea_orm::{
+	ActiveModelTrait, ActiveValue::{ NotSet, Set }, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter,
+	QueryOrder
+};
+
+use entity::api_key;
+
+#[async_trait]
+pub trait ApiKeyRepository: Send + Sync {
+	async fn insert(
+		&self,
+		user_id: i32,
+		name: &str,
+		prefix: &str,
+		key_hash: &str,
+		scopes: &str,
+		expires_at: Option<NaiveDateTime>
+	) -> Result<api_key::Model, DbErr>;
+	async fn find_by_hash(&self, key_hash: &str) -> Result<Option<api_key::Model>, DbErr>;
+	// Newest first.
+	async fn find_many(&self) -> Result<Vec<api_key::Model>, DbErr>;
+	async fn touch(&self, id: i32, now: NaiveDateTime) -> Result<(), DbErr>;
+	// False when there is no key with this id.
+	async fn delete(&self, id: i32) -> Result<bool, DbErr>;
+}
+
+pub struct SeaOrmApiKeyRepository {
+	db: DatabaseConnection
+}
+
+impl SeaOrmApiKeyRepository {
+	pub fn new(db: DatabaseConnection) -> Self {
+		Self { db }
+	}
+}
+
+#[async_trait]
+impl ApiKeyRepository for SeaOrmApiKeyRepository {
+	async fn insert(
+		&self,
+		user_id: i32,
+		name: &str,
+		prefix: &str,
+		key_hash: &str,
+		scopes: &str,
+		expires_at: Option<NaiveDateTime>
+	) -> Result<api_key::Model, DbErr> {
+		api_key::ActiveModel {
+			id: NotSet,
+			user_id: Set(user_id),
+			name: Set(name.to_owned()),
+			prefix: Set(prefix.to_owned()),
+			key_hash: Set(key_hash.to_owned()),
+			scopes: Set(scopes.to_owned()),
+			expires_at: Set(expires_at),
+			last_used_at: Set(None),
+			created_at: NotSet
+		}.insert(&self.db).await
+	}
+
+	async fn find_by_hash(&self, key_hash: &str) -> Result<Option<api_key::Model>, DbErr> {
+		api_key::Entity::find()
+		.filter(api_key::Column::KeyHash.eq(key_hash))
+		.one(&self.db).await
+	}
+
+	async fn find_many(&self) -> Result<Vec<api_key::Model>, DbErr> {
+		api_key::Entity::find()
+		.order_by_desc(api_key::Column::CreatedAt)
+		.order_by_desc(api_key::Column::Id)
+		.all(&self.db).await
+	}
+
+	async fn touch(&self, id: i32, now: NaiveDateTime) -> Result<(), DbErr> {
+		api_key::Entity::update_many()
+		.col_expr(api_key::Column::LastUsedAt, now.into())
+		.filter(api_key::Column::Id.eq(id))
+		.exec(&self.db).await?;
+
+		Ok(())
+	}
+
+	async fn delete(&self, id: i32) -> Result<bool, DbErr> {
+		let result = api_key::Entity::delete_by_id(id).exec(&self.db).await?;
+
+		Ok(result.rows_affected > 0)
+	}
+}
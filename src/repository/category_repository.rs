@@ -0,0 +1,77 @@
+use async_trait::async_trait;
+use sea_orm::{
+	ActiveModelTrait, ActiveValue::{ NotSet, Set }, Condition, DatabaseConnection, DbErr,
+	EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect
+};
+
+use crate::repository::name_contains;
+
+use entity::category;
+
+#[async_trait]
+pub trait CategoryRepository: Send + Sync {
+	async fn find_all(&self) -> Result<Vec<category::Model>, DbErr>;
+	async fn search(&self, term: &str, offset: u64, limit: u64) -> Result<Vec<category::Model>, DbErr>;
+	async fn count_search(&self, term: &str) -> Result<u64, DbErr>;
+	async fn find_by_id(&self, id: i32) -> Result<Option<category::Model>, DbErr>;
+	async fn insert(&self, name: String) -> Result<category::Model, DbErr>;
+	async fn update(&self, model: category::Model) -> Result<category::Model, DbErr>;
+	async fn delete_by_id(&self, id: i32) -> Result<u64, DbErr>;
+}
+
+pub struct SeaOrmCategoryRepository {
+	db: DatabaseConnection
+}
+
+impl SeaOrmCategoryRepository {
+	pub fn new(db: DatabaseConnection) -> Self {
+		Self { db }
+	}
+}
+
+#[async_trait]
+impl CategoryRepository for SeaOrmCategoryRepository {
+	async fn find_all(&self) -> Result<Vec<category::Model>, DbErr> {
+		category::Entity::find().order_by_asc(category::Column::Name).all(&self.db).await
+	}
+
+	async fn search(&self, term: &str, offset: u64, limit: u64) -> Result<Vec<category::Model>, DbErr> {
+		category::Entity::find().filter(
+			Condition::any().add(
+				name_contains(category::Column::Name, term)
+			)
+		).order_by_asc(category::Column::Name).offset(offset).limit(limit).all(&self.db).await
+	}
+
+	async fn count_search(&self, term: &str) -> Result<u64, DbErr> {
+		category::Entity::find().filter(
+			Condition::any().add(
+				name_contains(category::Column::Name, term)
+			)
+		).count(&self.db).await
+	}
+
+	async fn find_by_id(&self, id: i32) -> Result<Option<category::Model>, DbErr> {
+		category::Entity::find_by_id(id).one(&self.db).await
+	}
+
+	async fn insert(&self, name: String) -> Result<category::Model, DbErr> {
+		category::ActiveModel {
+			name: Set(name),
+			..Default::default()
+		}.insert(&self.db).await
+	}
+
+	async fn update(&self, model: category::Model) -> Result<category::Model, DbErr> {
+		category::ActiveModel {
+			id: Set(model.id),
+			name: Set(model.name),
+			created_at: NotSet,
+			updated_at: Set(model.updated_at)
+		}.update(&self.db).await
+	}
+
+	async fn delete_by_id(&self, id: i32) -> Result<u64, DbErr> {
+		Ok(category::Entity::delete_by_id(id).exec(&self.db).await?.rows_affected)
+	}
+}
@@ -0,0 +1,224 @@
+// In-memory repositories for service unit tests. They keep only the rules
+// services rely on; ordering and primary-image handling follow the SeaORM
+// implementations, foreign keys and `product.image` syncing are not modelled.
+
+use async_trait::async_trait;
+use sea_orm::DbErr;
+use std::sync::Mutex;
+
+use crate::repository::product_image_repository::ProductImageRepository;
+use crate::repository::product_repository::ProductRepository;
+use crate::repository::stored_file_repository::StoredFileRepository;
+
+use entity::{ category, product, product_image, stored_file };
+
+fn now() -> chrono::NaiveDateTime {
+	chrono::Utc::now().naive_utc()
+}
+
+#[derive(Default)]
+pub struct InMemoryProductRepository {
+	products: Mutex<Vec<product::Model>>
+}
+
+#[async_trait]
+impl ProductRepository for InMemoryProductRepository {
+	async fn search_with_category(&self, term: &str) -> Result<Vec<(product::Model, Option<category::Model>)>, DbErr> {
+		let term = term.to_lowercase();
+
+		Ok(self.products.lock().unwrap().iter()
+		.filter(|d| d.name.to_lowercase().contains(&term))
+		.map(|d| (d.clone(), None))
+		.collect())
+	}
+
+	async fn count_search(&self, term: &str) -> Result<u64, DbErr> {
+		Ok(self.search_with_category(term).await?.len() as u64)
+	}
+
+	async fn count_low_stock(&self, threshold: i32) -> Result<u64, DbErr> {
+		Ok(self.products.lock().unwrap().iter().filter(|d| d.stock < threshold).count() as u64)
+	}
+
+	async fn find_by_id(&self, id: i32) -> Result<Option<product::Model>, DbErr> {
+		Ok(self.products.lock().unwrap().iter().find(|d| d.id == id).cloned())
+	}
+
+	async fn insert(&self, model: product::Model) -> Result<product::Model, DbErr> {
+		let mut products = self.products.lock().unwrap();
+		let id = products.iter().map(|d| d.id).max().unwrap_or(0) + 1;
+		let model = product::Model { id, created_at: now(), updated_at: now(), ..model };
+
+		products.push(model.clone());
+
+		Ok(model)
+	}
+
+	async fn update(&self, model: product::Model) -> Result<product::Model, DbErr> {
+		let mut products = self.products.lock().unwrap();
+
+		match products.iter_mut().find(|d| d.id == model.id) {
+			Some(val) => {
+				*val = product::Model { created_at: val.created_at, updated_at: now(), ..model };
+				Ok(val.clone())
+			},
+			None => Err(DbErr::RecordNotFound(format!("product {}", model.id)))
+		}
+	}
+
+	async fn delete_by_id(&self, id: i32) -> Result<u64, DbErr> {
+		let mut products = self.products.lock().unwrap();
+		let before = products.len();
+
+		products.retain(|d| d.id != id);
+
+		Ok((before - products.len()) as u64)
+	}
+}
+
+#[derive(Default)]
+pub struct InMemoryProductImageRepository {
+	images: Mutex<Vec<product_image::Model>>
+}
+
+fn gallery(images: &[product_image::Model], product_id: i32) -> Vec<product_image::Model> {
+	let mut gallery: Vec<product_image::Model> = images.iter().filter(|d| d.product_id == product_id).cloned().collect();
+
+	gallery.sort_by_key(|d| (d.position, d.id));
+
+	gallery
+}
+
+fn mark_primary(images: &mut [product_image::Model], image: &product_image::Model) {
+	for val in images.iter_mut().filter(|d| d.product_id == image.product_id) {
+		val.is_primary = val.id == image.id;
+	}
+}
+
+#[async_trait]
+impl ProductImageRepository for InMemoryProductImageRepository {
+	async fn find_by_product(&self, product_id: i32) -> Result<Vec<product_image::Model>, DbErr> {
+		Ok(gallery(&self.images.lock().unwrap(), product_id))
+	}
+
+	async fn find_for_products(&self, product_ids: Vec<i32>) -> Result<Vec<product_image::Model>, DbErr> {
+		Ok(self.images.lock().unwrap().iter().filter(|d| product_ids.contains(&d.product_id)).cloned().collect())
+	}
+
+	async fn find(&self, product_id: i32, image_id: i32) -> Result<Option<product_image::Model>, DbErr> {
+		Ok(self.images.lock().unwrap().iter().find(|d| d.id == image_id && d.product_id == product_id).cloned())
+	}
+
+	async fn attach(
+		&self,
+		product_id: i32,
+		file_name: String,
+		alt_text: String,
+		make_primary: bool
+	) -> Result<product_image::Model, DbErr> {
+		let mut images = self.images.lock().unwrap();
+		let current = gallery(&images, product_id);
+
+		let image = product_image::Model {
+			id: images.iter().map(|d| d.id).max().unwrap_or(0) + 1,
+			product_id,
+			file_name,
+			alt_text,
+			position: current.iter().map(|d| d.position + 1).max().unwrap_or(0),
+			is_primary: make_primary || !current.iter().any(|d| d.is_primary),
+			created_at: now(),
+			updated_at: now()
+		};
+
+		images.push(image.clone());
+
+		if image.is_primary {
+			mark_primary(&mut images, &image);
+		}
+
+		Ok(image)
+	}
+
+	async fn reorder(&self, product_id: i32, image_ids: Vec<i32>) -> Result<(), DbErr> {
+		let mut images = self.images.lock().unwrap();
+
+		for (position, image_id) in image_ids.iter().enumerate() {
+			if let Some(val) = images.iter_mut().find(|d| d.id == *image_id && d.product_id == product_id) {
+				val.position = position as i32;
+			}
+		}
+
+		Ok(())
+	}
+
+	async fn set_primary(&self, image: &product_image::Model) -> Result<(), DbErr> {
+		mark_primary(&mut self.images.lock().unwrap(), image);
+
+		Ok(())
+	}
+
+	async fn detach(&self, image: &product_image::Model) -> Result<(), DbErr> {
+		let mut images = self.images.lock().unwrap();
+
+		images.retain(|d| d.id != image.id);
+
+		if image.is_primary {
+			if let Some(next) = gallery(&images, image.product_id).first() {
+				mark_primary(&mut images, next);
+			}
+		}
+
+		Ok(())
+	}
+
+	async fn count_references(&self, file_name: &str) -> Result<u64, DbErr> {
+		Ok(self.images.lock().unwrap().iter().filter(|d| d.file_name == file_name).count() as u64)
+	}
+}
+
+#[derive(Default)]
+pub struct InMemoryStoredFileRepository {
+	files: Mutex<Vec<stored_file::Model>>
+}
+
+#[async_trait]
+impl StoredFileRepository for InMemoryStoredFileRepository {
+	async fn find_by_hash(&self, namespace: &str, hash: &str) -> Result<Option<stored_file::Model>, DbErr> {
+		Ok(self.files.lock().unwrap().iter().find(|d| d.namespace == namespace && d.hash == hash).cloned())
+	}
+
+	async fn find_by_name(&self, namespace: &str, file_name: &str) -> Result<Option<stored_file::Model>, DbErr> {
+		Ok(self.files.lock().unwrap().iter().find(|d| d.namespace == namespace && d.file_name == file_name).cloned())
+	}
+
+	async fn insert(&self, model: stored_file::Model) -> Result<stored_file::Model, DbErr> {
+		let mut files = self.files.lock().unwrap();
+
+		if files.iter().any(|d| d.namespace == model.namespace && d.hash == model.hash) {
+			return Err(DbErr::Custom(format!("duplicate stored_file {}", model.hash)));
+		}
+
+		let model = stored_file::Model { id: files.iter().map(|d| d.id).max().unwrap_or(0) + 1, created_at: now(), ..model };
+
+		files.push(model.clone());
+
+		Ok(model)
+	}
+
+	async fn set_private(&self, id: i32, is_private: bool) -> Result<(), DbErr> {
+		if let Some(val) = self.files.lock().unwrap().iter_mut().find(|d| d.id == id) {
+			val.is_private = is_private;
+		}
+
+		Ok(())
+	}
+
+	async fn delete_by_name(&self, namespace: &str, file_name: &str) -> Result<u64, DbErr> {
+		let mut files = self.files.lock().unwrap();
+		let before = files.len();
+
+		files.retain(|d| !(d.namespace == namespace && d.file_name == file_name));
+
+		Ok((before - files.len()) as u64)
+	}
+}
@@ -0,0 +1,127 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sea_orm::{
+	sea_query::{ Expr, OnConflict }, ActiveValue::Set, ColumnTrait, Condition, DatabaseConnection, DbErr,
+	EntityTrait, QueryFilter
+};
+
+use entity::login_attempt;
+
+#[async_trait]
+pub trait LoginAttemptRepository: Send + Sync {
+	async fn find(&self, scope: &str, key: &str) -> Result<Option<login_attempt::Model>, DbErr>;
+	// Counts one failure in a single statement, so concurrent failures can't
+	// overwrite each other. The count restarts at 1 when no lock is active and
+	// the previous failure is older than `window_start`. Returns the row after
+	// the update.
+	async fn increment(
+		&self,
+		scope: &str,
+		key: &str,
+		now: NaiveDateTime,
+		window_start: NaiveDateTime
+	) -> Result<login_attempt::Model, DbErr>;
+	// Locks the row until `locked_until` if it has at least `max_failures` and
+	// isn't locked yet; true when this call set the lock.
+	async fn lock(
+		&self,
+		id: i32,
+		max_failures: i32,
+		now: NaiveDateTime,
+		locked_until: NaiveDateTime
+	) -> Result<bool, DbErr>;
+	async fn delete(&self, scope: &str, key: &str) -> Result<u64, DbErr>;
+}
+
+pub struct SeaOrmLoginAttemptRepository {
+	db: DatabaseConnection
+}
+
+impl SeaOrmLoginAttemptRepository {
+	pub fn new(db: DatabaseConnection) -> Self {
+		Self { db }
+	}
+}
+
+fn unlocked(now: NaiveDateTime) -> Condition {
+	Condition::any()
+	.add(login_attempt::Column::LockedUntil.is_null())
+	.add(login_attempt::Column::LockedUntil.lte(now))
+}
+
+#[async_trait]
+impl LoginAttemptRepository for SeaOrmLoginAttemptRepository {
+	async fn find(&self, scope: &str, key: &str) -> Result<Option<login_attempt::Model>, DbErr> {
+		login_attempt::Entity::find()
+		.filter(login_attempt::Column::Scope.eq(scope))
+		.filter(login_attempt::Column::Key.eq(key))
+		.one(&self.db).await
+	}
+
+	async fn increment(
+		&self,
+		scope: &str,
+		key: &str,
+		now: NaiveDateTime,
+		window_start: NaiveDateTime
+	) -> Result<login_attempt::Model, DbErr> {
+		// Make sure the row exists; a concurrent insert of the same key is fine.
+		login_attempt::Entity::insert(login_attempt::ActiveModel {
+			scope: Set(scope.to_owned()),
+			key: Set(key.to_owned()),
+			failures: Set(0),
+			last_failure_at: Set(now),
+			locked_until: Set(None),
+			..Default::default()
+		})
+		.on_conflict(
+			OnConflict::columns([login_attempt::Column::Scope, login_attempt::Column::Key])
+			// MySQL has no DO NOTHING; it gets `ON DUPLICATE KEY UPDATE id = id`.
+			.do_nothing_on([login_attempt::Column::Id])
+			.to_owned()
+		)
+		.exec_without_returning(&self.db).await?;
+
+		let expired = Condition::all()
+		.add(unlocked(now))
+		.add(login_attempt::Column::LastFailureAt.lt(window_start));
+
+		login_attempt::Entity::update_many()
+		.col_expr(
+			login_attempt::Column::Failures,
+			Expr::case(expired, 1).finally(Expr::col(login_attempt::Column::Failures).add(1)).into()
+		)
+		.col_expr(login_attempt::Column::LastFailureAt, Expr::value(now))
+		.filter(login_attempt::Column::Scope.eq(scope))
+		.filter(login_attempt::Column::Key.eq(key))
+		.exec(&self.db).await?;
+
+		self.find(scope, key).await?.ok_or_else(|| DbErr::RecordNotFound(format!("login_attempt {scope}:{key}")))
+	}
+
+	async fn lock(
+		&self,
+		id: i32,
+		max_failures: i32,
+		now: NaiveDateTime,
+		locked_until: NaiveDateTime
+	) -> Result<bool, DbErr> {
+		let result = login_attempt::Entity::update_many()
+		.col_expr(login_attempt::Column::LockedUntil, Expr::value(locked_until))
+		.filter(login_attempt::Column::Id.eq(id))
+		.filter(login_attempt::Column::Failures.gte(max_failures))
+		.filter(unlocked(now))
+		.exec(&self.db).await?;
+
+		Ok(result.rows_affected == 1)
+	}
+
+	async fn delete(&self, scope: &str, key: &str) -> Result<u64, DbErr> {
+		Ok(
+			login_attempt::Entity::delete_many()
+			.filter(login_attempt::Column::Scope.eq(scope))
+			.filter(login_attempt::Column::Key.eq(key))
+			.exec(&self.db).await?.rows_affected
+		)
+	}
+}
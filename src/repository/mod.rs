@@ -0,0 +1,25 @@
+pub mod api_key_repository;
+pub mod category_repository;
+#[cfg(test)]
+pub mod in_memory;
+pub mod login_attempt_repository;
+pub mod oidc_repository;
+pub mod password_history_repository;
+pub mod password_reset_repository;
+pub mod product_repository;
+pub mod product_image_repository;
+pub mod session_repository;
+pub mod stored_file_repository;
+pub mod two_factor_repository;
+pub mod user_repository;
+
+use sea_orm::sea_query::{ Expr, Func, LikeExpr, SimpleExpr };
+use sea_orm::ColumnTrait;
+
+// Case-insensitive substring match that works on every supported backend:
+// `LOWER(col) LIKE '%term%'`, with the term's own wildcards escaped.
+pub fn name_contains<C: ColumnTrait>(column: C, term: &str) -> SimpleExpr {
+	let escaped = term.to_lowercase().replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+
+	Expr::expr(Func::lower(Expr::col((column.entity_name(), column)))).like(LikeExpr::new(format!("%{escaped}%")).escape('\\'))
+}
@@ -0,0 +1,88 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sea_orm::{
+	ActiveModelTrait, ActiveValue::{ NotSet, Set }, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter
+};
+
+use entity::{ oidc_login_state, user_identity };
+
+#[async_trait]
+pub trait OidcRepository: Send + Sync {
+	async fn insert_state(&self, state: &str, code_verifier: &str, nonce: &str, expires_at: NaiveDateTime) -> Result<(), DbErr>;
+	// Removes and returns the login with this state if it hasn't expired at
+	// `now`, so that each state is redeemed once.
+	async fn take_state(&self, state: &str, now: NaiveDateTime) -> Result<Option<oidc_login_state::Model>, DbErr>;
+	async fn delete_expired_states(&self, now: NaiveDateTime) -> Result<u64, DbErr>;
+	async fn find_identity(&self, issuer: &str, subject: &str) -> Result<Option<user_identity::Model>, DbErr>;
+	async fn link_identity(&self, user_id: i32, issuer: &str, subject: &str) -> Result<user_identity::Model, DbErr>;
+}
+
+pub struct SeaOrmOidcRepository {
+	db: DatabaseConnection
+}
+
+impl SeaOrmOidcRepository {
+	pub fn new(db: DatabaseConnection) -> Self {
+		Self { db }
+	}
+}
+
+#[async_trait]
+impl OidcRepository for SeaOrmOidcRepository {
+	async fn insert_state(&self, state: &str, code_verifier: &str, nonce: &str, expires_at: NaiveDateTime) -> Result<(), DbErr> {
+		oidc_login_state::ActiveModel {
+			id: NotSet,
+			state: Set(state.to_owned()),
+			code_verifier: Set(code_verifier.to_owned()),
+			nonce: Set(nonce.to_owned()),
+			expires_at: Set(expires_at)
+		}.insert(&self.db).await?;
+
+		Ok(())
+	}
+
+	async fn take_state(&self, state: &str, now: NaiveDateTime) -> Result<Option<oidc_login_state::Model>, DbErr> {
+		let found = oidc_login_state::Entity::find()
+		.filter(oidc_login_state::Column::State.eq(state))
+		.one(&self.db).await?;
+
+		let model = match found {
+			Some(val) => val,
+			None => return Ok(None)
+		};
+
+		// Only the request that deletes the row gets to use it.
+		let deleted = oidc_login_state::Entity::delete_by_id(model.id).exec(&self.db).await?;
+
+		if deleted.rows_affected == 0 || model.expires_at <= now {
+			return Ok(None);
+		}
+
+		Ok(Some(model))
+	}
+
+	async fn delete_expired_states(&self, now: NaiveDateTime) -> Result<u64, DbErr> {
+		let result = oidc_login_state::Entity::delete_many()
+		.filter(oidc_login_state::Column::ExpiresAt.lte(now))
+		.exec(&self.db).await?;
+
+		Ok(result.rows_affected)
+	}
+
+	async fn find_identity(&self, issuer: &str, subject: &str) -> Result<Option<user_identity::Model>, DbErr> {
+		user_identity::Entity::find()
+		.filter(user_identity::Column::Issuer.eq(issuer))
+		.filter(user_identity::Column::Subject.eq(subject))
+		.one(&self.db).await
+	}
+
+	async fn link_identity(&self, user_id: i32, issuer: &str, subject: &str) -> Result<user_identity::Model, DbErr> {
+		user_identity::ActiveModel {
+			id: NotSet,
+			user_id: Set(user_id),
+			issuer: Set(issuer.to_owned()),
+			subject: Set(subject.to_owned()),
+			created_at: NotSet
+		}.insert(&self.db).await
+	}
+}
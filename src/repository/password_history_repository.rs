@@ -0,0 +1,69 @@
+use async_trait::async_trait;
+use sea_orm::{
+	ActiveModelTrait, ActiveValue::{ NotSet, Set }, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter,
+	QueryOrder, QuerySelect, TransactionTrait
+};
+
+use entity::password_history;
+
+#[async_trait]
+pub trait PasswordHistoryRepository: Send + Sync {
+	// Hashes of the user's most recently replaced passwords, newest first.
+	async fn find_recent(&self, user_id: i32, limit: u64) -> Result<Vec<String>, DbErr>;
+	// Records a replaced password and forgets all but the newest `keep`.
+	async fn push(&self, user_id: i32, password_hash: &str, keep: u64) -> Result<(), DbErr>;
+}
+
+pub struct SeaOrmPasswordHistoryRepository {
+	db: DatabaseConnection
+}
+
+impl SeaOrmPasswordHistoryRepository {
+	pub fn new(db: DatabaseConnection) -> Self {
+		Self { db }
+	}
+}
+
+#[async_trait]
+impl PasswordHistoryRepository for SeaOrmPasswordHistoryRepository {
+	async fn find_recent(&self, user_id: i32, limit: u64) -> Result<Vec<String>, DbErr> {
+		let models = password_history::Entity::find()
+		.filter(password_history::Column::UserId.eq(user_id))
+		.order_by_desc(password_history::Column::Id)
+		.limit(limit)
+		.all(&self.db).await?;
+
+		Ok(models.into_iter().map(|d| d.password_hash).collect())
+	}
+
+	async fn push(&self, user_id: i32, password_hash: &str, keep: u64) -> Result<(), DbErr> {
+		let txn = self.db.begin().await?;
+
+		password_history::ActiveModel {
+			id: NotSet,
+			user_id: Set(user_id),
+			password_hash: Set(password_hash.to_owned()),
+			created_at: NotSet
+		}.insert(&txn).await?;
+
+		// A user only ever has a handful of rows; OFFSET without LIMIT isn't
+		// portable across backends.
+		let ids: Vec<i32> = password_history::Entity::find()
+		.select_only()
+		.column(password_history::Column::Id)
+		.filter(password_history::Column::UserId.eq(user_id))
+		.order_by_desc(password_history::Column::Id)
+		.into_tuple()
+		.all(&txn).await?;
+
+		let expired: Vec<i32> = ids.into_iter().skip(keep as usize).collect();
+
+		if !expired.is_empty() {
+			password_history::Entity::delete_many()
+			.filter(password_history::Column::Id.is_in(expired))
+			.exec(&txn).await?;
+		}
+
+		txn.commit().await
+	}
+}
@@ -0,0 +1,68 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sea_orm::{
+	ActiveModelTrait, ActiveValue::{ NotSet, Set }, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter
+};
+
+use entity::password_reset_token;
+
+#[async_trait]
+pub trait PasswordResetRepository: Send + Sync {
+	async fn insert(&self, user_id: i32, token_hash: &str, expires_at: NaiveDateTime) -> Result<password_reset_token::Model, DbErr>;
+	// An unused token with this hash that hasn't expired at `now`.
+	async fn find_valid(&self, token_hash: &str, now: NaiveDateTime) -> Result<Option<password_reset_token::Model>, DbErr>;
+	// Marks the token used; false when another request got there first.
+	async fn claim(&self, id: i32, now: NaiveDateTime) -> Result<bool, DbErr>;
+	// Drops every outstanding token of the user.
+	async fn delete_for_user(&self, user_id: i32) -> Result<u64, DbErr>;
+}
+
+pub struct SeaOrmPasswordResetRepository {
+	db: DatabaseConnection
+}
+
+impl SeaOrmPasswordResetRepository {
+	pub fn new(db: DatabaseConnection) -> Self {
+		Self { db }
+	}
+}
+
+#[async_trait]
+impl PasswordResetRepository for SeaOrmPasswordResetRepository {
+	async fn insert(&self, user_id: i32, token_hash: &str, expires_at: NaiveDateTime) -> Result<password_reset_token::Model, DbErr> {
+		password_reset_token::ActiveModel {
+			id: NotSet,
+			user_id: Set(user_id),
+			token_hash: Set(token_hash.to_owned()),
+			expires_at: Set(expires_at),
+			used_at: Set(None),
+			created_at: NotSet
+		}.insert(&self.db).await
+	}
+
+	async fn find_valid(&self, token_hash: &str, now: NaiveDateTime) -> Result<Option<password_reset_token::Model>, DbErr> {
+		password_reset_token::Entity::find()
+		.filter(password_reset_token::Column::TokenHash.eq(token_hash))
+		.filter(password_reset_token::Column::UsedAt.is_null())
+		.filter(password_reset_token::Column::ExpiresAt.gt(now))
+		.one(&self.db).await
+	}
+
+	async fn claim(&self, id: i32, now: NaiveDateTime) -> Result<bool, DbErr> {
+		let result = password_reset_token::Entity::update_many()
+		.col_expr(password_reset_token::Column::UsedAt, now.into())
+		.filter(password_reset_token::Column::Id.eq(id))
+		.filter(password_reset_token::Column::UsedAt.is_null())
+		.exec(&self.db).await?;
+
+		Ok(result.rows_affected > 0)
+	}
+
+	async fn delete_for_user(&self, user_id: i32) -> Result<u64, DbErr> {
+		let result = password_reset_token::Entity::delete_many()
+		.filter(password_reset_token::Column::UserId.eq(user_id))
+		.exec(&self.db).await?;
+
+		Ok(result.rows_affected)
+	}
+}
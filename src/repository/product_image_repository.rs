@@ -0,0 +1,185 @@
+use async_trait::async_trait;
+use sea_orm::{
+	sea_query::Expr, ActiveModelTrait, ActiveValue::Set, ColumnTrait, Condition, ConnectionTrait,
+	DatabaseConnection, DbErr, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, TransactionTrait
+};
+
+use entity::{ product, product_image };
+
+// Gallery writes run in a transaction and keep `product.image` in sync with the
+// primary image, which older clients still read.
+#[async_trait]
+pub trait ProductImageRepository: Send + Sync {
+	async fn find_by_product(&self, product_id: i32) -> Result<Vec<product_image::Model>, DbErr>;
+	async fn find_for_products(&self, product_ids: Vec<i32>) -> Result<Vec<product_image::Model>, DbErr>;
+	async fn find(&self, product_id: i32, image_id: i32) -> Result<Option<product_image::Model>, DbErr>;
+	// Appends to the gallery; the first image of a product always becomes primary.
+	async fn attach(
+		&self,
+		product_id: i32,
+		file_name: String,
+		alt_text: String,
+		make_primary: bool
+	) -> Result<product_image::Model, DbErr>;
+	// `image_ids` is the full gallery in its new order.
+	async fn reorder(&self, product_id: i32, image_ids: Vec<i32>) -> Result<(), DbErr>;
+	async fn set_primary(&self, image: &product_image::Model) -> Result<(), DbErr>;
+	// Promotes the next image when the removed one was primary.
+	async fn detach(&self, image: &product_image::Model) -> Result<(), DbErr>;
+	// Gallery images and products (`product.image`) using the file.
+	async fn count_references(&self, file_name: &str) -> Result<u64, DbErr>;
+}
+
+pub struct SeaOrmProductImageRepository {
+	db: DatabaseConnection
+}
+
+impl SeaOrmProductImageRepository {
+	pub fn new(db: DatabaseConnection) -> Self {
+		Self { db }
+	}
+}
+
+async fn find_gallery<C: ConnectionTrait>(db: &C, product_id: i32) -> Result<Vec<product_image::Model>, DbErr> {
+	product_image::Entity::find()
+	.filter(product_image::Column::ProductId.eq(product_id))
+	.order_by_asc(product_image::Column::Position)
+	.order_by_asc(product_image::Column::Id)
+	.all(db).await
+}
+
+async fn mark_primary<C: ConnectionTrait>(db: &C, image: &product_image::Model) -> Result<(), DbErr> {
+	let now = chrono::Utc::now().naive_utc();
+
+	product_image::Entity::update_many()
+	.col_expr(product_image::Column::IsPrimary, Expr::value(false))
+	.filter(product_image::Column::ProductId.eq(image.product_id))
+	.exec(db).await?;
+
+	product_image::Entity::update_many()
+	.col_expr(product_image::Column::IsPrimary, Expr::value(true))
+	.col_expr(product_image::Column::UpdatedAt, Expr::value(now))
+	.filter(product_image::Column::Id.eq(image.id))
+	.exec(db).await?;
+
+	product::Entity::update_many()
+	.col_expr(product::Column::Image, Expr::value(image.file_name.clone()))
+	.col_expr(product::Column::UpdatedAt, Expr::value(now))
+	.filter(product::Column::Id.eq(image.product_id))
+	.exec(db).await?;
+
+	Ok(())
+}
+
+#[async_trait]
+impl ProductImageRepository for SeaOrmProductImageRepository {
+	async fn find_by_product(&self, product_id: i32) -> Result<Vec<product_image::Model>, DbErr> {
+		find_gallery(&self.db, product_id).await
+	}
+
+	async fn find_for_products(&self, product_ids: Vec<i32>) -> Result<Vec<product_image::Model>, DbErr> {
+		product_image::Entity::find()
+		.filter(product_image::Column::ProductId.is_in(product_ids))
+		.all(&self.db).await
+	}
+
+	async fn find(&self, product_id: i32, image_id: i32) -> Result<Option<product_image::Model>, DbErr> {
+		product_image::Entity::find().filter(
+			Condition::all()
+			.add(product_image::Column::Id.eq(image_id))
+			.add(product_image::Column::ProductId.eq(product_id))
+		).one(&self.db).await
+	}
+
+	async fn attach(
+		&self,
+		product_id: i32,
+		file_name: String,
+		alt_text: String,
+		make_primary: bool
+	) -> Result<product_image::Model, DbErr> {
+		let txn = self.db.begin().await?;
+
+		let gallery = find_gallery(&txn, product_id).await?;
+
+		let position = gallery.iter().map(|d| d.position + 1).max().unwrap_or(0);
+		let is_primary = make_primary || !gallery.iter().any(|d| d.is_primary);
+
+		let mut image = product_image::ActiveModel {
+			product_id: Set(product_id),
+			file_name: Set(file_name),
+			alt_text: Set(alt_text),
+			position: Set(position),
+			is_primary: Set(false),
+			..Default::default()
+		}.insert(&txn).await?;
+
+		if is_primary {
+			mark_primary(&txn, &image).await?;
+			image.is_primary = true;
+		}
+
+		txn.commit().await?;
+
+		Ok(image)
+	}
+
+	async fn reorder(&self, product_id: i32, image_ids: Vec<i32>) -> Result<(), DbErr> {
+		let txn = self.db.begin().await?;
+		let now = chrono::Utc::now().naive_utc();
+
+		for (position, image_id) in image_ids.iter().enumerate() {
+			product_image::Entity::update_many()
+			.col_expr(product_image::Column::Position, Expr::value(position as i32))
+			.col_expr(product_image::Column::UpdatedAt, Expr::value(now))
+			.filter(
+				Condition::all()
+				.add(product_image::Column::Id.eq(*image_id))
+				.add(product_image::Column::ProductId.eq(product_id))
+			)
+			.exec(&txn).await?;
+		}
+
+		txn.commit().await
+	}
+
+	async fn set_primary(&self, image: &product_image::Model) -> Result<(), DbErr> {
+		let txn = self.db.begin().await?;
+
+		mark_primary(&txn, image).await?;
+
+		txn.commit().await
+	}
+
+	async fn detach(&self, image: &product_image::Model) -> Result<(), DbErr> {
+		let txn = self.db.begin().await?;
+
+		product_image::Entity::delete_by_id(image.id).exec(&txn).await?;
+
+		if image.is_primary {
+			match find_gallery(&txn, image.product_id).await?.first() {
+				Some(next) => mark_primary(&txn, next).await?,
+				None => {
+					product::Entity::update_many()
+					.col_expr(product::Column::Image, Expr::value(""))
+					.filter(product::Column::Id.eq(image.product_id))
+					.exec(&txn).await?;
+				}
+			}
+		}
+
+		txn.commit().await
+	}
+
+	async fn count_references(&self, file_name: &str) -> Result<u64, DbErr> {
+		let images = product_image::Entity::find()
+		.filter(product_image::Column::FileName.eq(file_name))
+		.count(&self.db).await?;
+
+		let products = product::Entity::find()
+		.filter(product::Column::Image.eq(file_name))
+		.count(&self.db).await?;
+
+		Ok(images + products)
+	}
+}
@@ -0,0 +1,97 @@
+use async_trait::async_trait;
+use sea_orm::{
+	ActiveModelTrait, ActiveValue::{ NotSet, Set }, ColumnTrait, Condition, DatabaseConnection, DbErr,
+	EntityTrait, PaginatorTrait, QueryFilter, QueryOrder
+};
+
+use crate::repository::name_contains;
+
+use entity::{ category, product };
+
+#[async_trait]
+pub trait ProductRepository: Send + Sync {
+	async fn search_with_category(&self, term: &str) -> Result<Vec<(product::Model, Option<category::Model>)>, DbErr>;
+	async fn count_search(&self, term: &str) -> Result<u64, DbErr>;
+	// Products whose stock is below `threshold`.
+	async fn count_low_stock(&self, threshold: i32) -> Result<u64, DbErr>;
+	async fn find_by_id(&self, id: i32) -> Result<Option<product::Model>, DbErr>;
+	// `id` and the timestamps of `model` are ignored.
+	async fn insert(&self, model: product::Model) -> Result<product::Model, DbErr>;
+	async fn update(&self, model: product::Model) -> Result<product::Model, DbErr>;
+	async fn delete_by_id(&self, id: i32) -> Result<u64, DbErr>;
+}
+
+pub struct SeaOrmProductRepository {
+	db: DatabaseConnection
+}
+
+impl SeaOrmProductRepository {
+	pub fn new(db: DatabaseConnection) -> Self {
+		Self { db }
+	}
+}
+
+fn search_condition(term: &str) -> Condition {
+	Condition::any().add(
+		name_contains(product::Column::Name, term)
+	)
+}
+
+#[async_trait]
+impl ProductRepository for SeaOrmProductRepository {
+	async fn search_with_category(&self, term: &str) -> Result<Vec<(product::Model, Option<category::Model>)>, DbErr> {
+		product::Entity::find()
+		.order_by_asc(product::Column::Name)
+		.find_also_related(category::Entity)
+		.filter(search_condition(term))
+		.all(&self.db).await
+	}
+
+	async fn count_search(&self, term: &str) -> Result<u64, DbErr> {
+		product::Entity::find().filter(search_condition(term)).count(&self.db).await
+	}
+
+	async fn count_low_stock(&self, threshold: i32) -> Result<u64, DbErr> {
+		product::Entity::find().filter(product::Column::Stock.lt(threshold)).count(&self.db).await
+	}
+
+	async fn find_by_id(&self, id: i32) -> Result<Option<product::Model>, DbErr> {
+		product::Entity::find_by_id(id).one(&self.db).await
+	}
+
+	async fn insert(&self, model: product::Model) -> Result<product::Model, DbErr> {
+		product::ActiveModel {
+			id: NotSet,
+			name: Set(model.name),
+			description: Set(model.description),
+			purchase_price: Set(model.purchase_price),
+			selling_price: Set(model.selling_price),
+			stock: Set(model.stock),
+			discount: Set(model.discount),
+			image: Set(model.image),
+			category_id: Set(model.category_id),
+			created_at: NotSet,
+			updated_at: NotSet
+		}.insert(&self.db).await
+	}
+
+	async fn update(&self, model: product::Model) -> Result<product::Model, DbErr> {
+		product::ActiveModel {
+			id: Set(model.id),
+			name: Set(model.name),
+			description: Set(model.description),
+			purchase_price: Set(model.purchase_price),
+			selling_price: Set(model.selling_price),
+			stock: Set(model.stock),
+			discount: Set(model.discount),
+			image: Set(model.image),
+			category_id: Set(model.category_id),
+			created_at: NotSet,
+			updated_at: Set(model.updated_at)
+		}.update(&self.db).await
+	}
+
+	async fn delete_by_id(&self, id: i32) -> Result<u64, DbErr> {
+		Ok(product::Entity::delete_by_id(id).exec(&self.db).await?.rows_affected)
+	}
+}
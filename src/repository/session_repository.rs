@@ -0,0 +1,127 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sea_orm::{
+	ActiveModelTrait, ActiveValue::{ NotSet, Set }, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter,
+	QueryOrder
+};
+
+use entity::user_session;
+
+#[async_trait]
+pub trait SessionRepository: Send + Sync {
+	async fn insert(
+		&self,
+		user_id: i32,
+		sid: &str,
+		user_agent: &str,
+		ip: Option<String>,
+		expires_at: NaiveDateTime
+	) -> Result<user_session::Model, DbErr>;
+	// A session with this id that hasn't expired at `now`.
+	async fn find_valid(&self, sid: &str, now: NaiveDateTime) -> Result<Option<user_session::Model>, DbErr>;
+	// The user's unexpired sessions, most recently used first.
+	async fn find_active(&self, user_id: i32, now: NaiveDateTime) -> Result<Vec<user_session::Model>, DbErr>;
+	async fn touch(&self, id: i32, now: NaiveDateTime) -> Result<(), DbErr>;
+	async fn extend(&self, id: i32, expires_at: NaiveDateTime) -> Result<(), DbErr>;
+	// False when the user has no session with this id.
+	async fn delete(&self, user_id: i32, id: i32) -> Result<bool, DbErr>;
+	// Every session of the user but `except_sid`, if given.
+	async fn delete_for_user(&self, user_id: i32, except_sid: Option<&str>) -> Result<u64, DbErr>;
+	async fn delete_expired(&self, user_id: i32, now: NaiveDateTime) -> Result<u64, DbErr>;
+}
+
+pub struct SeaOrmSessionRepository {
+	db: DatabaseConnection
+}
+
+impl SeaOrmSessionRepository {
+	pub fn new(db: DatabaseConnection) -> Self {
+		Self { db }
+	}
+}
+
+#[async_trait]
+impl SessionRepository for SeaOrmSessionRepository {
+	async fn insert(
+		&self,
+		user_id: i32,
+		sid: &str,
+		user_agent: &str,
+		ip: Option<String>,
+		expires_at: NaiveDateTime
+	) -> Result<user_session::Model, DbErr> {
+		user_session::ActiveModel {
+			id: NotSet,
+			user_id: Set(user_id),
+			sid: Set(sid.to_owned()),
+			user_agent: Set(user_agent.to_owned()),
+			ip: Set(ip),
+			last_seen_at: Set(chrono::Utc::now().naive_utc()),
+			expires_at: Set(expires_at),
+			created_at: NotSet
+		}.insert(&self.db).await
+	}
+
+	async fn find_valid(&self, sid: &str, now: NaiveDateTime) -> Result<Option<user_session::Model>, DbErr> {
+		user_session::Entity::find()
+		.filter(user_session::Column::Sid.eq(sid))
+		.filter(user_session::Column::ExpiresAt.gt(now))
+		.one(&self.db).await
+	}
+
+	async fn find_active(&self, user_id: i32, now: NaiveDateTime) -> Result<Vec<user_session::Model>, DbErr> {
+		user_session::Entity::find()
+		.filter(user_session::Column::UserId.eq(user_id))
+		.filter(user_session::Column::ExpiresAt.gt(now))
+		.order_by_desc(user_session::Column::LastSeenAt)
+		.order_by_desc(user_session::Column::Id)
+		.all(&self.db).await
+	}
+
+	async fn touch(&self, id: i32, now: NaiveDateTime) -> Result<(), DbErr> {
+		user_session::Entity::update_many()
+		.col_expr(user_session::Column::LastSeenAt, now.into())
+		.filter(user_session::Column::Id.eq(id))
+		.exec(&self.db).await?;
+
+		Ok(())
+	}
+
+	async fn extend(&self, id: i32, expires_at: NaiveDateTime) -> Result<(), DbErr> {
+		user_session::Entity::update_many()
+		.col_expr(user_session::Column::ExpiresAt, expires_at.into())
+		.filter(user_session::Column::Id.eq(id))
+		.exec(&self.db).await?;
+
+		Ok(())
+	}
+
+	async fn delete(&self, user_id: i32, id: i32) -> Result<bool, DbErr> {
+		let result = user_session::Entity::delete_many()
+		.filter(user_session::Column::Id.eq(id))
+		.filter(user_session::Column::UserId.eq(user_id))
+		.exec(&self.db).await?;
+
+		Ok(result.rows_affected > 0)
+	}
+
+	async fn delete_for_user(&self, user_id: i32, except_sid: Option<&str>) -> Result<u64, DbErr> {
+		let mut query = user_session::Entity::delete_many()
+		.filter(user_session::Column::UserId.eq(user_id));
+
+		if let Some(sid) = except_sid {
+			query = query.filter(user_session::Column::Sid.ne(sid));
+		}
+
+		Ok(query.exec(&self.db).await?.rows_affected)
+	}
+
+	async fn delete_expired(&self, user_id: i32, now: NaiveDateTime) -> Result<u64, DbErr> {
+		let result = user_session::Entity::delete_many()
+		.filter(user_session::Column::UserId.eq(user_id))
+		.filter(user_session::Column::ExpiresAt.lte(now))
+		.exec(&self.db).await?;
+
+		Ok(result.rows_affected)
+	}
+}
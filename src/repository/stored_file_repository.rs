@@ -0,0 +1,79 @@
+use async_trait::async_trait;
+use sea_orm::{
+	sea_query::Expr, ActiveModelTrait, ActiveValue::{ NotSet, Set }, ColumnTrait, Condition, DatabaseConnection,
+	DbErr, EntityTrait, QueryFilter
+};
+
+use entity::stored_file;
+
+#[async_trait]
+pub trait StoredFileRepository: Send + Sync {
+	async fn find_by_hash(&self, namespace: &str, hash: &str) -> Result<Option<stored_file::Model>, DbErr>;
+	async fn find_by_name(&self, namespace: &str, file_name: &str) -> Result<Option<stored_file::Model>, DbErr>;
+	// `id` and `created_at` of `model` are ignored.
+	async fn insert(&self, model: stored_file::Model) -> Result<stored_file::Model, DbErr>;
+	async fn set_private(&self, id: i32, is_private: bool) -> Result<(), DbErr>;
+	async fn delete_by_name(&self, namespace: &str, file_name: &str) -> Result<u64, DbErr>;
+}
+
+pub struct SeaOrmStoredFileRepository {
+	db: DatabaseConnection
+}
+
+impl SeaOrmStoredFileRepository {
+	pub fn new(db: DatabaseConnection) -> Self {
+		Self { db }
+	}
+}
+
+#[async_trait]
+impl StoredFileRepository for SeaOrmStoredFileRepository {
+	async fn find_by_hash(&self, namespace: &str, hash: &str) -> Result<Option<stored_file::Model>, DbErr> {
+		stored_file::Entity::find().filter(
+			Condition::all()
+			.add(stored_file::Column::Namespace.eq(namespace))
+			.add(stored_file::Column::Hash.eq(hash))
+		).one(&self.db).await
+	}
+
+	async fn find_by_name(&self, namespace: &str, file_name: &str) -> Result<Option<stored_file::Model>, DbErr> {
+		stored_file::Entity::find().filter(
+			Condition::all()
+			.add(stored_file::Column::Namespace.eq(namespace))
+			.add(stored_file::Column::FileName.eq(file_name))
+		).one(&self.db).await
+	}
+
+	async fn insert(&self, model: stored_file::Model) -> Result<stored_file::Model, DbErr> {
+		stored_file::ActiveModel {
+			id: NotSet,
+			namespace: Set(model.namespace),
+			hash: Set(model.hash),
+			file_name: Set(model.file_name),
+			original_name: Set(model.original_name),
+			content_type: Set(model.content_type),
+			size: Set(model.size),
+			is_private: Set(model.is_private),
+			created_at: NotSet
+		}.insert(&self.db).await
+	}
+
+	async fn set_private(&self, id: i32, is_private: bool) -> Result<(), DbErr> {
+		stored_file::Entity::update_many()
+		.col_expr(stored_file::Column::IsPrivate, Expr::value(is_private))
+		.filter(stored_file::Column::Id.eq(id))
+		.exec(&self.db).await?;
+
+		Ok(())
+	}
+
+	async fn delete_by_name(&self, namespace: &str, file_name: &str) -> Result<u64, DbErr> {
+		let result = stored_file::Entity::delete_many().filter(
+			Condition::all()
+			.add(stored_file::Column::Namespace.eq(namespace))
+			.add(stored_file::Column::FileName.eq(file_name))
+		).exec(&self.db).await?;
+
+		Ok(result.rows_affected)
+	}
+}
@@ -0,0 +1,160 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sea_orm::{
+	sea_query::OnConflict, ActiveModelTrait, ActiveValue::{ NotSet, Set }, ColumnTrait, Condition, DatabaseConnection,
+	DbErr, EntityTrait, PaginatorTrait, QueryFilter, TransactionTrait
+};
+
+use entity::{ used_challenge, user_recovery_code, user_totp };
+
+#[async_trait]
+pub trait TwoFactorRepository: Send + Sync {
+	async fn find_totp(&self, user_id: i32) -> Result<Option<user_totp::Model>, DbErr>;
+	// Replaces any existing secret of the user with a new, unconfirmed one.
+	async fn replace_totp(&self, user_id: i32, secret: &str) -> Result<user_totp::Model, DbErr>;
+	async fn update_totp(&self, model: user_totp::Model) -> Result<user_totp::Model, DbErr>;
+	// Records `step` as used unless it (or a later one) already was, so two
+	// requests racing with the same code can't both succeed.
+	async fn claim_step(&self, totp_id: i32, step: i64) -> Result<bool, DbErr>;
+	// Removes the secret and every recovery code.
+	async fn delete_all(&self, user_id: i32) -> Result<(), DbErr>;
+	async fn replace_recovery_codes(&self, user_id: i32, code_hashes: Vec<String>) -> Result<(), DbErr>;
+	// Marks an unused code as used; false when there is none with this hash.
+	async fn use_recovery_code(&self, user_id: i32, code_hash: &str, used_at: NaiveDateTime) -> Result<bool, DbErr>;
+	async fn count_unused_recovery_codes(&self, user_id: i32) -> Result<u64, DbErr>;
+	// Records a challenge `jti` as redeemed; false when it already was. Rows
+	// past their expiry are dropped along the way.
+	async fn use_challenge(&self, jti: &str, expires_at: NaiveDateTime, now: NaiveDateTime) -> Result<bool, DbErr>;
+	async fn is_challenge_used(&self, jti: &str) -> Result<bool, DbErr>;
+}
+
+pub struct SeaOrmTwoFactorRepository {
+	db: DatabaseConnection
+}
+
+impl SeaOrmTwoFactorRepository {
+	pub fn new(db: DatabaseConnection) -> Self {
+		Self { db }
+	}
+}
+
+#[async_trait]
+impl TwoFactorRepository for SeaOrmTwoFactorRepository {
+	async fn find_totp(&self, user_id: i32) -> Result<Option<user_totp::Model>, DbErr> {
+		user_totp::Entity::find().filter(user_totp::Column::UserId.eq(user_id)).one(&self.db).await
+	}
+
+	async fn replace_totp(&self, user_id: i32, secret: &str) -> Result<user_totp::Model, DbErr> {
+		let txn = self.db.begin().await?;
+
+		user_totp::Entity::delete_many().filter(user_totp::Column::UserId.eq(user_id)).exec(&txn).await?;
+
+		let model = user_totp::ActiveModel {
+			id: NotSet,
+			user_id: Set(user_id),
+			secret: Set(secret.to_owned()),
+			confirmed_at: Set(None),
+			last_used_step: Set(None),
+			created_at: NotSet
+		}.insert(&txn).await?;
+
+		txn.commit().await?;
+
+		Ok(model)
+	}
+
+	async fn update_totp(&self, model: user_totp::Model) -> Result<user_totp::Model, DbErr> {
+		user_totp::ActiveModel {
+			id: Set(model.id),
+			user_id: Set(model.user_id),
+			secret: Set(model.secret),
+			confirmed_at: Set(model.confirmed_at),
+			last_used_step: Set(model.last_used_step),
+			created_at: NotSet
+		}.update(&self.db).await
+	}
+
+	async fn claim_step(&self, totp_id: i32, step: i64) -> Result<bool, DbErr> {
+		let result = user_totp::Entity::update_many()
+		.col_expr(user_totp::Column::LastUsedStep, step.into())
+		.filter(user_totp::Column::Id.eq(totp_id))
+		.filter(
+			Condition::any()
+			.add(user_totp::Column::LastUsedStep.is_null())
+			.add(user_totp::Column::LastUsedStep.lt(step))
+		)
+		.exec(&self.db).await?;
+
+		Ok(result.rows_affected > 0)
+	}
+
+	async fn delete_all(&self, user_id: i32) -> Result<(), DbErr> {
+		let txn = self.db.begin().await?;
+
+		user_recovery_code::Entity::delete_many().filter(user_recovery_code::Column::UserId.eq(user_id)).exec(&txn).await?;
+		user_totp::Entity::delete_many().filter(user_totp::Column::UserId.eq(user_id)).exec(&txn).await?;
+
+		txn.commit().await
+	}
+
+	async fn replace_recovery_codes(&self, user_id: i32, code_hashes: Vec<String>) -> Result<(), DbErr> {
+		let txn = self.db.begin().await?;
+
+		user_recovery_code::Entity::delete_many().filter(user_recovery_code::Column::UserId.eq(user_id)).exec(&txn).await?;
+
+		let models: Vec<user_recovery_code::ActiveModel> = code_hashes.into_iter().map(|code_hash| user_recovery_code::ActiveModel {
+			id: NotSet,
+			user_id: Set(user_id),
+			code_hash: Set(code_hash),
+			used_at: Set(None),
+			created_at: NotSet
+		}).collect();
+
+		if !models.is_empty() {
+			user_recovery_code::Entity::insert_many(models).exec(&txn).await?;
+		}
+
+		txn.commit().await
+	}
+
+	async fn use_recovery_code(&self, user_id: i32, code_hash: &str, used_at: NaiveDateTime) -> Result<bool, DbErr> {
+		let result = user_recovery_code::Entity::update_many()
+		.col_expr(user_recovery_code::Column::UsedAt, used_at.into())
+		.filter(user_recovery_code::Column::UserId.eq(user_id))
+		.filter(user_recovery_code::Column::CodeHash.eq(code_hash))
+		.filter(user_recovery_code::Column::UsedAt.is_null())
+		.exec(&self.db).await?;
+
+		Ok(result.rows_affected > 0)
+	}
+
+	async fn count_unused_recovery_codes(&self, user_id: i32) -> Result<u64, DbErr> {
+		user_recovery_code::Entity::find()
+		.filter(user_recovery_code::Column::UserId.eq(user_id))
+		.filter(user_recovery_code::Column::UsedAt.is_null())
+		.count(&self.db).await
+	}
+
+	async fn use_challenge(&self, jti: &str, expires_at: NaiveDateTime, now: NaiveDateTime) -> Result<bool, DbErr> {
+		used_challenge::Entity::delete_many().filter(used_challenge::Column::ExpiresAt.lt(now)).exec(&self.db).await?;
+
+		let inserted = used_challenge::Entity::insert(used_challenge::ActiveModel {
+			id: NotSet,
+			jti: Set(jti.to_owned()),
+			expires_at: Set(expires_at)
+		})
+		.on_conflict(
+			OnConflict::column(used_challenge::Column::Jti)
+			// MySQL has no DO NOTHING; it gets `ON DUPLICATE KEY UPDATE id = id`.
+			.do_nothing_on([used_challenge::Column::Id])
+			.to_owned()
+		)
+		.exec_without_returning(&self.db).await?;
+
+		Ok(inserted > 0)
+	}
+
+	async fn is_challenge_used(&self, jti: &str) -> Result<bool, DbErr> {
+		Ok(used_challenge::Entity::find().filter(used_challenge::Column::Jti.eq(jti)).count(&self.db).await? > 0)
+	}
+}
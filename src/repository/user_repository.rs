@@ -0,0 +1,97 @@
+use async_trait::async_trait;
+use sea_orm::{
+	ActiveModelTrait, ActiveValue::{ NotSet, Set }, ColumnTrait, DatabaseConnection, DbErr, EntityTrait,
+	PaginatorTrait, QueryFilter, QueryOrder, sea_query::Expr
+};
+
+use entity::user;
+
+#[async_trait]
+pub trait UserRepository: Send + Sync {
+	async fn find_all(&self) -> Result<Vec<user::Model>, DbErr>;
+	async fn find_by_id(&self, id: i32) -> Result<Option<user::Model>, DbErr>;
+	async fn find_by_username(&self, username: &str) -> Result<Option<user::Model>, DbErr>;
+	// `id` and the timestamps of `model` are ignored.
+	async fn insert(&self, model: user::Model) -> Result<user::Model, DbErr>;
+	// Leaves `token_version` alone; see `revoke_sessions`.
+	async fn update(&self, model: user::Model) -> Result<user::Model, DbErr>;
+	// Bumps `token_version`, which invalidates every token issued so far.
+	async fn revoke_sessions(&self, id: i32) -> Result<(), DbErr>;
+	async fn delete_by_id(&self, id: i32) -> Result<u64, DbErr>;
+	// Users whose `photo` is the file.
+	async fn count_photo_references(&self, file_name: &str) -> Result<u64, DbErr>;
+}
+
+pub struct SeaOrmUserRepository {
+	db: DatabaseConnection
+}
+
+impl SeaOrmUserRepository {
+	pub fn new(db: DatabaseConnection) -> Self {
+		Self { db }
+	}
+}
+
+#[async_trait]
+impl UserRepository for SeaOrmUserRepository {
+	async fn find_all(&self) -> Result<Vec<user::Model>, DbErr> {
+		user::Entity::find().order_by_asc(user::Column::FullName).all(&self.db).await
+	}
+
+	async fn find_by_id(&self, id: i32) -> Result<Option<user::Model>, DbErr> {
+		user::Entity::find_by_id(id).one(&self.db).await
+	}
+
+	async fn find_by_username(&self, username: &str) -> Result<Option<user::Model>, DbErr> {
+		user::Entity::find().filter(user::Column::Username.eq(username)).one(&self.db).await
+	}
+
+	async fn insert(&self, model: user::Model) -> Result<user::Model, DbErr> {
+		user::ActiveModel {
+			id: NotSet,
+			username: Set(model.username),
+			password: Set(model.password),
+			full_name: Set(model.full_name),
+			address: Set(model.address),
+			phone_number: Set(model.phone_number),
+			role: Set(model.role),
+			photo: Set(model.photo),
+			created_at: NotSet,
+			updated_at: NotSet,
+			token_version: NotSet
+		}.insert(&self.db).await
+	}
+
+	async fn update(&self, model: user::Model) -> Result<user::Model, DbErr> {
+		user::ActiveModel {
+			id: Set(model.id),
+			username: Set(model.username),
+			password: Set(model.password),
+			full_name: Set(model.full_name),
+			address: Set(model.address),
+			phone_number: Set(model.phone_number),
+			role: Set(model.role),
+			photo: Set(model.photo),
+			created_at: NotSet,
+			updated_at: Set(model.updated_at),
+			token_version: NotSet
+		}.update(&self.db).await
+	}
+
+	async fn revoke_sessions(&self, id: i32) -> Result<(), DbErr> {
+		user::Entity::update_many()
+		.col_expr(user::Column::TokenVersion, Expr::col(user::Column::TokenVersion).add(1))
+		.filter(user::Column::Id.eq(id))
+		.exec(&self.db).await?;
+
+		Ok(())
+	}
+
+	async fn delete_by_id(&self, id: i32) -> Result<u64, DbErr> {
+		Ok(user::Entity::delete_by_id(id).exec(&self.db).await?.rows_affected)
+	}
+
+	async fn count_photo_references(&self, file_name: &str) -> Result<u64, DbErr> {
+		user::Entity::find().filter(user::Column::Photo.eq(file_name)).count(&self.db).await
+	}
+}
@@ -0,0 +1,120 @@
+use rand::RngCore;
+use sha2::{ Digest, Sha256 };
+use std::sync::Arc;
+
+use crate::model::api_key_model::{ ApiKeyData, ApiKeyPrincipal, CreateApiKeyBody, CreatedApiKeyResponse };
+use crate::repository::api_key_repository::ApiKeyRepository;
+use crate::service::ServiceError;
+use crate::utils::api_scope::SCOPES;
+use crate::utils::touch;
+
+// Keys look like `ak_` followed by 40 hex characters.
+const KEY_PREFIX: &str = "ak_";
+// How much of the key is kept in the clear to tell keys apart.
+const DISPLAY_PREFIX_LEN: usize = 11;
+
+#[derive(Clone)]
+pub struct ApiKeyService {
+	repository: Arc<dyn ApiKeyRepository>
+}
+
+fn hash_key(key: &str) -> String {
+	hex::encode(Sha256::digest(key.trim().as_bytes()))
+}
+
+fn invalid_key() -> ServiceError {
+	ServiceError::Unauthorized("INVALID API KEY.".to_owned())
+}
+
+impl ApiKeyService {
+	pub fn new(repository: Arc<dyn ApiKeyRepository>) -> Self {
+		Self { repository }
+	}
+
+	// Creates a key on behalf of `user_id`. The key is in the response only.
+	pub async fn create(&self, user_id: i32, body: CreateApiKeyBody) -> Result<CreatedApiKeyResponse, ServiceError> {
+		let name = body.name.trim();
+
+		if name.is_empty() || name.chars().count() > 100 {
+			return Err(ServiceError::BadRequest("The name must be 1 to 100 characters long.".to_owned()));
+		}
+
+		let mut scopes: Vec<String> = Vec::new();
+
+		for scope in body.scopes.iter().map(|d| d.trim()) {
+			if !SCOPES.contains(&scope) {
+				return Err(ServiceError::BadRequest(format!("Unknown scope {scope}. Known scopes: {}.", SCOPES.join(", "))));
+			}
+
+			if !scopes.iter().any(|d| d == scope) {
+				scopes.push(scope.to_owned());
+			}
+		}
+
+		if scopes.is_empty() {
+			return Err(ServiceError::BadRequest("An API key needs at least one scope.".to_owned()));
+		}
+
+		if body.expires_at.is_some_and(|d| d <= chrono::Utc::now().naive_utc()) {
+			return Err(ServiceError::BadRequest("expires_at must be in the future.".to_owned()));
+		}
+
+		let mut bytes = [0u8; 20];
+		rand::thread_rng().fill_bytes(&mut bytes);
+		let key = format!("{KEY_PREFIX}{}", hex::encode(bytes));
+
+		let model = self.repository.insert(
+			user_id,
+			name,
+			&key[..DISPLAY_PREFIX_LEN],
+			&hash_key(&key),
+			&scopes.join(","),
+			body.expires_at
+		).await?;
+
+		tracing::info!(user_id, api_key_id = model.id, "api key created");
+
+		Ok(CreatedApiKeyResponse { success: true, key, data: model.into() })
+	}
+
+	pub async fn find_many(&self) -> Result<Vec<ApiKeyData>, ServiceError> {
+		Ok(self.repository.find_many().await?.into_iter().map(ApiKeyData::from).collect())
+	}
+
+	pub async fn revoke(&self, id: i32) -> Result<(), ServiceError> {
+		if !self.repository.delete(id).await? {
+			return Err(ServiceError::NotFound("API key not found.".to_owned()));
+		}
+
+		tracing::info!(api_key_id = id, "api key revoked");
+
+		Ok(())
+	}
+
+	// The principal of an `X-Api-Key` header, if it names an unexpired key.
+	pub async fn authenticate(&self, key: &str) -> Result<ApiKeyPrincipal, ServiceError> {
+		if !key.starts_with(KEY_PREFIX) {
+			return Err(invalid_key());
+		}
+
+		let now = chrono::Utc::now().naive_utc();
+
+		let model = match self.repository.find_by_hash(&hash_key(key)).await? {
+			Some(val) => val,
+			None => return Err(invalid_key())
+		};
+
+		if model.expires_at.is_some_and(|d| d <= now) {
+			return Err(ServiceError::Unauthorized("API KEY EXPIRED.".to_owned()));
+		}
+
+		if touch::is_due(model.last_used_at, now) {
+			self.repository.touch(model.id, now).await?;
+		}
+
+		Ok(ApiKeyPrincipal {
+			key_id: model.id,
+			scopes: model.scopes.split(',').map(str::to_owned).collect()
+		})
+	}
+}
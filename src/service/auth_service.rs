@@ -0,0 +1,365 @@
+use metrics::counter;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::{ SystemTime, UNIX_EPOCH };
+
+use crate::model::auth_model::{ ChangePasswordBody, LoginBody, UnlockBody };
+use crate::model::two_factor_model::{ ChallengeClaims, TwoFactorLoginBody };
+use crate::model::user_model::{ JwtClaims, UserData };
+use crate::repository::user_repository::UserRepository;
+use crate::service::login_throttle_service::LoginThrottleService;
+use crate::service::password_service::PasswordService;
+use crate::service::session_service::SessionService;
+use crate::service::two_factor_service::TwoFactorService;
+use crate::service::ServiceError;
+use crate::utils::client_ip::ClientInfo;
+use crate::utils::jwt::JwtKeys;
+
+use entity::{ user, user_session };
+
+pub struct AuthenticatedUser {
+	pub user: UserData,
+	pub token: String
+}
+
+pub enum LoginOutcome {
+	Authenticated(AuthenticatedUser),
+	// The password was right but a second factor is still needed.
+	Challenge {
+		token: String,
+		enrollment_required: bool,
+		expires_in: u64
+	}
+}
+
+// Whose 2FA an enrolment request manages. `challenge` is set when the
+// bearer is an `enroll` challenge rather than a normal token.
+pub struct TwoFactorSubject {
+	pub user: user::Model,
+	pub challenge: Option<ChallengeClaims>
+}
+
+const CHALLENGE_LOGIN: &str = "login";
+const CHALLENGE_ENROLL: &str = "enroll";
+const CHALLENGE_AUDIENCE: &str = "two-factor-challenge";
+
+#[derive(Clone)]
+pub struct AuthService {
+	users: Arc<dyn UserRepository>,
+	jwt: Arc<JwtKeys>,
+	throttle: LoginThrottleService,
+	two_factor: TwoFactorService,
+	passwords: PasswordService,
+	sessions: SessionService
+}
+
+fn now_seconds() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+fn naive_at(seconds: u64) -> chrono::NaiveDateTime {
+	chrono::DateTime::from_timestamp(seconds as i64, 0).unwrap_or_default().naive_utc()
+}
+
+impl AuthService {
+	pub fn new(
+		users: Arc<dyn UserRepository>,
+		jwt: Arc<JwtKeys>,
+		throttle: LoginThrottleService,
+		two_factor: TwoFactorService,
+		passwords: PasswordService,
+		sessions: SessionService
+	) -> Self {
+		Self { users, jwt, throttle, two_factor, passwords, sessions }
+	}
+
+	fn issue_token(&self, user: &user::Model, sid: &str, expires_at: u64) -> Result<String, ServiceError> {
+		let jwt_claims = JwtClaims {
+			sub: user.id,
+			iat: now_seconds() as usize,
+			exp: expires_at as usize,
+			jti: uuid::Uuid::new_v4().to_string(),
+			iss: self.jwt.issuer.clone(),
+			aud: self.jwt.audience.clone(),
+			role: user.role.clone(),
+			token_version: user.token_version,
+			sid: sid.to_owned()
+		};
+
+		self.jwt.encode(&jwt_claims)
+		.map_err(|e| ServiceError::Internal(e.to_string()))
+	}
+
+	fn issue_challenge(&self, user_id: i32, purpose: &str) -> Result<String, ServiceError> {
+		let claims = ChallengeClaims {
+			sub: user_id,
+			purpose: purpose.to_owned(),
+			exp: (now_seconds() + self.two_factor.challenge_lifetime_seconds()) as usize,
+			iss: self.jwt.issuer.clone(),
+			aud: CHALLENGE_AUDIENCE.to_owned(),
+			jti: uuid::Uuid::new_v4().to_string()
+		};
+
+		self.jwt.encode(&claims)
+		.map_err(|e| ServiceError::Internal(e.to_string()))
+	}
+
+	// A redeemed challenge is turned away before any factor is checked, so a
+	// replay can't use up a recovery code.
+	async fn decode_challenge(&self, token: &str, purpose: &str) -> Result<ChallengeClaims, ServiceError> {
+		let claims = self.jwt.decode::<ChallengeClaims>(token, CHALLENGE_AUDIENCE)
+		.map_err(|_| ServiceError::Unauthorized("INVALID OR EXPIRED CHALLENGE.".to_owned()))?;
+
+		if claims.purpose != purpose || self.two_factor.is_challenge_used(&claims.jti).await? {
+			return Err(ServiceError::Unauthorized("INVALID OR EXPIRED CHALLENGE.".to_owned()));
+		}
+
+		Ok(claims)
+	}
+
+	async fn use_challenge(&self, claims: &ChallengeClaims) -> Result<(), ServiceError> {
+		if !self.two_factor.use_challenge(&claims.jti, claims.exp).await? {
+			return Err(ServiceError::Unauthorized("INVALID OR EXPIRED CHALLENGE.".to_owned()));
+		}
+
+		Ok(())
+	}
+
+	// Opens a session for the user and hands out its first token, once every
+	// factor has passed.
+	pub async fn authenticate(&self, user: user::Model, client: &ClientInfo) -> Result<AuthenticatedUser, ServiceError> {
+		counter!("auth_login_attempts_total", "outcome" => "success").increment(1);
+
+		let expires_at = now_seconds() + self.jwt.token_lifetime_seconds;
+		let sid = self.sessions.start(user.id, client, naive_at(expires_at)).await?;
+		let token = self.issue_token(&user, &sid, expires_at)?;
+
+		Ok(AuthenticatedUser { user: UserData::from(user), token })
+	}
+
+	// Checks a bearer token: signature, expiry, issuer, audience, that its
+	// user's sessions haven't all been revoked since it was issued and that
+	// its own session is still open.
+	async fn verify(&self, token: &str) -> Result<(JwtClaims, user::Model, user_session::Model), ServiceError> {
+		let claims = self.jwt.decode::<JwtClaims>(token, &self.jwt.audience)
+		.map_err(|e| ServiceError::Unauthorized(e.to_string()))?;
+
+		let user = match self.users.find_by_id(claims.sub).await? {
+			Some(val) if val.token_version == claims.token_version => val,
+			_ => return Err(ServiceError::Unauthorized("SESSION REVOKED.".to_owned()))
+		};
+
+		let session = self.sessions.find_valid(&claims.sid).await?
+		.filter(|d| d.user_id == user.id)
+		.ok_or_else(|| ServiceError::Unauthorized("SESSION REVOKED.".to_owned()))?;
+
+		Ok((claims, user, session))
+	}
+
+	// The user is loaded anyway, so a role change applies at once instead of
+	// when the token expires.
+	pub async fn authorize(&self, token: &str) -> Result<JwtClaims, ServiceError> {
+		let (mut claims, user, _) = self.verify(token).await?;
+		claims.role = user.role;
+
+		Ok(claims)
+	}
+
+	// The throttle is only reset once every factor has passed, so a known
+	// password doesn't buy unlimited guesses at the code.
+	pub async fn login(&self, body: LoginBody, client: &ClientInfo) -> Result<LoginOutcome, ServiceError> {
+		let ip = client.ip;
+		self.throttle.check(&body.username, ip).await?;
+
+		let user = self.users.find_by_username(&body.username).await?;
+
+		// A malformed stored hash counts as a wrong password.
+		let user = match user {
+			Some(val) if self.passwords.verify(&body.password, &val.password) => val,
+			Some(_) => return Err(self.reject_login(&body.username, ip).await),
+			None => {
+				self.passwords.verify_dummy(&body.password);
+				return Err(self.reject_login(&body.username, ip).await);
+			}
+		};
+
+		let user = self.upgrade_hash(user, &body.password).await;
+
+		if let Some(challenge) = self.second_factor(&user).await? {
+			return Ok(challenge);
+		}
+
+		self.throttle.record_success(&body.username).await?;
+
+		Ok(LoginOutcome::Authenticated(self.authenticate(user, client).await?))
+	}
+
+	// A user an identity provider vouched for still owes the local second
+	// factor, so `two_factor.required_roles` can't be sidestepped through it.
+	pub async fn login_external(&self, user: user::Model, client: &ClientInfo) -> Result<LoginOutcome, ServiceError> {
+		if let Some(challenge) = self.second_factor(&user).await? {
+			return Ok(challenge);
+		}
+
+		Ok(LoginOutcome::Authenticated(self.authenticate(user, client).await?))
+	}
+
+	// The challenge to answer before the user gets a session, if any: a code
+	// when 2FA is on, enrolment when the role requires it.
+	async fn second_factor(&self, user: &user::Model) -> Result<Option<LoginOutcome>, ServiceError> {
+		let purpose = if self.two_factor.is_enabled(user.id).await? {
+			CHALLENGE_LOGIN
+		} else if self.two_factor.required_for(&user.role) {
+			CHALLENGE_ENROLL
+		} else {
+			return Ok(None);
+		};
+
+		counter!("auth_login_attempts_total", "outcome" => "challenge").increment(1);
+
+		Ok(Some(LoginOutcome::Challenge {
+			token: self.issue_challenge(user.id, purpose)?,
+			enrollment_required: purpose == CHALLENGE_ENROLL,
+			expires_in: self.two_factor.challenge_lifetime_seconds()
+		}))
+	}
+
+	// Second step of a login that got a `login` challenge.
+	pub async fn complete_two_factor(&self, body: TwoFactorLoginBody, client: &ClientInfo) -> Result<AuthenticatedUser, ServiceError> {
+		let ip = client.ip;
+		let claims = self.decode_challenge(&body.challenge_token, CHALLENGE_LOGIN).await?;
+
+		let user = self.users.find_by_id(claims.sub).await?
+		.ok_or_else(|| ServiceError::Unauthorized("INVALID OR EXPIRED CHALLENGE.".to_owned()))?;
+
+		self.throttle.check(&user.username, ip).await?;
+
+		if !self.two_factor.verify(user.id, &body.code).await? {
+			counter!("auth_login_attempts_total", "outcome" => "failure").increment(1);
+			self.throttle.record_failure(&user.username, ip).await?;
+
+			return Err(ServiceError::Unauthorized("INVALID TWO-FACTOR CODE".to_owned()));
+		}
+
+		self.use_challenge(&claims).await?;
+		self.throttle.record_success(&user.username).await?;
+
+		self.authenticate(user, client).await
+	}
+
+	// Enrolment accepts either a normal token or an `enroll` challenge, the
+	// latter for users whose role requires 2FA before they can log in. The
+	// challenge only holds while that is still the case, so it can't turn into
+	// a session once the role changed or 2FA was set up some other way.
+	pub async fn two_factor_subject(&self, token: &str) -> Result<TwoFactorSubject, ServiceError> {
+		if let Ok((_, user, _)) = self.verify(token).await {
+			return Ok(TwoFactorSubject { user, challenge: None });
+		}
+
+		let claims = self.decode_challenge(token, CHALLENGE_ENROLL).await?;
+
+		let user = match self.users.find_by_id(claims.sub).await? {
+			Some(val) if self.two_factor.required_for(&val.role) && !self.two_factor.is_enabled(val.id).await? => val,
+			_ => return Err(ServiceError::Unauthorized("INVALID OR EXPIRED CHALLENGE.".to_owned()))
+		};
+
+		Ok(TwoFactorSubject { user, challenge: Some(claims) })
+	}
+
+	// Finishes a login that was held back until 2FA was set up, redeeming the
+	// challenge so it can't open a second session.
+	pub async fn complete_enrollment(&self, subject: TwoFactorSubject, client: &ClientInfo) -> Result<Option<AuthenticatedUser>, ServiceError> {
+		let Some(claims) = subject.challenge else {
+			return Ok(None);
+		};
+
+		self.use_challenge(&claims).await?;
+		self.throttle.record_success(&subject.user.username).await?;
+
+		self.authenticate(subject.user, client).await.map(Some)
+	}
+
+	// Moves a legacy hash (e.g. bcrypt) to the current algorithm while the
+	// plain password is at hand. A failure only delays the upgrade.
+	async fn upgrade_hash(&self, mut user: user::Model, password: &str) -> user::Model {
+		let rehashed = match self.passwords.rehash_if_outdated(password, &user.password) {
+			Ok(Some(val)) => val,
+			Ok(None) => return user,
+			Err(e) => {
+				tracing::warn!(user_id = user.id, error = %e, "password rehash failed");
+				return user;
+			}
+		};
+
+		let previous = std::mem::replace(&mut user.password, rehashed);
+
+		match self.users.update(user.clone()).await {
+			Ok(val) => {
+				counter!("auth_password_rehashes_total").increment(1);
+				val
+			},
+			Err(e) => {
+				tracing::warn!(user_id = user.id, error = %e, "storing rehashed password failed");
+				user.password = previous;
+				user
+			}
+		}
+	}
+
+	async fn reject_login(&self, username: &str, ip: Option<IpAddr>) -> ServiceError {
+		counter!("auth_login_attempts_total", "outcome" => "failure").increment(1);
+
+		match self.throttle.record_failure(username, ip).await {
+			Ok(()) => ServiceError::Unauthorized("INVALID USERNAME / PASSWORD".to_owned()),
+			Err(e) => e
+		}
+	}
+
+	// Exchanges a still-valid token for a fresh one with the refresh lifetime.
+	pub async fn refresh(&self, token: &str) -> Result<AuthenticatedUser, ServiceError> {
+		let (claims, user, session) = self.verify(token).await
+		.map_err(|_| ServiceError::Unauthorized("INVALID CREDENTIALS.".to_owned()))?;
+
+		let expires_at = now_seconds() + self.jwt.refresh_lifetime_seconds;
+		self.sessions.extend(&session, naive_at(expires_at)).await?;
+
+		let token = self.issue_token(&user, &claims.sid, expires_at)?;
+
+		Ok(AuthenticatedUser { user: UserData::from(user), token })
+	}
+
+	// Checking the old password is as good an oracle as logging in, so it is
+	// throttled the same way.
+	pub async fn change_password(&self, body: ChangePasswordBody, ip: Option<IpAddr>) -> Result<(), ServiceError> {
+		let mut user = match self.users.find_by_id(body.id).await {
+			Ok(Some(val)) => val,
+			_ => return Err(ServiceError::NotFound("Data User tidak ditemukan".to_owned()))
+		};
+
+		self.throttle.check(&user.username, ip).await?;
+
+		if !self.passwords.verify(&body.old_password, &user.password) {
+			self.throttle.record_failure(&user.username, ip).await?;
+			return Err(ServiceError::BadRequest("Old password didn't matc!!!".to_owned()));
+		}
+
+		self.throttle.record_success(&user.username).await?;
+
+		let new_hash = self.passwords.hash_replacement(&user, &body.new_password).await?;
+		let old_hash = std::mem::replace(&mut user.password, new_hash);
+		user.updated_at = chrono::Utc::now().naive_utc();
+
+		self.users.update(user).await
+		.map_err(|_| ServiceError::BadRequest("Request Failed.".to_owned()))?;
+
+		self.passwords.remember(body.id, &old_hash).await
+	}
+
+	pub async fn unlock(&self, body: UnlockBody) -> Result<u64, ServiceError> {
+		if body.username.is_none() && body.ip.is_none() {
+			return Err(ServiceError::BadRequest("Give a username, an ip or both.".to_owned()));
+		}
+
+		self.throttle.unlock(body.username.as_deref(), body.ip.as_deref()).await
+	}
+}
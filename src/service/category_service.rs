@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use crate::model::category_model::{ CategoryCreateBody, CategoryData, CategoryPaginate, CategoryUpdateBody };
+use crate::model::pagination_model::{ PaginationBody, PaginationResponse };
+use crate::repository::category_repository::CategoryRepository;
+use crate::service::ServiceError;
+
+const PAGE_TAKE: i64 = 10;
+
+#[derive(Clone)]
+pub struct CategoryService {
+	categories: Arc<dyn CategoryRepository>
+}
+
+impl CategoryService {
+	pub fn new(categories: Arc<dyn CategoryRepository>) -> Self {
+		Self { categories }
+	}
+
+	pub async fn find_many(&self) -> Result<Vec<CategoryData>, ServiceError> {
+		Ok(self.categories.find_all().await?.into_iter().map(CategoryData::from).collect())
+	}
+
+	pub async fn search_paginate(&self, body: PaginationBody) -> Result<CategoryPaginate, ServiceError> {
+		let page_offset = (body.page - 1).max(0) * PAGE_TAKE;
+
+		let count = self.categories.count_search(&body.term).await?;
+		let data = self.categories.search(&body.term, page_offset as u64, PAGE_TAKE as u64).await?;
+
+		Ok(CategoryPaginate {
+			data: data.into_iter().map(CategoryData::from).collect(),
+			paginate: PaginationResponse::new(count, body.page, PAGE_TAKE)
+		})
+	}
+
+	pub async fn find_first(&self, id: i32) -> Result<CategoryData, ServiceError> {
+		match self.categories.find_by_id(id).await? {
+			Some(val) => Ok(val.into()),
+			None => Err(ServiceError::NotFound("Data Not Found!!!".to_owned()))
+		}
+	}
+
+	pub async fn create(&self, body: CategoryCreateBody) -> Result<CategoryData, ServiceError> {
+		Ok(self.categories.insert(body.name).await?.into())
+	}
+
+	pub async fn update(&self, id: i32, body: CategoryUpdateBody) -> Result<CategoryData, ServiceError> {
+		let mut category = match self.categories.find_by_id(id).await? {
+			Some(val) => val,
+			None => return Err(ServiceError::NotFound("Data Not Found!!!".to_owned()))
+		};
+
+		if let Some(name) = body.name {
+			category.name = name;
+		}
+
+		category.updated_at = chrono::Utc::now().naive_utc();
+
+		self.categories.update(category).await
+		.map(CategoryData::from)
+		.map_err(|_| ServiceError::BadRequest("Failed to Update Category Data.".to_owned()))
+	}
+
+	pub async fn delete(&self, id: i32) -> Result<(), ServiceError> {
+		match self.categories.delete_by_id(id).await? {
+			0 => Err(ServiceError::NotFound("Data Not Found!!!.".to_owned())),
+			_ => Ok(())
+		}
+	}
+}
@@ -0,0 +1,375 @@
+use bytes::Bytes;
+use futures_util::{ Stream, StreamExt };
+use metrics::counter;
+use sha2::{ Digest, Sha256 };
+use std::{ collections::HashMap, fmt, path::PathBuf, sync::Arc };
+use tokio::{ fs::{ self, File }, io::{ AsyncReadExt, AsyncWriteExt } };
+
+use crate::model::file_model::{ FileUploadResponse, SignedFileUrl };
+use crate::model::product_image_model::ProductImageData;
+use crate::repository::stored_file_repository::StoredFileRepository;
+use crate::repository::user_repository::UserRepository;
+use crate::service::product_image_service::ProductImageService;
+use crate::service::ServiceError;
+use crate::utils::file_buckets::FileBucket;
+use crate::utils::file_store::{ content_type_for, file_extension, file_url, is_safe_file_name, FileStorage };
+use crate::utils::signed_url::{ signed_file_url, verify };
+
+use entity::stored_file;
+
+pub struct StoredUpload {
+	pub file: stored_file::Model,
+	pub duplicate: bool
+}
+
+pub struct FileContent {
+	pub content_type: String,
+	pub contents: Vec<u8>
+}
+
+#[derive(Clone)]
+pub struct FileService {
+	storage: Arc<FileStorage>,
+	files: Arc<dyn StoredFileRepository>,
+	product_images: ProductImageService,
+	users: Arc<dyn UserRepository>
+}
+
+fn file_not_found() -> ServiceError {
+	ServiceError::NotFound("File Not Found.".to_owned())
+}
+
+async fn read_file(file_path: PathBuf) -> Option<Vec<u8>> {
+	let mut file = File::open(&file_path).await.ok()?;
+	let mut contents = Vec::new();
+
+	file.read_to_end(&mut contents).await.ok()?;
+
+	Some(contents)
+}
+
+fn duplicate_of(existing: stored_file::Model, is_private: bool) -> Result<StoredUpload, ServiceError> {
+	if existing.is_private != is_private {
+		return Err(ServiceError::Conflict("Identical content is already stored with a different visibility.".to_owned()));
+	}
+
+	Ok(StoredUpload { file: existing, duplicate: true })
+}
+
+impl FileService {
+	pub fn new(
+		storage: Arc<FileStorage>,
+		files: Arc<dyn StoredFileRepository>,
+		product_images: ProductImageService,
+		users: Arc<dyn UserRepository>
+	) -> Self {
+		Self { storage, files, product_images, users }
+	}
+
+	pub fn bucket(&self, name: &str) -> Result<&FileBucket, ServiceError> {
+		self.storage.buckets.get(name).ok_or_else(|| ServiceError::NotFound("Unknown file bucket.".to_owned()))
+	}
+
+	// Streams the upload into a temporary file while hashing it, then moves it to
+	// `{hash}.{ext}`. An upload whose content already exists in the namespace
+	// resolves to the existing object instead of being written again, as long as
+	// it asks for the same visibility: both share one name, so one can't be
+	// public while the other is private.
+	pub async fn store<S, E>(
+		&self,
+		bucket: &FileBucket,
+		original_name: &str,
+		is_private: bool,
+		chunks: S
+	) -> Result<StoredUpload, ServiceError>
+	where
+		S: Stream<Item = Result<Bytes, E>>,
+		E: fmt::Display
+	{
+		let namespace = bucket.name.as_str();
+		let upload_dir = bucket.directory();
+		let ext = file_extension(original_name);
+
+		if !bucket.allows_extension(&ext) {
+			return Err(ServiceError::BadRequest(format!("File type .{ext} is not allowed in {namespace}.")));
+		}
+
+		let internal = |e: &dyn fmt::Display| ServiceError::Internal(e.to_string());
+
+		fs::create_dir_all(&upload_dir).await.map_err(|e| internal(&e))?;
+
+		let temp_path = format!("{}/.upload-{}", upload_dir, uuid::Uuid::new_v4());
+		let mut temp_file = File::create(&temp_path).await.map_err(|e| internal(&e))?;
+
+		let mut chunks = std::pin::pin!(chunks);
+		let mut hasher = Sha256::new();
+		let mut size: i64 = 0;
+
+		while let Some(chunk) = chunks.next().await {
+			let chunk = match chunk {
+				Ok(chunk) => chunk,
+				Err(e) => {
+					let _ = fs::remove_file(&temp_path).await;
+					return Err(ServiceError::BadRequest(e.to_string()));
+				}
+			};
+
+			hasher.update(&chunk);
+			size += chunk.len() as i64;
+
+			if size as u64 > bucket.max_file_size {
+				let _ = fs::remove_file(&temp_path).await;
+				return Err(ServiceError::BadRequest(
+					format!("File exceeds the {} byte limit of {namespace}.", bucket.max_file_size)
+				));
+			}
+
+			if let Err(e) = temp_file.write_all(&chunk).await {
+				let _ = fs::remove_file(&temp_path).await;
+				return Err(internal(&e));
+			}
+		}
+
+		if let Err(e) = temp_file.flush().await {
+			let _ = fs::remove_file(&temp_path).await;
+			return Err(internal(&e));
+		}
+
+		drop(temp_file);
+
+		// Received bytes, whether or not they turn out to duplicate a stored file.
+		counter!("file_upload_bytes_total", "bucket" => namespace.to_owned()).increment(size as u64);
+		counter!("file_uploads_total", "bucket" => namespace.to_owned()).increment(1);
+
+		let hash = hex::encode(hasher.finalize());
+
+		if let Some(existing) = self.files.find_by_hash(namespace, &hash).await? {
+			let _ = fs::remove_file(&temp_path).await;
+
+			return duplicate_of(existing, is_private);
+		}
+
+		// `.jfif` uploads are stored without an extension, as before.
+		let file_name = if ext.is_empty() || ext == "jfif" {
+			hash.clone()
+		} else {
+			format!("{hash}.{ext}")
+		};
+
+		let file_path = format!("{}/{}", upload_dir, file_name);
+
+		if fs::try_exists(&file_path).await.unwrap_or(false) {
+			let _ = fs::remove_file(&temp_path).await;
+		} else if let Err(e) = fs::rename(&temp_path, &file_path).await {
+			let _ = fs::remove_file(&temp_path).await;
+			return Err(internal(&e));
+		}
+
+		let data = stored_file::Model {
+			id: 0,
+			namespace: namespace.to_owned(),
+			hash: hash.clone(),
+			file_name,
+			original_name: original_name.to_owned(),
+			content_type: content_type_for(&ext).to_owned(),
+			size,
+			is_private,
+			created_at: chrono::Utc::now().naive_utc()
+		};
+
+		match self.files.insert(data).await {
+			Ok(file) => Ok(StoredUpload { file, duplicate: false }),
+			Err(e) => {
+				// A concurrent upload of the same content may have won the unique index.
+				match self.files.find_by_hash(namespace, &hash).await? {
+					Some(existing) => duplicate_of(existing, is_private),
+					None => Err(e.into())
+				}
+			}
+		}
+	}
+
+	pub fn upload_response(&self, bucket: &FileBucket, stored: StoredUpload) -> FileUploadResponse {
+		let namespace = bucket.name.as_str();
+
+		let url = if stored.file.is_private {
+			signed_file_url(&self.storage.config, namespace, &stored.file.file_name).0
+		} else {
+			file_url(namespace, &stored.file.file_name)
+		};
+
+		FileUploadResponse {
+			id: stored.file.id,
+			file_extension: file_extension(&stored.file.original_name),
+			url,
+			is_private: stored.file.is_private,
+			hash: stored.file.hash,
+			file_name: stored.file.file_name,
+			content_type: stored.file.content_type,
+			size: stored.file.size,
+			duplicate: stored.duplicate
+		}
+	}
+
+	// Stores one file of a batch. `form` holds the plain fields sent before it:
+	// `visibility`, and for the product bucket `product_id`, `alt_text` and
+	// `is_primary`, which also add the file to that product's gallery.
+	pub async fn upload<S, E>(
+		&self,
+		bucket: &FileBucket,
+		form: &HashMap<String, String>,
+		original_name: &str,
+		chunks: S
+	) -> Result<(FileUploadResponse, Option<ProductImageData>), ServiceError>
+	where
+		S: Stream<Item = Result<Bytes, E>>,
+		E: fmt::Display
+	{
+		// Resolve the target product before storing so a bad id doesn't leave an orphan file.
+		let product_id = match form.get("product_id") {
+			Some(val) if bucket.name == "product" => {
+				let id = val.trim().parse::<i32>()
+				.map_err(|_| ServiceError::BadRequest("product_id must be a number.".to_owned()))?;
+
+				self.product_images.ensure_product(id).await?;
+
+				Some(id)
+			},
+			_ => None
+		};
+
+		let is_private = form.get("visibility").is_some_and(|val| val == "private");
+
+		let stored = self.store(bucket, original_name, is_private, chunks).await?;
+		let file_name = stored.file.file_name.clone();
+		let file = self.upload_response(bucket, stored);
+
+		let image = match product_id {
+			Some(product_id) => {
+				let alt_text = form.get("alt_text").cloned();
+				let is_primary = form.get("is_primary").is_some_and(|val| val == "true");
+
+				Some(self.product_images.attach(product_id, file_name, alt_text, is_primary).await?)
+			},
+			None => None
+		};
+
+		Ok((file, image))
+	}
+
+	// Files without a `stored_file` row predate visibility tracking and stay public.
+	// Missing files fall back to the bucket's placeholder image when it has one.
+	pub async fn open(
+		&self,
+		bucket: &FileBucket,
+		filename: &str,
+		expires: Option<i64>,
+		signature: Option<&str>
+	) -> Result<FileContent, ServiceError> {
+		if !is_safe_file_name(filename) {
+			return Err(file_not_found());
+		}
+
+		let namespace = bucket.name.as_str();
+		// A failed lookup must not serve a possibly private file as public.
+		let stored = self.files.find_by_name(namespace, filename).await?;
+
+		if let Some(val) = &stored {
+			if val.is_private {
+				let authorized = match (expires, signature) {
+					(Some(expires), Some(signature)) => verify(&self.storage.config, namespace, filename, expires, signature),
+					_ => false
+				};
+
+				if !authorized {
+					return Err(ServiceError::Forbidden("Invalid or expired file signature.".to_owned()));
+				}
+			}
+		}
+
+		let content_type = stored.map(|val| val.content_type).unwrap_or("image/*".to_string());
+
+		if let Some(contents) = read_file(PathBuf::from(bucket.directory()).join(filename)).await {
+			return Ok(FileContent { content_type, contents });
+		}
+
+		if let Some(default_file) = &bucket.default_file {
+			if let Some(contents) = read_file(PathBuf::from(bucket.directory()).join(default_file)).await {
+				return Ok(FileContent { content_type: "image/*".to_string(), contents });
+			}
+		}
+
+		Err(file_not_found())
+	}
+
+	pub async fn sign(&self, bucket: &FileBucket, filename: &str) -> Result<SignedFileUrl, ServiceError> {
+		let namespace = bucket.name.as_str();
+
+		match self.files.find_by_name(namespace, filename).await? {
+			Some(val) => {
+				let (url, expires_at) = signed_file_url(&self.storage.config, namespace, &val.file_name);
+
+				Ok(SignedFileUrl { url, expires_at })
+			},
+			None => Err(file_not_found())
+		}
+	}
+
+	// Refused while several records use the file: they may belong to
+	// different uploaders, who would all be affected.
+	pub async fn update_visibility(&self, bucket: &FileBucket, filename: &str, is_private: bool) -> Result<(), ServiceError> {
+		let stored = self.files.find_by_name(&bucket.name, filename).await?.ok_or_else(file_not_found)?;
+		let references = self.count_references(bucket, filename).await?;
+
+		if references > 1 {
+			return Err(ServiceError::Conflict(format!("The file is shared by {references} records; its visibility can't change.")));
+		}
+
+		Ok(self.files.set_private(stored.id, is_private).await?)
+	}
+
+	// Rows naming the file: gallery images and products for `product`, user
+	// photos for `user`. Uploads of the same content by different users
+	// resolve to one object, so any of them may be the one still in use.
+	async fn count_references(&self, bucket: &FileBucket, file_name: &str) -> Result<u64, ServiceError> {
+		Ok(match bucket.name.as_str() {
+			"product" => self.product_images.count_references(file_name).await?,
+			"user" => self.users.count_photo_references(file_name).await?,
+			_ => 0
+		})
+	}
+
+	// Deduplicated uploads share one object, so it is only removed once
+	// nothing else points at it. The row goes first: a row without its object
+	// would make later uploads of the same content resolve to a missing file.
+	pub async fn delete(&self, bucket: &FileBucket, filename: &str) -> Result<(), ServiceError> {
+		let not_found = || ServiceError::NotFound("File not found or cannot deleted the default file.".to_owned());
+
+		if !is_safe_file_name(filename) || bucket.is_default_file(filename) {
+			return Err(not_found());
+		}
+
+		let file_path = PathBuf::from(bucket.directory()).join(filename);
+		let stored = self.files.find_by_name(&bucket.name, filename).await?;
+
+		if stored.is_none() && !fs::try_exists(&file_path).await.unwrap_or(false) {
+			return Err(not_found());
+		}
+
+		let references = self.count_references(bucket, filename).await?;
+
+		if references > 0 {
+			return Err(ServiceError::Conflict(format!("The file is still used by {references} record(s).")));
+		}
+
+		if stored.is_some() {
+			self.files.delete_by_name(&bucket.name, filename).await?;
+		}
+
+		match fs::remove_file(file_path).await {
+			Ok(()) => Ok(()),
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+			Err(e) => Err(ServiceError::Internal(e.to_string()))
+		}
+	}
+}
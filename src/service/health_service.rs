@@ -0,0 +1,51 @@
+use migration::{ Migrator, MigratorTrait };
+use sea_orm::DatabaseConnection;
+use std::sync::{ atomic::{ AtomicBool, Ordering }, Arc };
+
+use crate::model::health_model::ReadinessResponse;
+
+#[derive(Clone)]
+pub struct HealthService {
+	db: DatabaseConnection,
+	shutting_down: Arc<AtomicBool>
+}
+
+impl HealthService {
+	pub fn new(db: DatabaseConnection) -> Self {
+		Self { db, shutting_down: Arc::new(AtomicBool::new(false)) }
+	}
+
+	// Fails readiness from now on so load balancers stop routing here while
+	// in-flight requests drain.
+	pub fn begin_shutdown(&self) {
+		self.shutting_down.store(true, Ordering::SeqCst);
+	}
+
+	pub async fn readiness(&self) -> ReadinessResponse {
+		let database = match self.db.ping().await {
+			Ok(()) => "ok".to_owned(),
+			Err(e) => {
+				tracing::warn!(error = %e, "readiness: database ping failed");
+				"unreachable".to_owned()
+			}
+		};
+
+		let pending_migrations = match Migrator::get_pending_migrations(&self.db).await {
+			Ok(val) => Some(val.len()),
+			Err(e) => {
+				tracing::warn!(error = %e, "readiness: failed to read migration status");
+				None
+			}
+		};
+
+		let shutting_down = self.shutting_down.load(Ordering::SeqCst);
+		let ready = database == "ok" && pending_migrations == Some(0) && !shutting_down;
+
+		ReadinessResponse {
+			status: if ready { "ready" } else { "unavailable" }.to_owned(),
+			database,
+			pending_migrations,
+			shutting_down
+		}
+	}
+}
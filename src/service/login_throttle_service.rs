@@ -0,0 +1,141 @@
+use chrono::{ NaiveDateTime, TimeDelta, Utc };
+use metrics::counter;
+use std::{ net::IpAddr, sync::Arc };
+
+use crate::config::LoginThrottleConfig;
+use crate::repository::login_attempt_repository::LoginAttemptRepository;
+use crate::service::ServiceError;
+
+use entity::login_attempt;
+
+const USERNAME_SCOPE: &str = "username";
+const IP_SCOPE: &str = "ip";
+
+// Longest key stored; the column is a default-length string.
+const MAX_KEY_CHARS: usize = 255;
+
+// Backoff and lockout of password guessing. Usernames are tracked whether or
+// not they exist, so a lock reveals nothing about which accounts do.
+#[derive(Clone)]
+pub struct LoginThrottleService {
+	attempts: Arc<dyn LoginAttemptRepository>,
+	config: LoginThrottleConfig
+}
+
+fn keys(username: &str, ip: Option<IpAddr>) -> Vec<(&'static str, String)> {
+	let mut keys = vec![(USERNAME_SCOPE, username.trim().chars().take(MAX_KEY_CHARS).collect())];
+
+	if let Some(ip) = ip {
+		keys.push((IP_SCOPE, ip.to_string()));
+	}
+
+	keys
+}
+
+fn seconds(val: u64) -> TimeDelta {
+	TimeDelta::seconds(val.min(i64::MAX as u64) as i64)
+}
+
+impl LoginThrottleService {
+	pub fn new(attempts: Arc<dyn LoginAttemptRepository>, config: LoginThrottleConfig) -> Self {
+		Self { attempts, config }
+	}
+
+	fn max_failures(&self, scope: &str) -> u32 {
+		if scope == IP_SCOPE { self.config.ip_max_failures } else { self.config.max_failures }
+	}
+
+	fn is_expired(&self, attempt: &login_attempt::Model, now: NaiveDateTime) -> bool {
+		attempt.locked_until.is_none_or(|d| d <= now)
+		&& now - attempt.last_failure_at > seconds(self.config.window_seconds)
+	}
+
+	// When the next attempt for this key is allowed, if not yet.
+	fn blocked_until(&self, attempt: &login_attempt::Model, now: NaiveDateTime) -> Option<NaiveDateTime> {
+		if let Some(locked_until) = attempt.locked_until.filter(|d| *d > now) {
+			return Some(locked_until);
+		}
+
+		let failures = attempt.failures.max(0) as u32;
+
+		if self.is_expired(attempt, now) || failures < self.config.free_attempts {
+			return None;
+		}
+
+		let exponent = (failures - self.config.free_attempts).min(32);
+		let delay = self.config.backoff_base_seconds.saturating_mul(1 << exponent).min(self.config.backoff_max_seconds);
+
+		Some(attempt.last_failure_at + seconds(delay)).filter(|d| *d > now)
+	}
+
+	pub async fn check(&self, username: &str, ip: Option<IpAddr>) -> Result<(), ServiceError> {
+		let now = Utc::now().naive_utc();
+		let mut retry_at: Option<NaiveDateTime> = None;
+
+		for (scope, key) in keys(username, ip) {
+			if let Some(attempt) = self.attempts.find(scope, &key).await? {
+				retry_at = retry_at.max(self.blocked_until(&attempt, now));
+			}
+		}
+
+		match retry_at {
+			Some(val) => Err(ServiceError::TooManyRequests(
+				"Too many failed login attempts, try again later.".to_owned(),
+				(val - now).num_seconds().max(1) as u64
+			)),
+			None => Ok(())
+		}
+	}
+
+	pub async fn record_failure(&self, username: &str, ip: Option<IpAddr>) -> Result<(), ServiceError> {
+		let now = Utc::now().naive_utc();
+		let window_start = now - seconds(self.config.window_seconds);
+
+		for (scope, key) in keys(username, ip) {
+			let attempt = self.attempts.increment(scope, &key, now, window_start).await?;
+			let max_failures = self.max_failures(scope).min(i32::MAX as u32) as i32;
+
+			if attempt.failures >= max_failures {
+				let locked_until = now + seconds(self.config.lockout_seconds);
+
+				// Only the failure that actually sets the lock reports it.
+				if self.attempts.lock(attempt.id, max_failures, now, locked_until).await? {
+					counter!("auth_lockouts_total", "scope" => scope).increment(1);
+					tracing::warn!(scope, key, failures = attempt.failures, "login locked out");
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	// A successful login clears its username; the IP keeps its count so that
+	// logging into one account can't be used to reset guesses at another.
+	pub async fn record_success(&self, username: &str) -> Result<(), ServiceError> {
+		for (scope, key) in keys(username, None) {
+			self.attempts.delete(scope, &key).await?;
+		}
+
+		Ok(())
+	}
+
+	// Lifts the backoff and lockout of a username and/or an IP address.
+	pub async fn unlock(&self, username: Option<&str>, ip: Option<&str>) -> Result<u64, ServiceError> {
+		let mut cleared = 0;
+
+		if let Some(username) = username {
+			for (scope, key) in keys(username, None) {
+				cleared += self.attempts.delete(scope, &key).await?;
+			}
+		}
+
+		if let Some(ip) = ip {
+			let ip: IpAddr = ip.trim().parse()
+			.map_err(|_| ServiceError::BadRequest(format!("{ip} is not an IP address.")))?;
+
+			cleared += self.attempts.delete(IP_SCOPE, &ip.to_string()).await?;
+		}
+
+		Ok(cleared)
+	}
+}
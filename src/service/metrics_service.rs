@@ -0,0 +1,140 @@
+use metrics::gauge;
+use metrics_exporter_prometheus::PrometheusHandle;
+use sea_orm::{ ConnectionTrait, DatabaseBackend, DatabaseConnection };
+use std::sync::Arc;
+use std::time::{ Duration, Instant };
+use tokio::sync::Mutex;
+
+use crate::config::MetricsConfig;
+use crate::repository::product_repository::ProductRepository;
+
+// Gauges that are cheaper to read on scrape than to keep up to date on every
+// write are refreshed here before the exposition is rendered.
+#[derive(Clone)]
+pub struct MetricsService {
+	handle: PrometheusHandle,
+	db: DatabaseConnection,
+	products: Arc<dyn ProductRepository>,
+	max_connections: u32,
+	config: MetricsConfig,
+	// When `products_low_stock` was last counted.
+	low_stock_counted_at: Arc<Mutex<Option<Instant>>>
+}
+
+impl MetricsService {
+	pub fn new(
+		handle: PrometheusHandle,
+		db: DatabaseConnection,
+		products: Arc<dyn ProductRepository>,
+		max_connections: u32,
+		config: MetricsConfig
+	) -> Self {
+		Self { handle, db, products, max_connections, config, low_stock_counted_at: Arc::default() }
+	}
+
+	pub async fn render(&self) -> String {
+		self.record_pool_usage();
+		self.refresh_low_stock().await;
+
+		self.handle.render()
+	}
+
+	// Counts at most once per `low_stock_refresh_seconds`; a scrape that finds
+	// another one counting keeps the previous value instead of waiting.
+	async fn refresh_low_stock(&self) {
+		let Ok(mut counted_at) = self.low_stock_counted_at.try_lock() else {
+			return;
+		};
+
+		let max_age = Duration::from_secs(self.config.low_stock_refresh_seconds);
+
+		if counted_at.is_some_and(|d| d.elapsed() < max_age) {
+			return;
+		}
+
+		match self.products.count_low_stock(self.config.low_stock_threshold).await {
+			Ok(count) => {
+				gauge!("products_low_stock").set(count as f64);
+				*counted_at = Some(Instant::now());
+			},
+			Err(e) => tracing::warn!(error = %e, "failed to count low stock products")
+		}
+	}
+
+	fn record_pool_usage(&self) {
+		let Some((size, idle)) = pool_usage(&self.db) else {
+			return;
+		};
+
+		gauge!("db_pool_max_connections").set(self.max_connections as f64);
+		gauge!("db_pool_connections", "state" => "idle").set(idle as f64);
+		gauge!("db_pool_connections", "state" => "in_use").set(size.saturating_sub(idle) as f64);
+	}
+}
+
+// (open connections, idle connections) of the sqlx pool behind `db`.
+fn pool_usage(db: &DatabaseConnection) -> Option<(u32, u32)> {
+	match db.get_database_backend() {
+		#[cfg(feature = "postgres")]
+		DatabaseBackend::Postgres => {
+			let pool = db.get_postgres_connection_pool();
+			Some((pool.size(), pool.num_idle() as u32))
+		},
+		#[cfg(feature = "mysql")]
+		DatabaseBackend::MySql => {
+			let pool = db.get_mysql_connection_pool();
+			Some((pool.size(), pool.num_idle() as u32))
+		},
+		#[cfg(feature = "sqlite")]
+		DatabaseBackend::Sqlite => {
+			let pool = db.get_sqlite_connection_pool();
+			Some((pool.size(), pool.num_idle() as u32))
+		},
+		#[allow(unreachable_patterns)]
+		_ => None
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::repository::in_memory::InMemoryProductRepository;
+	use metrics_exporter_prometheus::PrometheusBuilder;
+
+	async fn service(low_stock_refresh_seconds: u64) -> MetricsService {
+		MetricsService::new(
+			PrometheusBuilder::new().build_recorder().handle(),
+			sea_orm::Database::connect("sqlite::memory:").await.unwrap(),
+			Arc::new(InMemoryProductRepository::default()),
+			1,
+			MetricsConfig { enabled: true, low_stock_threshold: 5, low_stock_refresh_seconds }
+		)
+	}
+
+	async fn counted_at(metrics: &MetricsService) -> Option<Instant> {
+		*metrics.low_stock_counted_at.lock().await
+	}
+
+	#[tokio::test]
+	async fn scrapes_reuse_a_recent_low_stock_count() {
+		let metrics = service(3600).await;
+
+		metrics.render().await;
+		let first = counted_at(&metrics).await;
+		metrics.render().await;
+
+		assert!(first.is_some());
+		assert_eq!(counted_at(&metrics).await, first);
+	}
+
+	#[tokio::test]
+	async fn low_stock_is_counted_again_once_stale() {
+		let metrics = service(0).await;
+
+		metrics.render().await;
+		let first = counted_at(&metrics).await;
+		metrics.render().await;
+
+		assert!(counted_at(&metrics).await > first);
+	}
+}
@@ -0,0 +1,62 @@
+pub mod api_key_service;
+pub mod auth_service;
+pub mod category_service;
+pub mod file_service;
+pub mod health_service;
+pub mod login_throttle_service;
+pub mod metrics_service;
+pub mod oidc_service;
+pub mod password_reset_service;
+pub mod password_service;
+pub mod product_image_service;
+pub mod product_service;
+pub mod session_service;
+pub mod two_factor_service;
+pub mod user_service;
+
+use sea_orm::DbErr;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum ServiceError {
+	BadRequest(String),
+	Unauthorized(String),
+	Forbidden(String),
+	NotFound(String),
+	// The resource is still in use.
+	Conflict(String),
+	// The request is well formed but refers to something that can't be used.
+	UnprocessableEntity(String),
+	// Message and the number of seconds before the client may retry.
+	TooManyRequests(String, u64),
+	Internal(String)
+}
+
+impl ServiceError {
+	pub fn message(&self) -> &str {
+		match self {
+			ServiceError::BadRequest(e)
+			| ServiceError::Unauthorized(e)
+			| ServiceError::Forbidden(e)
+			| ServiceError::NotFound(e)
+			| ServiceError::Conflict(e)
+			| ServiceError::UnprocessableEntity(e)
+			| ServiceError::TooManyRequests(e, _)
+			| ServiceError::Internal(e) => e
+		}
+	}
+}
+
+impl fmt::Display for ServiceError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}", self.message())
+	}
+}
+
+impl std::error::Error for ServiceError {}
+
+impl From<DbErr> for ServiceError {
+	fn from(e: DbErr) -> Self {
+		ServiceError::Internal(e.to_string())
+	}
+}
@@ -0,0 +1,229 @@
+use base64::{ engine::general_purpose::URL_SAFE_NO_PAD, Engine };
+use rand::RngCore;
+use serde_json::Value;
+use sha2::{ Digest, Sha256 };
+use std::sync::Arc;
+
+use crate::config::OidcConfig;
+use crate::model::oidc_model::OidcCallbackQuery;
+use crate::repository::oidc_repository::OidcRepository;
+use crate::repository::user_repository::UserRepository;
+use crate::service::auth_service::{ AuthService, LoginOutcome };
+use crate::service::password_service::PasswordService;
+use crate::service::ServiceError;
+use crate::utils::client_ip::ClientInfo;
+use crate::utils::oidc::{ ExternalIdentity, IdentityProvider };
+
+use entity::user;
+
+// Login through an external identity provider with the authorization code
+// flow and PKCE. Identities are linked to local users on their first login.
+// A login can only be finished by the browser that started it: `state` is
+// also handed to that browser in a cookie, which the callback must present.
+#[derive(Clone)]
+pub struct OidcService {
+	provider: Option<Arc<dyn IdentityProvider>>,
+	repository: Arc<dyn OidcRepository>,
+	users: Arc<dyn UserRepository>,
+	passwords: PasswordService,
+	auth: AuthService,
+	config: OidcConfig
+}
+
+// Holds `state` between `/login` and `/callback`.
+pub const STATE_COOKIE: &str = "oidc_state";
+
+// 32 random bytes, URL-safe: 43 characters, as long as PKCE allows for the
+// shortest verifier.
+fn random_token() -> String {
+	let mut bytes = [0u8; 32];
+	rand::thread_rng().fill_bytes(&mut bytes);
+
+	URL_SAFE_NO_PAD.encode(bytes)
+}
+
+impl OidcService {
+	pub fn new(
+		repository: Arc<dyn OidcRepository>,
+		users: Arc<dyn UserRepository>,
+		passwords: PasswordService,
+		auth: AuthService,
+		config: OidcConfig
+	) -> Self {
+		Self { provider: None, repository, users, passwords, auth, config }
+	}
+
+	pub fn with_provider(mut self, provider: Arc<dyn IdentityProvider>) -> Self {
+		self.provider = Some(provider);
+		self
+	}
+
+	fn provider(&self) -> Result<&Arc<dyn IdentityProvider>, ServiceError> {
+		self.provider.as_ref().ok_or_else(|| ServiceError::NotFound("OIDC login is not enabled.".to_owned()))
+	}
+
+	// Starts a login and returns the provider URL to send the browser to,
+	// along with its `state` for the cookie.
+	pub async fn begin(&self) -> Result<(String, String), ServiceError> {
+		let provider = self.provider()?;
+		let now = chrono::Utc::now().naive_utc();
+
+		self.repository.delete_expired_states(now).await?;
+
+		let state = random_token();
+		let nonce = random_token();
+		let code_verifier = random_token();
+		let code_challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()));
+
+		let expires_at = now + chrono::Duration::seconds(self.config.login_timeout_seconds as i64);
+		self.repository.insert_state(&state, &code_verifier, &nonce, expires_at).await?;
+
+		let authorization_url = provider.authorization_url(&state, &nonce, &code_challenge).await?;
+
+		Ok((authorization_url, state))
+	}
+
+	// Lax, so that it comes along when the provider redirects back. `Secure`
+	// unless the callback is plain HTTP, as on a development machine.
+	pub fn state_cookie(&self, state: &str) -> String {
+		let secure = if self.config.redirect_uri.starts_with("http://") { "" } else { "; Secure" };
+
+		format!(
+			"{STATE_COOKIE}={state}; Path=/api/auth/oidc; Max-Age={}; HttpOnly; SameSite=Lax{secure}",
+			self.config.login_timeout_seconds
+		)
+	}
+
+	// Finishes a login the provider sent back to `redirect_uri`. Local 2FA
+	// applies as it does to password logins, so the outcome may still be a
+	// challenge.
+	pub async fn complete(
+		&self,
+		query: OidcCallbackQuery,
+		browser_state: Option<&str>,
+		client: &ClientInfo
+	) -> Result<LoginOutcome, ServiceError> {
+		let provider = self.provider()?;
+
+		if let Some(error) = query.error {
+			return Err(ServiceError::Unauthorized(format!(
+				"The identity provider refused the login: {}", query.error_description.unwrap_or(error)
+			)));
+		}
+
+		let (code, state) = match (query.code, query.state) {
+			(Some(code), Some(state)) => (code, state),
+			_ => return Err(ServiceError::BadRequest("code and state are required.".to_owned()))
+		};
+
+		// Otherwise anyone could log a victim's browser into their own account
+		// by sending it a callback link of theirs.
+		if browser_state != Some(state.as_str()) {
+			return Err(ServiceError::BadRequest("LOGIN STATE DOES NOT BELONG TO THIS BROWSER.".to_owned()));
+		}
+
+		let pending = self.repository.take_state(&state, chrono::Utc::now().naive_utc()).await?
+		.ok_or_else(|| ServiceError::BadRequest("INVALID OR EXPIRED LOGIN STATE.".to_owned()))?;
+
+		let identity = provider.exchange(&code, &pending.code_verifier, &pending.nonce).await?;
+		let user = self.resolve(&identity).await?;
+
+		tracing::info!(user_id = user.id, issuer = %identity.issuer, "external login");
+
+		self.auth.login_external(user, client).await
+	}
+
+	// The local user of an identity, linked or provisioned on its first
+	// login. A role mapped from the claims replaces the user's role.
+	async fn resolve(&self, identity: &ExternalIdentity) -> Result<user::Model, ServiceError> {
+		let role = self.mapped_role(identity);
+
+		let mut user = match self.repository.find_identity(&identity.issuer, &identity.subject).await? {
+			Some(link) => self.users.find_by_id(link.user_id).await?
+			.ok_or_else(|| ServiceError::Forbidden("No account is linked to this identity.".to_owned()))?,
+			None => self.link(identity, role.as_deref()).await?
+		};
+
+		if let Some(role) = role.filter(|d| *d != user.role) {
+			tracing::info!(user_id = user.id, from = %user.role, to = %role, "role updated from identity provider");
+
+			user.role = role;
+			user.updated_at = chrono::Utc::now().naive_utc();
+			user = self.users.update(user).await?;
+		}
+
+		Ok(user)
+	}
+
+	async fn link(&self, identity: &ExternalIdentity, role: Option<&str>) -> Result<user::Model, ServiceError> {
+		let username = claim(identity, &self.config.username_claim)
+		.ok_or_else(|| ServiceError::Unauthorized(format!("The identity provider sent no {} claim.", self.config.username_claim)))?;
+
+		let existing = match verified_email(identity) {
+			Some(email) if self.config.link_existing_users => self.users.find_by_username(email).await?,
+			_ => None
+		};
+
+		let user = match existing {
+			Some(val) => val,
+			None if self.users.find_by_username(username).await?.is_some() => {
+				return Err(ServiceError::Forbidden("A local account already uses this username.".to_owned()));
+			},
+			None if self.config.auto_provision => self.provision(username, identity, role).await?,
+			None => return Err(ServiceError::Forbidden("No account is linked to this identity.".to_owned()))
+		};
+
+		self.repository.link_identity(user.id, &identity.issuer, &identity.subject).await?;
+
+		tracing::info!(user_id = user.id, issuer = %identity.issuer, "external identity linked");
+
+		Ok(user)
+	}
+
+	// A local account for the identity. Its password is random and never
+	// shown, so it can only log in through the provider until an admin sets one.
+	async fn provision(&self, username: &str, identity: &ExternalIdentity, role: Option<&str>) -> Result<user::Model, ServiceError> {
+		let now = chrono::Utc::now().naive_utc();
+
+		let full_name = identity.claims.get("name").and_then(Value::as_str).unwrap_or(username);
+
+		let data = user::Model {
+			id: 0,
+			username: username.to_owned(),
+			password: self.passwords.hash_new(&random_token())?,
+			full_name: full_name.to_owned(),
+			address: String::new(),
+			phone_number: String::new(),
+			role: role.unwrap_or(&self.config.default_role).to_owned(),
+			photo: String::new(),
+			created_at: now,
+			updated_at: now,
+			token_version: 0
+		};
+
+		Ok(self.users.insert(data).await?)
+	}
+
+	fn mapped_role(&self, identity: &ExternalIdentity) -> Option<String> {
+		let values: Vec<&str> = match identity.claims.get(&self.config.role_claim) {
+			Some(Value::String(val)) => vec![val.as_str()],
+			Some(Value::Array(list)) => list.iter().filter_map(Value::as_str).collect(),
+			_ => Vec::new()
+		};
+
+		self.config.role_mapping.iter().find(|d| values.contains(&d.value.as_str())).map(|d| d.role.clone())
+	}
+}
+
+fn claim<'a>(identity: &'a ExternalIdentity, name: &str) -> Option<&'a str> {
+	identity.claims.get(name).and_then(Value::as_str).map(str::trim).filter(|d| !d.is_empty())
+}
+
+// `email`, if the provider vouches that the user controls it. Unlike display
+// names, users can't just set it to someone else's address.
+fn verified_email(identity: &ExternalIdentity) -> Option<&str> {
+	match identity.claims.get("email_verified") {
+		Some(Value::Bool(true)) => claim(identity, "email"),
+		_ => None
+	}
+}
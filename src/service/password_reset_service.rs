@@ -0,0 +1,135 @@
+use chrono::NaiveDateTime;
+use rand::RngCore;
+use sha2::{ Digest, Sha256 };
+use std::sync::Arc;
+
+use crate::config::PasswordResetConfig;
+use crate::model::auth_model::ResetPasswordBody;
+use crate::model::user_model::UserData;
+use crate::repository::password_reset_repository::PasswordResetRepository;
+use crate::repository::user_repository::UserRepository;
+use crate::service::login_throttle_service::LoginThrottleService;
+use crate::service::password_service::PasswordService;
+use crate::service::session_service::SessionService;
+use crate::service::ServiceError;
+use crate::utils::password_reset_notifier::PasswordResetNotifier;
+
+#[derive(Clone)]
+pub struct PasswordResetService {
+	resets: Arc<dyn PasswordResetRepository>,
+	users: Arc<dyn UserRepository>,
+	passwords: PasswordService,
+	throttle: LoginThrottleService,
+	sessions: SessionService,
+	notifier: Option<Arc<dyn PasswordResetNotifier>>,
+	config: PasswordResetConfig
+}
+
+fn hash_token(token: &str) -> String {
+	hex::encode(Sha256::digest(token.trim().as_bytes()))
+}
+
+fn invalid_token() -> ServiceError {
+	ServiceError::BadRequest("INVALID OR EXPIRED RESET TOKEN.".to_owned())
+}
+
+impl PasswordResetService {
+	pub fn new(
+		resets: Arc<dyn PasswordResetRepository>,
+		users: Arc<dyn UserRepository>,
+		passwords: PasswordService,
+		throttle: LoginThrottleService,
+		sessions: SessionService,
+		config: PasswordResetConfig
+	) -> Self {
+		Self { resets, users, passwords, throttle, sessions, notifier: None, config }
+	}
+
+	pub fn with_notifier(mut self, notifier: Arc<dyn PasswordResetNotifier>) -> Self {
+		self.notifier = Some(notifier);
+		self
+	}
+
+	// A new token for the user. Earlier ones stay valid until they expire or
+	// one of them is used.
+	pub async fn issue(&self, user_id: i32) -> Result<(String, NaiveDateTime), ServiceError> {
+		if self.users.find_by_id(user_id).await?.is_none() {
+			return Err(ServiceError::NotFound("Data User tidak ditemukan".to_owned()));
+		}
+
+		let mut bytes = [0u8; 32];
+		rand::rngs::OsRng.fill_bytes(&mut bytes);
+
+		let token = hex::encode(bytes);
+		let expires_at = chrono::Utc::now().naive_utc() + chrono::Duration::seconds(self.config.token_lifetime_seconds as i64);
+
+		self.resets.insert(user_id, &hash_token(&token), expires_at).await?;
+
+		Ok((token, expires_at))
+	}
+
+	// Self-service request. Whether the username exists is never revealed: the
+	// lookup, the token and its delivery all happen in the background, so
+	// every request answers after the same work.
+	pub async fn request(&self, username: &str) -> Result<(), ServiceError> {
+		let Some(notifier) = self.notifier.clone() else {
+			tracing::warn!(username, "password reset requested but no notifier is configured");
+			return Ok(());
+		};
+
+		let resets = self.clone();
+		let username = username.to_owned();
+
+		tokio::spawn(async move {
+			if let Err(e) = resets.deliver(notifier.as_ref(), &username).await {
+				tracing::warn!(username, error = %e, "password reset notification failed");
+			}
+		});
+
+		Ok(())
+	}
+
+	async fn deliver(&self, notifier: &dyn PasswordResetNotifier, username: &str) -> Result<(), ServiceError> {
+		let Some(user) = self.users.find_by_username(username).await? else {
+			return Ok(());
+		};
+
+		let (token, expires_at) = self.issue(user.id).await?;
+
+		notifier.send(&UserData::from(user), &token, expires_at).await
+	}
+
+	// Sets the new password, burns every outstanding token of the user, revokes
+	// their sessions and lifts a login lockout on the username.
+	pub async fn reset(&self, body: ResetPasswordBody) -> Result<(), ServiceError> {
+		let now = chrono::Utc::now().naive_utc();
+
+		let token = self.resets.find_valid(&hash_token(&body.token), now).await?
+		.ok_or_else(invalid_token)?;
+
+		let mut user = self.users.find_by_id(token.user_id).await?
+		.ok_or_else(invalid_token)?;
+
+		// Checked before the token is used up, so a rejected password can be retried.
+		let new_hash = self.passwords.hash_replacement(&user, &body.new_password).await?;
+
+		if !self.resets.claim(token.id, now).await? {
+			return Err(invalid_token());
+		}
+
+		let old_hash = std::mem::replace(&mut user.password, new_hash);
+		user.updated_at = now;
+
+		let user = self.users.update(user).await?;
+
+		self.passwords.remember(user.id, &old_hash).await?;
+		self.users.revoke_sessions(user.id).await?;
+		self.sessions.revoke_all(user.id, None).await?;
+		self.resets.delete_for_user(user.id).await?;
+		self.throttle.unlock(Some(&user.username), None).await?;
+
+		tracing::info!(user_id = user.id, "password reset");
+
+		Ok(())
+	}
+}
@@ -0,0 +1,116 @@
+use std::collections::HashSet;
+use std::sync::{ Arc, OnceLock };
+
+use crate::config::PasswordConfig;
+use crate::repository::password_history_repository::PasswordHistoryRepository;
+use crate::service::ServiceError;
+use crate::utils::password::PasswordHasher;
+
+use entity::user;
+
+// Password hashing plus the policy every new password has to pass.
+#[derive(Clone)]
+pub struct PasswordService {
+	hasher: Arc<PasswordHasher>,
+	history: Arc<dyn PasswordHistoryRepository>,
+	config: PasswordConfig,
+	breached: Arc<HashSet<String>>,
+	dummy_hash: Arc<OnceLock<String>>
+}
+
+// One password per line; blank lines are skipped.
+pub fn load_breached_list(path: &str) -> HashSet<String> {
+	if path.is_empty() {
+		return HashSet::new();
+	}
+
+	let contents = std::fs::read_to_string(path)
+	.unwrap_or_else(|e| panic!("Failed to read breached password list {path}: {e}"));
+
+	contents.lines().map(|d| d.trim().to_lowercase()).filter(|d| !d.is_empty()).collect()
+}
+
+impl PasswordService {
+	pub fn new(history: Arc<dyn PasswordHistoryRepository>, config: PasswordConfig, breached: HashSet<String>) -> Self {
+		Self {
+			hasher: Arc::new(PasswordHasher::from_config(&config)),
+			history,
+			config,
+			breached: Arc::new(breached),
+			dummy_hash: Arc::new(OnceLock::new())
+		}
+	}
+
+	pub fn verify(&self, password: &str, stored: &str) -> bool {
+		self.hasher.verify(password, stored)
+	}
+
+	// Takes as long as `verify` against a real hash, so that unknown and
+	// known usernames can't be told apart by timing.
+	pub fn verify_dummy(&self, password: &str) {
+		let dummy = self.dummy_hash.get_or_init(|| self.hasher.hash("not-a-real-password").expect("hash of a constant"));
+
+		let _ = self.hasher.verify(password, dummy);
+	}
+
+	// For a password that has just been verified against `stored`.
+	pub fn rehash_if_outdated(&self, password: &str, stored: &str) -> Result<Option<String>, ServiceError> {
+		if !self.hasher.needs_rehash(stored) {
+			return Ok(None);
+		}
+
+		self.hasher.hash(password).map(Some)
+	}
+
+	fn check_policy(&self, password: &str) -> Result<(), ServiceError> {
+		let length = password.chars().count();
+
+		if length < self.config.min_length {
+			return Err(ServiceError::BadRequest(format!("Password must be at least {} characters long.", self.config.min_length)));
+		}
+
+		if length > self.config.max_length {
+			return Err(ServiceError::BadRequest(format!("Password must be at most {} characters long.", self.config.max_length)));
+		}
+
+		if self.breached.contains(&password.to_lowercase()) {
+			return Err(ServiceError::BadRequest("This password appears in known data breaches; choose another.".to_owned()));
+		}
+
+		Ok(())
+	}
+
+	// Hash of the password of a new user.
+	pub fn hash_new(&self, password: &str) -> Result<String, ServiceError> {
+		self.check_policy(password)?;
+
+		self.hasher.hash(password)
+	}
+
+	// Hash of a password replacing `user`'s current one. Store it, then call
+	// `remember` with the old hash.
+	pub async fn hash_replacement(&self, user: &user::Model, password: &str) -> Result<String, ServiceError> {
+		self.check_policy(password)?;
+
+		if self.config.history_size > 0 {
+			let previous = self.history.find_recent(user.id, self.config.history_size - 1).await?;
+
+			if std::iter::once(&user.password).chain(previous.iter()).any(|d| self.hasher.verify(password, d)) {
+				return Err(ServiceError::BadRequest(format!(
+					"Password must differ from your last {} password(s).",
+					self.config.history_size
+				)));
+			}
+		}
+
+		self.hasher.hash(password)
+	}
+
+	pub async fn remember(&self, user_id: i32, old_hash: &str) -> Result<(), ServiceError> {
+		if self.config.history_size > 1 {
+			self.history.push(user_id, old_hash, self.config.history_size - 1).await?;
+		}
+
+		Ok(())
+	}
+}
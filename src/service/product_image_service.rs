@@ -0,0 +1,216 @@
+use std::sync::Arc;
+
+use crate::model::product_image_model::{ ProductImageCreateBody, ProductImageData, ProductImageReorderBody };
+use crate::repository::product_image_repository::ProductImageRepository;
+use crate::repository::product_repository::ProductRepository;
+use crate::repository::stored_file_repository::StoredFileRepository;
+use crate::service::ServiceError;
+use crate::utils::file_store::file_url;
+
+use entity::product_image;
+
+pub fn image_data(image: product_image::Model) -> ProductImageData {
+	ProductImageData {
+		id: image.id,
+		product_id: image.product_id,
+		url: file_url("product", &image.file_name),
+		file_name: image.file_name,
+		alt_text: image.alt_text,
+		position: image.position,
+		is_primary: image.is_primary,
+		created_at: image.created_at,
+		updated_at: image.updated_at
+	}
+}
+
+#[derive(Clone)]
+pub struct ProductImageService {
+	products: Arc<dyn ProductRepository>,
+	images: Arc<dyn ProductImageRepository>,
+	files: Arc<dyn StoredFileRepository>
+}
+
+impl ProductImageService {
+	pub fn new(
+		products: Arc<dyn ProductRepository>,
+		images: Arc<dyn ProductImageRepository>,
+		files: Arc<dyn StoredFileRepository>
+	) -> Self {
+		Self { products, images, files }
+	}
+
+	pub async fn ensure_product(&self, product_id: i32) -> Result<(), ServiceError> {
+		match self.products.find_by_id(product_id).await? {
+			Some(_) => Ok(()),
+			None => Err(ServiceError::NotFound("Product Data Not Found!!!".to_owned()))
+		}
+	}
+
+	async fn find_image(&self, product_id: i32, image_id: i32) -> Result<product_image::Model, ServiceError> {
+		match self.images.find(product_id, image_id).await? {
+			Some(val) => Ok(val),
+			None => Err(ServiceError::NotFound("Product Image Not Found!!!".to_owned()))
+		}
+	}
+
+	pub async fn find_many(&self, product_id: i32) -> Result<Vec<ProductImageData>, ServiceError> {
+		self.ensure_product(product_id).await?;
+
+		Ok(self.images.find_by_product(product_id).await?.into_iter().map(image_data).collect())
+	}
+
+	pub async fn attach(
+		&self,
+		product_id: i32,
+		file_name: String,
+		alt_text: Option<String>,
+		is_primary: bool
+	) -> Result<ProductImageData, ServiceError> {
+		let image = self.images.attach(product_id, file_name, alt_text.unwrap_or_default(), is_primary).await?;
+
+		Ok(image_data(image))
+	}
+
+	pub async fn create(&self, product_id: i32, body: ProductImageCreateBody) -> Result<ProductImageData, ServiceError> {
+		self.ensure_product(product_id).await?;
+
+		if self.files.find_by_name("product", &body.file_name).await?.is_none() {
+			return Err(ServiceError::UnprocessableEntity("file_name is not an uploaded file of the product bucket.".to_owned()));
+		}
+
+		self.attach(product_id, body.file_name, body.alt_text, body.is_primary.unwrap_or(false)).await
+	}
+
+	pub async fn reorder(&self, product_id: i32, body: ProductImageReorderBody) -> Result<(), ServiceError> {
+		let mut current_ids: Vec<i32> = self.images.find_by_product(product_id).await?.iter().map(|d| d.id).collect();
+		let mut requested_ids = body.image_ids.clone();
+
+		current_ids.sort();
+		requested_ids.sort();
+
+		if current_ids != requested_ids {
+			return Err(ServiceError::BadRequest("image_ids must list every image of the product exactly once.".to_owned()));
+		}
+
+		Ok(self.images.reorder(product_id, body.image_ids).await?)
+	}
+
+	pub async fn set_primary(&self, product_id: i32, image_id: i32) -> Result<(), ServiceError> {
+		let image = self.find_image(product_id, image_id).await?;
+
+		Ok(self.images.set_primary(&image).await?)
+	}
+
+	// How many gallery images and products use the file of the product bucket.
+	pub async fn count_references(&self, file_name: &str) -> Result<u64, ServiceError> {
+		Ok(self.images.count_references(file_name).await?)
+	}
+
+	pub async fn delete(&self, product_id: i32, image_id: i32) -> Result<(), ServiceError> {
+		let image = self.find_image(product_id, image_id).await?;
+
+		Ok(self.images.detach(&image).await?)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::repository::in_memory::{
+		InMemoryProductImageRepository, InMemoryProductRepository, InMemoryStoredFileRepository
+	};
+
+	struct Fixture {
+		service: ProductImageService,
+		files: Arc<InMemoryStoredFileRepository>,
+		product_id: i32
+	}
+
+	async fn fixture() -> Fixture {
+		let products = Arc::new(InMemoryProductRepository::default());
+		let files = Arc::new(InMemoryStoredFileRepository::default());
+
+		let product = products.insert(entity::product::Model {
+			id: 0,
+			name: "Tea".to_owned(),
+			description: String::new(),
+			purchase_price: 1000,
+			selling_price: 1500,
+			stock: 10,
+			discount: 0,
+			image: String::new(),
+			category_id: 1,
+			created_at: Default::default(),
+			updated_at: Default::default()
+		}).await.unwrap();
+
+		Fixture {
+			service: ProductImageService::new(products, Arc::new(InMemoryProductImageRepository::default()), files.clone()),
+			files,
+			product_id: product.id
+		}
+	}
+
+	async fn upload(files: &InMemoryStoredFileRepository, namespace: &str, file_name: &str) {
+		files.insert(entity::stored_file::Model {
+			id: 0,
+			namespace: namespace.to_owned(),
+			hash: file_name.to_owned(),
+			file_name: file_name.to_owned(),
+			original_name: file_name.to_owned(),
+			content_type: "image/png".to_owned(),
+			size: 1,
+			is_private: false,
+			created_at: Default::default()
+		}).await.unwrap();
+	}
+
+	fn body(file_name: &str) -> ProductImageCreateBody {
+		ProductImageCreateBody { file_name: file_name.to_owned(), alt_text: None, is_primary: None }
+	}
+
+	fn reorder_body(image_ids: &[i32]) -> ProductImageReorderBody {
+		ProductImageReorderBody { image_ids: image_ids.to_vec() }
+	}
+
+	#[tokio::test]
+	async fn reorder_needs_every_image_exactly_once() {
+		let Fixture { service, files, product_id } = fixture().await;
+
+		upload(&files, "product", "a.png").await;
+		upload(&files, "product", "b.png").await;
+
+		let a = service.create(product_id, body("a.png")).await.unwrap().id;
+		let b = service.create(product_id, body("b.png")).await.unwrap().id;
+
+		for ids in [vec![a], vec![a, a], vec![a, b, 999], vec![]] {
+			let result = service.reorder(product_id, reorder_body(&ids)).await;
+
+			assert!(matches!(result, Err(ServiceError::BadRequest(_))), "{ids:?} was accepted");
+		}
+
+		service.reorder(product_id, reorder_body(&[b, a])).await.unwrap();
+
+		let order: Vec<i32> = service.find_many(product_id).await.unwrap().iter().map(|d| d.id).collect();
+
+		assert_eq!(order, vec![b, a]);
+	}
+
+	#[tokio::test]
+	async fn create_only_accepts_files_of_the_product_bucket() {
+		let Fixture { service, files, product_id } = fixture().await;
+
+		upload(&files, "user", "avatar.png").await;
+
+		assert!(matches!(service.create(product_id, body("missing.png")).await, Err(ServiceError::UnprocessableEntity(_))));
+		assert!(matches!(service.create(product_id, body("avatar.png")).await, Err(ServiceError::UnprocessableEntity(_))));
+		assert!(matches!(service.create(product_id + 1, body("avatar.png")).await, Err(ServiceError::NotFound(_))));
+
+		upload(&files, "product", "tea.png").await;
+
+		let image = service.create(product_id, body("tea.png")).await.unwrap();
+
+		assert!(image.is_primary);
+		assert_eq!(image.url, "/api/files/product/image/tea.png");
+	}
+}
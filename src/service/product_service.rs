@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::model::category_model::CategoryData;
+use crate::model::pagination_model::{ PaginationBody, PaginationResponse };
+use crate::model::product_model::{ ProductCreateDto, ProductPaginate, ProductUpdateDto, ProductWithCategoryData };
+use crate::repository::product_image_repository::ProductImageRepository;
+use crate::repository::product_repository::ProductRepository;
+use crate::service::ServiceError;
+use crate::utils::file_store::file_url;
+
+use entity::product;
+
+const PAGE_TAKE: i64 = 10;
+
+#[derive(Clone)]
+pub struct ProductService {
+	products: Arc<dyn ProductRepository>,
+	images: Arc<dyn ProductImageRepository>
+}
+
+impl ProductService {
+	pub fn new(products: Arc<dyn ProductRepository>, images: Arc<dyn ProductImageRepository>) -> Self {
+		Self { products, images }
+	}
+
+	pub async fn search_paginate(&self, body: PaginationBody) -> Result<ProductPaginate, ServiceError> {
+		let count = self.products.count_search(&body.term).await?;
+		let rows = self.products.search_with_category(&body.term).await?;
+
+		let product_ids: Vec<i32> = rows.iter().map(|d| d.0.id).collect();
+
+		// (image count, primary image file name) for every product on the page.
+		let mut gallery: HashMap<i32, (i64, Option<String>)> = HashMap::new();
+
+		for image in self.images.find_for_products(product_ids).await? {
+			let entry = gallery.entry(image.product_id).or_insert((0, None));
+
+			entry.0 += 1;
+
+			if image.is_primary {
+				entry.1 = Some(image.file_name);
+			}
+		}
+
+		let mut data = Vec::with_capacity(rows.len());
+
+		for (product, category) in rows {
+			let category = match category {
+				Some(val) => CategoryData::from(val),
+				None => return Err(ServiceError::Internal(format!("Product {} has no category.", product.id)))
+			};
+
+			let (image_count, primary_image) = gallery.remove(&product.id).unwrap_or((0, None));
+
+			let primary_image_url = match primary_image {
+				Some(file_name) => Some(file_url("product", &file_name)),
+				None if !product.image.is_empty() => Some(file_url("product", &product.image)),
+				None => None
+			};
+
+			data.push(ProductWithCategoryData {
+				id: product.id,
+				name: product.name,
+				description: product.description,
+				purchase_price: product.purchase_price,
+				selling_price: product.selling_price,
+				stock: product.stock,
+				discount: product.discount,
+				image: product.image,
+				category_id: product.category_id,
+				category,
+				primary_image_url,
+				image_count,
+				created_at: product.created_at,
+				updated_at: product.updated_at
+			});
+		}
+
+		Ok(ProductPaginate {
+			data,
+			paginate: PaginationResponse::new(count, body.page, PAGE_TAKE)
+		})
+	}
+
+	pub async fn create(&self, body: ProductCreateDto) -> Result<product::Model, ServiceError> {
+		let now = chrono::Utc::now().naive_utc();
+
+		let data = product::Model {
+			id: 0,
+			name: body.name,
+			description: body.description,
+			purchase_price: body.purchase_price,
+			selling_price: body.selling_price,
+			stock: body.stock,
+			discount: body.discount,
+			image: body.image,
+			category_id: body.category_id,
+			created_at: now,
+			updated_at: now
+		};
+
+		Ok(self.products.insert(data).await?)
+	}
+
+	pub async fn update(&self, id: i32, body: ProductUpdateDto) -> Result<product::Model, ServiceError> {
+		let mut product = match self.products.find_by_id(id).await? {
+			Some(val) => val,
+			None => return Err(ServiceError::NotFound("Data not Found!!!".to_owned()))
+		};
+
+		if let Some(val) = body.name { product.name = val; }
+		if let Some(val) = body.description { product.description = val; }
+		if let Some(val) = body.purchase_price { product.purchase_price = val; }
+		if let Some(val) = body.selling_price { product.selling_price = val; }
+		if let Some(val) = body.stock { product.stock = val; }
+		if let Some(val) = body.discount { product.discount = val; }
+		if let Some(val) = body.image { product.image = val; }
+		if let Some(val) = body.category_id { product.category_id = val; }
+
+		product.updated_at = chrono::Utc::now().naive_utc();
+
+		self.products.update(product).await
+		.map_err(|_| ServiceError::BadRequest("Failed to Update Product Data.".to_owned()))
+	}
+
+	pub async fn delete(&self, id: i32) -> Result<(), ServiceError> {
+		match self.products.delete_by_id(id).await? {
+			0 => Err(ServiceError::NotFound("Product Data Not Found!!!.".to_owned())),
+			_ => Ok(())
+		}
+	}
+}
@@ -0,0 +1,83 @@
+use chrono::NaiveDateTime;
+use std::sync::Arc;
+
+use crate::model::session_model::SessionData;
+use crate::repository::session_repository::SessionRepository;
+use crate::service::ServiceError;
+use crate::utils::client_ip::ClientInfo;
+use crate::utils::touch;
+
+use entity::user_session;
+
+// Server-side records of logins. A token is only good while the session it
+// names in `sid` exists and hasn't expired.
+#[derive(Clone)]
+pub struct SessionService {
+	repository: Arc<dyn SessionRepository>
+}
+
+impl SessionService {
+	pub fn new(repository: Arc<dyn SessionRepository>) -> Self {
+		Self { repository }
+	}
+
+	// Records a login and returns its session id. The user's expired
+	// sessions are cleared out on the way.
+	pub async fn start(&self, user_id: i32, client: &ClientInfo, expires_at: NaiveDateTime) -> Result<String, ServiceError> {
+		let now = chrono::Utc::now().naive_utc();
+		self.repository.delete_expired(user_id, now).await?;
+
+		let sid = uuid::Uuid::new_v4().to_string();
+		self.repository.insert(user_id, &sid, &client.user_agent, client.ip.map(|d| d.to_string()), expires_at).await?;
+
+		Ok(sid)
+	}
+
+	pub async fn find_valid(&self, sid: &str) -> Result<Option<user_session::Model>, ServiceError> {
+		let now = chrono::Utc::now().naive_utc();
+
+		let session = match self.repository.find_valid(sid, now).await? {
+			Some(val) => val,
+			None => return Ok(None)
+		};
+
+		if touch::is_due(Some(session.last_seen_at), now) {
+			self.repository.touch(session.id, now).await?;
+		}
+
+		Ok(Some(session))
+	}
+
+	// Keeps the session alive as long as a refreshed token.
+	pub async fn extend(&self, session: &user_session::Model, expires_at: NaiveDateTime) -> Result<(), ServiceError> {
+		if expires_at > session.expires_at {
+			self.repository.extend(session.id, expires_at).await?;
+		}
+
+		Ok(())
+	}
+
+	pub async fn list(&self, user_id: i32, current_sid: &str) -> Result<Vec<SessionData>, ServiceError> {
+		let now = chrono::Utc::now().naive_utc();
+
+		Ok(self.repository.find_active(user_id, now).await?.into_iter().map(|d| SessionData::from_model(d, current_sid)).collect())
+	}
+
+	pub async fn revoke(&self, user_id: i32, id: i32) -> Result<(), ServiceError> {
+		if !self.repository.delete(user_id, id).await? {
+			return Err(ServiceError::NotFound("Session not found.".to_owned()));
+		}
+
+		Ok(())
+	}
+
+	// Ends every session of the user, or all but `except_sid`. Returns how
+	// many were ended.
+	pub async fn revoke_all(&self, user_id: i32, except_sid: Option<&str>) -> Result<u64, ServiceError> {
+		let revoked = self.repository.delete_for_user(user_id, except_sid).await?;
+
+		tracing::info!(user_id, revoked, "sessions revoked");
+
+		Ok(revoked)
+	}
+}
@@ -0,0 +1,151 @@
+use rand::RngCore;
+use sha2::{ Digest, Sha256 };
+use std::sync::Arc;
+
+use crate::config::TwoFactorConfig;
+use crate::repository::two_factor_repository::TwoFactorRepository;
+use crate::service::ServiceError;
+use crate::utils::totp;
+
+// TOTP enrolment and verification, plus the recovery codes that stand in for
+// a lost authenticator.
+#[derive(Clone)]
+pub struct TwoFactorService {
+	repository: Arc<dyn TwoFactorRepository>,
+	config: TwoFactorConfig
+}
+
+// Recovery codes are compared case- and dash-insensitively.
+fn normalize_recovery_code(code: &str) -> String {
+	code.chars().filter(|d| d.is_ascii_alphanumeric()).map(|d| d.to_ascii_lowercase()).collect()
+}
+
+fn hash_recovery_code(code: &str) -> String {
+	hex::encode(Sha256::digest(normalize_recovery_code(code).as_bytes()))
+}
+
+// Ten base32 characters (50 bits), shown as `xxxxx-xxxxx`.
+fn generate_recovery_code() -> String {
+	let mut bytes = [0u8; 7];
+	rand::rngs::OsRng.fill_bytes(&mut bytes);
+
+	let code = totp::base32_encode(&bytes).to_lowercase();
+
+	format!("{}-{}", &code[..5], &code[5..10])
+}
+
+impl TwoFactorService {
+	pub fn new(repository: Arc<dyn TwoFactorRepository>, config: TwoFactorConfig) -> Self {
+		Self { repository, config }
+	}
+
+	pub fn required_for(&self, role: &str) -> bool {
+		self.config.required_roles.iter().any(|d| d == role)
+	}
+
+	pub fn challenge_lifetime_seconds(&self) -> u64 {
+		self.config.challenge_lifetime_seconds
+	}
+
+	// Challenges are single use; false when this one was redeemed before.
+	pub async fn use_challenge(&self, jti: &str, expires_at: usize) -> Result<bool, ServiceError> {
+		let expires_at = chrono::DateTime::from_timestamp(expires_at as i64, 0).unwrap_or_default().naive_utc();
+
+		Ok(self.repository.use_challenge(jti, expires_at, chrono::Utc::now().naive_utc()).await?)
+	}
+
+	pub async fn is_challenge_used(&self, jti: &str) -> Result<bool, ServiceError> {
+		Ok(self.repository.is_challenge_used(jti).await?)
+	}
+
+	pub async fn is_enabled(&self, user_id: i32) -> Result<bool, ServiceError> {
+		Ok(self.repository.find_totp(user_id).await?.is_some_and(|d| d.confirmed_at.is_some()))
+	}
+
+	// Starts (or restarts) enrolment. Returns the secret and its otpauth URI.
+	pub async fn enroll(&self, user_id: i32, username: &str) -> Result<(String, String), ServiceError> {
+		if self.is_enabled(user_id).await? {
+			return Err(ServiceError::BadRequest("Two-factor authentication is already on; disable it first.".to_owned()));
+		}
+
+		let secret = totp::generate_secret();
+		self.repository.replace_totp(user_id, &secret).await?;
+
+		let uri = totp::otpauth_uri(&self.config.issuer, username, &secret);
+
+		Ok((secret, uri))
+	}
+
+	// Turns 2FA on once the user proves their authenticator produces valid
+	// codes. Returns the first set of recovery codes.
+	pub async fn confirm(&self, user_id: i32, code: &str) -> Result<Vec<String>, ServiceError> {
+		let mut totp_model = match self.repository.find_totp(user_id).await? {
+			Some(val) if val.confirmed_at.is_none() => val,
+			Some(_) => return Err(ServiceError::BadRequest("Two-factor authentication is already on.".to_owned())),
+			None => return Err(ServiceError::BadRequest("Start the enrolment first.".to_owned()))
+		};
+
+		let now = chrono::Utc::now();
+		let step = totp::verify(&totp_model.secret, code, now.timestamp(), None)
+		.ok_or_else(|| ServiceError::BadRequest("INVALID TWO-FACTOR CODE".to_owned()))?;
+
+		totp_model.confirmed_at = Some(now.naive_utc());
+		totp_model.last_used_step = Some(step);
+		self.repository.update_totp(totp_model).await?;
+
+		self.new_recovery_codes(user_id).await
+	}
+
+	// Accepts a current TOTP code or an unused recovery code; either is
+	// consumed by a successful check.
+	pub async fn verify(&self, user_id: i32, code: &str) -> Result<bool, ServiceError> {
+		let totp_model = match self.repository.find_totp(user_id).await? {
+			Some(val) if val.confirmed_at.is_some() => val,
+			_ => return Ok(false)
+		};
+
+		let now = chrono::Utc::now();
+
+		if let Some(step) = totp::verify(&totp_model.secret, code, now.timestamp(), totp_model.last_used_step) {
+			return Ok(self.repository.claim_step(totp_model.id, step).await?);
+		}
+
+		let used = self.repository.use_recovery_code(user_id, &hash_recovery_code(code), now.naive_utc()).await?;
+
+		if used {
+			let left = self.repository.count_unused_recovery_codes(user_id).await?;
+			tracing::info!(user_id, recovery_codes_left = left, "recovery code used");
+		}
+
+		Ok(used)
+	}
+
+	pub async fn disable(&self, user_id: i32, role: &str, code: &str) -> Result<(), ServiceError> {
+		if self.required_for(role) {
+			return Err(ServiceError::Forbidden(format!("Two-factor authentication is required for the {role} role.")));
+		}
+
+		if !self.verify(user_id, code).await? {
+			return Err(ServiceError::BadRequest("INVALID TWO-FACTOR CODE".to_owned()));
+		}
+
+		Ok(self.repository.delete_all(user_id).await?)
+	}
+
+	// Replaces every recovery code, used or not.
+	pub async fn regenerate_recovery_codes(&self, user_id: i32, code: &str) -> Result<Vec<String>, ServiceError> {
+		if !self.verify(user_id, code).await? {
+			return Err(ServiceError::BadRequest("INVALID TWO-FACTOR CODE".to_owned()));
+		}
+
+		self.new_recovery_codes(user_id).await
+	}
+
+	async fn new_recovery_codes(&self, user_id: i32) -> Result<Vec<String>, ServiceError> {
+		let codes: Vec<String> = (0..self.config.recovery_codes).map(|_| generate_recovery_code()).collect();
+
+		self.repository.replace_recovery_codes(user_id, codes.iter().map(|d| hash_recovery_code(d)).collect()).await?;
+
+		Ok(codes)
+	}
+}
@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use crate::model::user_model::{ UserCreateBody, UserData, UserUpdateBody };
+use crate::repository::user_repository::UserRepository;
+use crate::service::password_service::PasswordService;
+use crate::service::session_service::SessionService;
+use crate::service::ServiceError;
+
+use entity::user;
+
+#[derive(Clone)]
+pub struct UserService {
+	users: Arc<dyn UserRepository>,
+	passwords: PasswordService,
+	sessions: SessionService
+}
+
+impl UserService {
+	pub fn new(users: Arc<dyn UserRepository>, passwords: PasswordService, sessions: SessionService) -> Self {
+		Self { users, passwords, sessions }
+	}
+
+	pub async fn find_many(&self) -> Result<Vec<UserData>, ServiceError> {
+		Ok(self.users.find_all().await?.into_iter().map(UserData::from).collect())
+	}
+
+	pub async fn find_first(&self, id: i32) -> Result<UserData, ServiceError> {
+		match self.users.find_by_id(id).await? {
+			Some(val) => Ok(val.into()),
+			None => Err(ServiceError::NotFound("Data not Found!!!".to_owned()))
+		}
+	}
+
+	pub async fn create(&self, body: UserCreateBody) -> Result<UserData, ServiceError> {
+		let hashed_password = self.passwords.hash_new(&body.password)?;
+
+		let now = chrono::Utc::now().naive_utc();
+
+		let data = user::Model {
+			id: 0,
+			username: body.username,
+			password: hashed_password,
+			full_name: body.full_name,
+			address: body.address,
+			phone_number: body.phone_number,
+			role: body.role,
+			photo: body.photo,
+			created_at: now,
+			updated_at: now,
+			token_version: 0
+		};
+
+		Ok(self.users.insert(data).await?.into())
+	}
+
+	// A role change ends the user's sessions, so nothing keeps acting under
+	// the role it was issued with.
+	pub async fn update(&self, id: i32, body: UserUpdateBody) -> Result<UserData, ServiceError> {
+		let mut user = match self.users.find_by_id(id).await? {
+			Some(val) => val,
+			None => return Err(ServiceError::NotFound("Data not Found!!!".to_owned()))
+		};
+
+		let previous_role = user.role.clone();
+
+		if let Some(val) = body.username { user.username = val; }
+		if let Some(val) = body.full_name { user.full_name = val; }
+		if let Some(val) = body.address { user.address = val; }
+		if let Some(val) = body.phone_number { user.phone_number = val; }
+		if let Some(val) = body.role { user.role = val; }
+		if let Some(val) = body.photo { user.photo = val; }
+
+		user.updated_at = chrono::Utc::now().naive_utc();
+
+		let user = self.users.update(user).await
+		.map_err(|_| ServiceError::BadRequest("Failed to Update User Data.".to_owned()))?;
+
+		if user.role != previous_role {
+			self.users.revoke_sessions(user.id).await?;
+			self.sessions.revoke_all(user.id, None).await?;
+		}
+
+		Ok(user.into())
+	}
+
+	pub async fn delete(&self, id: i32) -> Result<(), ServiceError> {
+		match self.users.delete_by_id(id).await? {
+			0 => Err(ServiceError::NotFound("Data Not Found!!!.".to_owned())),
+			_ => Ok(())
+		}
+	}
+}
@@ -0,0 +1,182 @@
+use axum::extract::FromRef;
+use sea_orm::DatabaseConnection;
+use std::sync::Arc;
+
+use crate::config::AppConfig;
+use crate::repository::{
+	api_key_repository::SeaOrmApiKeyRepository,
+	category_repository::SeaOrmCategoryRepository,
+	login_attempt_repository::SeaOrmLoginAttemptRepository,
+	oidc_repository::SeaOrmOidcRepository,
+	password_history_repository::SeaOrmPasswordHistoryRepository,
+	password_reset_repository::SeaOrmPasswordResetRepository,
+	product_image_repository::SeaOrmProductImageRepository,
+	product_repository::SeaOrmProductRepository,
+	session_repository::SeaOrmSessionRepository,
+	stored_file_repository::SeaOrmStoredFileRepository,
+	two_factor_repository::SeaOrmTwoFactorRepository,
+	user_repository::SeaOrmUserRepository
+};
+use crate::service::{
+	api_key_service::ApiKeyService,
+	auth_service::AuthService,
+	category_service::CategoryService,
+	file_service::FileService,
+	health_service::HealthService,
+	login_throttle_service::LoginThrottleService,
+	metrics_service::MetricsService,
+	oidc_service::OidcService,
+	password_reset_service::PasswordResetService,
+	password_service::{ load_breached_list, PasswordService },
+	product_image_service::ProductImageService,
+	product_service::ProductService,
+	session_service::SessionService,
+	two_factor_service::TwoFactorService,
+	user_service::UserService
+};
+use crate::utils::file_store::FileStorage;
+use crate::utils::jwt::JwtKeys;
+use crate::utils::oidc::{ IdentityProvider, OidcProvider };
+use crate::utils::password_reset_notifier::PasswordResetNotifier;
+use crate::utils::rate_limit::{ InMemoryRateLimitStore, RateLimitStore, RateLimits };
+use crate::utils::request_metrics;
+
+// Handlers extract only the part they need, e.g. `State<CategoryService>`
+// or `State<Arc<JwtKeys>>`; new shared services become another field here.
+#[derive(Clone, FromRef)]
+pub struct AppState {
+	pub db: DatabaseConnection,
+	pub config: Arc<AppConfig>,
+	pub jwt: Arc<JwtKeys>,
+	pub categories: CategoryService,
+	pub products: ProductService,
+	pub product_images: ProductImageService,
+	pub users: UserService,
+	pub auth: AuthService,
+	pub two_factor: TwoFactorService,
+	pub password_resets: PasswordResetService,
+	pub sessions: SessionService,
+	pub api_keys: ApiKeyService,
+	pub oidc: OidcService,
+	pub files: FileService,
+	pub metrics: MetricsService,
+	pub health: HealthService,
+	pub rate_limits: RateLimits
+}
+
+impl AppState {
+	pub fn new(db: DatabaseConnection, config: AppConfig) -> Self {
+		let jwt = Arc::new(
+			JwtKeys::from_config(&config.jwt).unwrap_or_else(|e| panic!("Failed to load the JWT keys: {e}"))
+		);
+
+		let category_repository = Arc::new(SeaOrmCategoryRepository::new(db.clone()));
+		let product_repository = Arc::new(SeaOrmProductRepository::new(db.clone()));
+		let product_image_repository = Arc::new(SeaOrmProductImageRepository::new(db.clone()));
+		let stored_file_repository = Arc::new(SeaOrmStoredFileRepository::new(db.clone()));
+		let user_repository = Arc::new(SeaOrmUserRepository::new(db.clone()));
+
+		let login_throttle = LoginThrottleService::new(
+			Arc::new(SeaOrmLoginAttemptRepository::new(db.clone())),
+			config.login_throttle.clone()
+		);
+
+		let passwords = PasswordService::new(
+			Arc::new(SeaOrmPasswordHistoryRepository::new(db.clone())),
+			config.password.clone(),
+			load_breached_list(&config.password.breached_list_path)
+		);
+
+		let two_factor = TwoFactorService::new(
+			Arc::new(SeaOrmTwoFactorRepository::new(db.clone())),
+			config.two_factor.clone()
+		);
+
+		let sessions = SessionService::new(Arc::new(SeaOrmSessionRepository::new(db.clone())));
+
+		let password_resets = PasswordResetService::new(
+			Arc::new(SeaOrmPasswordResetRepository::new(db.clone())),
+			user_repository.clone(),
+			passwords.clone(),
+			login_throttle.clone(),
+			sessions.clone(),
+			config.password_reset.clone()
+		);
+
+		let product_images = ProductImageService::new(
+			product_repository.clone(),
+			product_image_repository.clone(),
+			stored_file_repository.clone()
+		);
+
+		let metrics = MetricsService::new(
+			request_metrics::install_recorder(),
+			db.clone(),
+			product_repository.clone(),
+			config.database.max_connections,
+			config.metrics.clone()
+		);
+
+		let auth = AuthService::new(user_repository.clone(), jwt.clone(), login_throttle, two_factor.clone(), passwords.clone(), sessions.clone());
+
+		let mut oidc = OidcService::new(
+			Arc::new(SeaOrmOidcRepository::new(db.clone())),
+			user_repository.clone(),
+			passwords.clone(),
+			auth.clone(),
+			config.oidc.clone()
+		);
+
+		if config.oidc.enabled {
+			oidc = oidc.with_provider(Arc::new(OidcProvider::new(config.oidc.clone())));
+		}
+
+		Self {
+			categories: CategoryService::new(category_repository),
+			products: ProductService::new(product_repository, product_image_repository),
+			users: UserService::new(user_repository.clone(), passwords, sessions.clone()),
+			auth,
+			two_factor,
+			password_resets,
+			sessions,
+			oidc,
+			api_keys: ApiKeyService::new(Arc::new(SeaOrmApiKeyRepository::new(db.clone()))),
+			files: FileService::new(
+				Arc::new(FileStorage::from_config(&config.uploads)),
+				stored_file_repository,
+				product_images.clone(),
+				user_repository
+			),
+			product_images,
+			metrics,
+			health: HealthService::new(db.clone()),
+			rate_limits: RateLimits::new(
+				Arc::new(InMemoryRateLimitStore::default()),
+				config.rate_limit.clone(),
+				config.server.forwarded_hops()
+			),
+			jwt,
+			config: Arc::new(config),
+			db
+		}
+	}
+
+	// Lets users request their own reset tokens, delivered by `notifier`.
+	pub fn with_password_reset_notifier(mut self, notifier: Arc<dyn PasswordResetNotifier>) -> Self {
+		self.password_resets = self.password_resets.with_notifier(notifier);
+		self
+	}
+
+	// Logs users in through `provider` instead of the OIDC provider of
+	// `oidc.enabled`, e.g. one speaking another protocol.
+	pub fn with_identity_provider(mut self, provider: Arc<dyn IdentityProvider>) -> Self {
+		self.oidc = self.oidc.with_provider(provider);
+		self
+	}
+
+	// Replaces the per-process bucket store, e.g. with one shared by all replicas.
+	pub fn with_rate_limit_store(mut self, store: Arc<dyn RateLimitStore>) -> Self {
+		self.rate_limits = RateLimits::new(store, self.config.rate_limit.clone(), self.config.server.forwarded_hops());
+		self
+	}
+}
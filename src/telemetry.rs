@@ -0,0 +1,106 @@
+use axum::{
+	body::{ to_bytes, Body, HttpBody },
+	extract::{ MatchedPath, Request },
+	http::{ header, Response },
+	middleware::Next
+};
+use serde_json::Value;
+use std::time::Duration;
+use tracing::{ field::Empty, Span };
+use tracing_subscriber::EnvFilter;
+
+use crate::config::{ LogFormat, LoggingConfig };
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+// Error bodies larger than this are passed through without a `request_id`.
+const MAX_ERROR_BODY_BYTES: usize = 64 * 1024;
+
+// Installs the global subscriber. `log` records, such as SeaORM's and sqlx's
+// statement logs, are forwarded into `tracing`.
+pub fn init(config: &LoggingConfig) {
+	let filter = EnvFilter::try_new(&config.filter).unwrap_or_else(|_| EnvFilter::new("info"));
+	let subscriber = tracing_subscriber::fmt().with_env_filter(filter);
+
+	let result = match config.format {
+		LogFormat::Json => subscriber.json().with_current_span(true).with_span_list(false).try_init(),
+		LogFormat::Pretty => subscriber.try_init()
+	};
+
+	if let Err(e) = result {
+		eprintln!("Logging was already initialised: {e}");
+	}
+}
+
+// One span per request. `user_id` is filled in by `auth_guard`, `status` and
+// `latency_ms` when the response is ready.
+pub fn make_span(req: &Request<Body>) -> Span {
+	let route = req.extensions().get::<MatchedPath>().map(|d| d.as_str()).unwrap_or("");
+	let request_id = req.headers().get(REQUEST_ID_HEADER).and_then(|d| d.to_str().ok()).unwrap_or("");
+
+	tracing::info_span!(
+		"request",
+		method = %req.method(),
+		route,
+		path = %req.uri().path(),
+		request_id,
+		user_id = Empty,
+		status = Empty,
+		latency_ms = Empty
+	)
+}
+
+pub fn on_response(response: &Response<Body>, latency: Duration, span: &Span) {
+	let status = response.status().as_u16();
+	let latency_ms = latency.as_millis() as u64;
+
+	span.record("status", status);
+	span.record("latency_ms", latency_ms);
+
+	if response.status().is_server_error() {
+		tracing::error!(status, latency_ms, "request failed");
+	} else {
+		tracing::info!(status, latency_ms, "request finished");
+	}
+}
+
+pub fn record_user_id(user_id: i32) {
+	Span::current().record("user_id", user_id);
+}
+
+// Adds the request id to JSON error bodies so a client report can be matched to the logs.
+pub async fn request_id_in_errors(req: Request, next: Next) -> Response<Body> {
+	let request_id = req.headers().get(REQUEST_ID_HEADER).cloned();
+	let response = next.run(req).await;
+
+	let request_id = match request_id.as_ref().and_then(|d| d.to_str().ok()) {
+		Some(val) if response.status().is_client_error() || response.status().is_server_error() => val.to_owned(),
+		_ => return response
+	};
+
+	// Only small bodies of known size are buffered; streams pass through untouched.
+	let body_size = response.body().size_hint().exact();
+
+	if body_size.is_none_or(|d| d > MAX_ERROR_BODY_BYTES as u64) {
+		return response;
+	}
+
+	let (mut parts, body) = response.into_parts();
+
+	let bytes = match to_bytes(body, MAX_ERROR_BODY_BYTES).await {
+		Ok(val) => val,
+		Err(_) => return Response::from_parts(parts, Body::empty())
+	};
+
+	let body = match serde_json::from_slice::<Value>(&bytes) {
+		Ok(Value::Object(mut object)) => {
+			object.insert("request_id".to_owned(), Value::String(request_id));
+			parts.headers.remove(header::CONTENT_LENGTH);
+
+			Body::from(Value::Object(object).to_string())
+		},
+		_ => Body::from(bytes)
+	};
+
+	Response::from_parts(parts, body)
+}
@@ -0,0 +1,47 @@
+use axum::http::Method;
+
+// Every scope an API key can be granted.
+pub const SCOPES: &[&str] = &[
+	"category:read",
+	"category:write",
+	"product:read",
+	"product:write",
+	"file:write"
+];
+
+// The scope an API key needs for a route group, added to its requests as an
+// extension outside `auth_guard`. Groups without one don't accept API keys.
+#[derive(Clone, Copy, Debug)]
+pub struct ApiScope {
+	resource: &'static str,
+	// Set when the access doesn't follow the method.
+	access: Option<&'static str>
+}
+
+impl ApiScope {
+	// `<resource>:read` for GET and HEAD, `<resource>:write` for the rest.
+	pub const fn of(resource: &'static str) -> Self {
+		Self { resource, access: None }
+	}
+
+	// `<resource>:read` whatever the method, for searches sent as POST.
+	pub const fn read_only(resource: &'static str) -> Self {
+		Self { resource, access: Some("read") }
+	}
+
+	// `<resource>:write` whatever the method, for groups with nothing to read.
+	pub const fn write_only(resource: &'static str) -> Self {
+		Self { resource, access: Some("write") }
+	}
+
+	// Whether every scope this group can require is one keys may be granted.
+	pub fn is_known(&self) -> bool {
+		[Method::GET, Method::POST].iter().all(|method| SCOPES.contains(&self.required(method).as_str()))
+	}
+
+	pub fn required(&self, method: &Method) -> String {
+		let access = self.access.unwrap_or(if method == Method::GET || method == Method::HEAD { "read" } else { "write" });
+
+		format!("{}:{access}", self.resource)
+	}
+}
@@ -0,0 +1,73 @@
+use axum::{
+	extract::{ ConnectInfo, FromRef, FromRequestParts },
+	http::{ header::USER_AGENT, request::Parts, Extensions, HeaderMap }
+};
+use std::{ convert::Infallible, net::{ IpAddr, SocketAddr }, sync::Arc };
+
+use crate::config::AppConfig;
+
+// Address of the client, from the `X-Forwarded-For` entry `forwarded_hops`
+// from the right (the one the outermost trusted proxy appended), else from
+// the connection. `None` when neither is available, e.g. for requests driven
+// in-process.
+pub fn client_ip(headers: &HeaderMap, extensions: &Extensions, forwarded_hops: usize) -> Option<IpAddr> {
+	if forwarded_hops > 0 {
+		let entries: Vec<&str> = headers.get_all("x-forwarded-for").iter()
+		.filter_map(|d| d.to_str().ok())
+		.flat_map(|d| d.split(','))
+		.collect();
+
+		let forwarded = entries.iter().rev().nth(forwarded_hops - 1)
+		.and_then(|d| d.trim().parse::<IpAddr>().ok());
+
+		if forwarded.is_some() {
+			return forwarded;
+		}
+	}
+
+	extensions.get::<ConnectInfo<SocketAddr>>().map(|d| d.0.ip())
+}
+
+pub struct ClientIp(pub Option<IpAddr>);
+
+impl<S> FromRequestParts<S> for ClientIp
+where
+	Arc<AppConfig>: FromRef<S>,
+	S: Send + Sync
+{
+	type Rejection = Infallible;
+
+	async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+		let config = Arc::<AppConfig>::from_ref(state);
+
+		Ok(ClientIp(client_ip(&parts.headers, &parts.extensions, config.server.forwarded_hops())))
+	}
+}
+
+// Where a login comes from, as recorded on its session.
+pub struct ClientInfo {
+	pub ip: Option<IpAddr>,
+	pub user_agent: String
+}
+
+// Longer values are cut to fit the session's column.
+const MAX_USER_AGENT_CHARS: usize = 255;
+
+impl<S> FromRequestParts<S> for ClientInfo
+where
+	Arc<AppConfig>: FromRef<S>,
+	S: Send + Sync
+{
+	type Rejection = Infallible;
+
+	async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+		let ClientIp(ip) = ClientIp::from_request_parts(parts, state).await?;
+
+		let user_agent = parts.headers.get(USER_AGENT)
+		.and_then(|d| d.to_str().ok())
+		.map(|d| d.chars().take(MAX_USER_AGENT_CHARS).collect())
+		.unwrap_or_default();
+
+		Ok(ClientInfo { ip, user_agent })
+	}
+}
@@ -0,0 +1,87 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::config::UploadConfig;
+
+const DEFAULT_MAX_FILE_SIZE: u64 = 5 * 1024 * 1024;
+
+fn default_max_file_size() -> u64 {
+	DEFAULT_MAX_FILE_SIZE
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct FileBucket {
+	pub name: String,
+	pub directory: Option<String>,
+	#[serde(default)]
+	pub allowed_extensions: Vec<String>,
+	#[serde(default = "default_max_file_size")]
+	pub max_file_size: u64,
+	pub default_file: Option<String>
+}
+
+impl FileBucket {
+	// Resolved by `FileBuckets::new`, so always set for registered buckets.
+	pub fn directory(&self) -> String {
+		self.directory.clone().unwrap_or(format!("uploads/{}", self.name))
+	}
+
+	// An empty list accepts every extension.
+	pub fn allows_extension(&self, ext: &str) -> bool {
+		self.allowed_extensions.is_empty() || self.allowed_extensions.iter().any(|d| d.eq_ignore_ascii_case(ext))
+	}
+
+	pub fn is_default_file(&self, file_name: &str) -> bool {
+		self.default_file.as_deref() == Some(file_name)
+	}
+}
+
+// Buckets used when the config doesn't register any.
+pub fn default_buckets() -> Vec<FileBucket> {
+	let image_bucket = |name: &str, default_file: &str| FileBucket {
+		name: name.to_owned(),
+		directory: None,
+		allowed_extensions: ["png", "jpg", "jpeg", "jfif", "gif", "webp"].iter().map(|d| d.to_string()).collect(),
+		max_file_size: DEFAULT_MAX_FILE_SIZE,
+		default_file: Some(default_file.to_owned())
+	};
+
+	vec![
+		image_bucket("user", "default_user.png"),
+		image_bucket("product", "default_product.png")
+	]
+}
+
+pub struct FileBuckets {
+	buckets: HashMap<String, FileBucket>
+}
+
+impl FileBuckets {
+	pub fn new(root: &str, buckets: Vec<FileBucket>) -> Self {
+		Self {
+			buckets: buckets.into_iter().map(|mut d| {
+				if d.directory.is_none() {
+					d.directory = Some(format!("{root}/{}", d.name));
+				}
+
+				(d.name.clone(), d)
+			}).collect()
+		}
+	}
+
+	pub fn from_config(config: &UploadConfig) -> Self {
+		Self::new(&config.root, config.buckets.clone())
+	}
+
+	pub fn get(&self, name: &str) -> Option<&FileBucket> {
+		self.buckets.get(name)
+	}
+
+	// A directory more than one bucket stores into. Their objects would be
+	// served through either bucket while visibility is tracked per bucket.
+	pub fn shared_directory(&self) -> Option<String> {
+		let mut seen = std::collections::HashSet::new();
+
+		self.buckets.values().map(FileBucket::directory).find(|d| !seen.insert(d.clone()))
+	}
+}
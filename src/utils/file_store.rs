@@ -0,0 +1,54 @@
+use crate::config::UploadConfig;
+use crate::utils::file_buckets::FileBuckets;
+
+pub fn file_url(namespace: &str, file_name: &str) -> String {
+	format!("/api/files/{namespace}/image/{file_name}")
+}
+
+// Rejects names that could escape the bucket directory.
+pub fn is_safe_file_name(file_name: &str) -> bool {
+	!file_name.is_empty()
+	&& !file_name.starts_with('.')
+	&& !file_name.contains(['/', '\\'])
+}
+
+pub fn file_extension(file_name: &str) -> String {
+	match file_name.rsplit_once(".") {
+		Some((_, ext)) => ext.to_lowercase(),
+		None => String::new()
+	}
+}
+
+pub fn content_type_for(ext: &str) -> &'static str {
+	match ext {
+		"png" => "image/png",
+		"jpg" | "jpeg" | "jfif" => "image/jpeg",
+		"gif" => "image/gif",
+		"webp" => "image/webp",
+		"svg" => "image/svg+xml",
+		"bmp" => "image/bmp",
+		"pdf" => "application/pdf",
+		_ => "application/octet-stream"
+	}
+}
+
+// Raster images are shown in place; anything else (SVG can carry scripts) is
+// only offered as a download.
+pub fn served_inline(content_type: &str) -> bool {
+	content_type.starts_with("image/") && content_type != "image/svg+xml"
+}
+
+// Bucket registry plus the upload settings needed to sign and verify URLs.
+pub struct FileStorage {
+	pub buckets: FileBuckets,
+	pub config: UploadConfig
+}
+
+impl FileStorage {
+	pub fn from_config(config: &UploadConfig) -> Self {
+		Self {
+			buckets: FileBuckets::from_config(config),
+			config: config.clone()
+		}
+	}
+}
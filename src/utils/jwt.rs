@@ -0,0 +1,370 @@
+use base64::{ engine::general_purpose::URL_SAFE_NO_PAD, Engine };
+use jsonwebtoken::{
+	decode, decode_header, encode,
+	errors::ErrorKind,
+	jwk::{
+		AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm, OctetKeyPairParameters,
+		OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType
+	},
+	Algorithm, DecodingKey, EncodingKey, Header, Validation
+};
+use ring::signature::{ Ed25519KeyPair, KeyPair };
+use rsa::{
+	pkcs1::{ DecodeRsaPrivateKey, EncodeRsaPrivateKey },
+	pkcs8::{ DecodePrivateKey, EncodePrivateKey },
+	traits::PublicKeyParts,
+	RsaPrivateKey
+};
+use serde::{ de::DeserializeOwned, Serialize };
+use std::{ fmt, fs, path::{ Path, PathBuf } };
+
+use crate::config::{ JwtAlgorithm, JwtConfig };
+
+const RSA_KEY_BITS: usize = 2048;
+
+#[derive(Debug)]
+pub enum JwtKeyError {
+	Io(String, std::io::Error),
+	InvalidKey(String, String),
+	Unsupported(String)
+}
+
+impl fmt::Display for JwtKeyError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			JwtKeyError::Io(path, e) => write!(f, "{path}: {e}"),
+			JwtKeyError::InvalidKey(path, e) => write!(f, "{path} is not a usable key: {e}"),
+			JwtKeyError::Unsupported(e) => write!(f, "{e}")
+		}
+	}
+}
+
+impl std::error::Error for JwtKeyError {}
+
+struct SigningKey {
+	kid: Option<String>,
+	algorithm: Algorithm,
+	key: EncodingKey
+}
+
+struct VerificationKey {
+	kid: Option<String>,
+	algorithm: Algorithm,
+	key: DecodingKey
+}
+
+// The HS256 secret of before a switch to an asymmetric algorithm.
+struct LegacyKey {
+	key: DecodingKey,
+	until: chrono::DateTime<chrono::Utc>
+}
+
+// A private key read from `keys_dir`, with its public half as a JWK.
+struct StoredKey {
+	kid: String,
+	algorithm: Algorithm,
+	encoding: EncodingKey,
+	jwk: Jwk
+}
+
+// Key material derived once from `JwtConfig` instead of on every request.
+pub struct JwtKeys {
+	signing: SigningKey,
+	verification: Vec<VerificationKey>,
+	legacy: Option<LegacyKey>,
+	jwks: JwkSet,
+	pub issuer: String,
+	pub audience: String,
+	pub token_lifetime_seconds: u64,
+	pub refresh_lifetime_seconds: u64
+}
+
+impl JwtKeys {
+	// With RS256 or EdDSA and no key of that type in `keys_dir`, one is
+	// generated first.
+	pub fn from_config(config: &JwtConfig) -> Result<Self, JwtKeyError> {
+		let signing = match algorithm_of(config.algorithm) {
+			Algorithm::HS256 => {
+				return Ok(Self {
+					signing: SigningKey { kid: None, algorithm: Algorithm::HS256, key: EncodingKey::from_secret(config.secret.as_ref()) },
+					verification: vec![VerificationKey {
+						kid: None,
+						algorithm: Algorithm::HS256,
+						key: DecodingKey::from_secret(config.secret.as_ref())
+					}],
+					legacy: None,
+					jwks: JwkSet { keys: Vec::new() },
+					issuer: config.issuer.clone(),
+					audience: config.audience.clone(),
+					token_lifetime_seconds: config.token_lifetime_seconds,
+					refresh_lifetime_seconds: config.refresh_lifetime_seconds
+				});
+			},
+			algorithm => algorithm
+		};
+
+		let mut stored = load_keys(Path::new(&config.keys_dir))?;
+
+		// Replicas booting together take turns, so only the first generates
+		// a key and the others load it.
+		if !stored.iter().any(|d| d.algorithm == signing) {
+			let _lock = lock_keys_dir(config)?;
+			stored = load_keys(Path::new(&config.keys_dir))?;
+
+			if !stored.iter().any(|d| d.algorithm == signing) {
+				let kid = add_key(config)?.kid;
+				tracing::warn!(%kid, keys_dir = %config.keys_dir, "no JWT signing key found, generated one");
+
+				stored = load_keys(Path::new(&config.keys_dir))?;
+			}
+		}
+
+		let mut verification = Vec::new();
+		let mut jwks = JwkSet { keys: Vec::new() };
+
+		for key in &stored {
+			let decoding = DecodingKey::from_jwk(&key.jwk)
+			.map_err(|e| JwtKeyError::InvalidKey(key.kid.clone(), e.to_string()))?;
+
+			verification.push(VerificationKey { kid: Some(key.kid.clone()), algorithm: key.algorithm, key: decoding });
+			jwks.keys.push(key.jwk.clone());
+		}
+
+		// Keys sort oldest first, so the newest of the configured type signs.
+		let newest = stored.into_iter().rev().find(|d| d.algorithm == signing)
+		.ok_or_else(|| JwtKeyError::Unsupported(format!("no {signing:?} key in {}", config.keys_dir)))?;
+
+		Ok(Self {
+			signing: SigningKey { kid: Some(newest.kid), algorithm: newest.algorithm, key: newest.encoding },
+			verification,
+			legacy: config.accept_hs256_until.filter(|_| !config.secret.is_empty()).map(|until| LegacyKey {
+				key: DecodingKey::from_secret(config.secret.as_ref()),
+				until
+			}),
+			jwks,
+			issuer: config.issuer.clone(),
+			audience: config.audience.clone(),
+			token_lifetime_seconds: config.token_lifetime_seconds,
+			refresh_lifetime_seconds: config.refresh_lifetime_seconds
+		})
+	}
+
+	pub fn encode<T: Serialize>(&self, claims: &T) -> jsonwebtoken::errors::Result<String> {
+		let mut header = Header::new(self.signing.algorithm);
+		header.kid = self.signing.kid.clone();
+
+		encode(&header, claims, &self.signing.key)
+	}
+
+	// Verifies with the key named by the token's `kid`; tokens without one
+	// can only be HS256 ones checked against the secret, and after a switch
+	// only access tokens until `accept_hs256_until`. `iss` must be ours and
+	// `aud` the given audience, so one kind of token can't pass for another.
+	pub fn decode<T: DeserializeOwned>(&self, token: &str, audience: &str) -> jsonwebtoken::errors::Result<T> {
+		let header = decode_header(token)?;
+
+		let (algorithm, key) = match self.verification.iter().find(|d| d.kid == header.kid) {
+			Some(val) => (val.algorithm, &val.key),
+			None => match &self.legacy {
+				Some(val) if header.kid.is_none() && audience == self.audience && chrono::Utc::now() < val.until => {
+					(Algorithm::HS256, &val.key)
+				},
+				_ => return Err(ErrorKind::InvalidToken.into())
+			}
+		};
+
+		let mut validation = Validation::new(algorithm);
+		validation.set_issuer(&[&self.issuer]);
+		validation.set_audience(&[audience]);
+		validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+		decode::<T>(token, key, &validation).map(|d| d.claims)
+	}
+
+	// The public half of every verification key, for `/.well-known/jwks.json`.
+	pub fn jwks(&self) -> &JwkSet {
+		&self.jwks
+	}
+}
+
+pub struct RotatedKey {
+	pub kid: String,
+	pub retired: Vec<String>
+}
+
+// Writes a new private key for `config.algorithm` to `keys_dir`, which then
+// signs from the next boot on, and removes all but the newest `max_keys`.
+pub fn rotate(config: &JwtConfig) -> Result<RotatedKey, JwtKeyError> {
+	let _lock = lock_keys_dir(config)?;
+
+	add_key(config)
+}
+
+// Held while keys are added, against other processes sharing `keys_dir`;
+// released when dropped.
+fn lock_keys_dir(config: &JwtConfig) -> Result<fs::File, JwtKeyError> {
+	let path = Path::new(&config.keys_dir).join(".lock");
+
+	fs::create_dir_all(&config.keys_dir).map_err(|e| JwtKeyError::Io(config.keys_dir.clone(), e))?;
+
+	let file = fs::OpenOptions::new().create(true).truncate(false).write(true).open(&path)
+	.map_err(|e| JwtKeyError::Io(path.display().to_string(), e))?;
+
+	file.lock().map_err(|e| JwtKeyError::Io(path.display().to_string(), e))?;
+
+	Ok(file)
+}
+
+fn add_key(config: &JwtConfig) -> Result<RotatedKey, JwtKeyError> {
+	let dir = Path::new(&config.keys_dir);
+
+	let der = match config.algorithm {
+		JwtAlgorithm::EdDSA => Ed25519KeyPair::generate_pkcs8(&ring::rand::SystemRandom::new())
+		.map_err(|_| JwtKeyError::Unsupported("failed to generate an Ed25519 key".to_owned()))?
+		.as_ref()
+		.to_vec(),
+		JwtAlgorithm::RS256 => RsaPrivateKey::new(&mut rand::rngs::OsRng, RSA_KEY_BITS)
+		.and_then(|d| d.to_pkcs8_der().map_err(rsa::Error::from))
+		.map_err(|e| JwtKeyError::Unsupported(format!("failed to generate an RSA key: {e}")))?
+		.as_bytes()
+		.to_vec(),
+		JwtAlgorithm::HS256 => {
+			return Err(JwtKeyError::Unsupported("HS256 uses JWT_SECRET; there is no key to rotate.".to_owned()));
+		}
+	};
+
+	fs::create_dir_all(dir).map_err(|e| JwtKeyError::Io(config.keys_dir.clone(), e))?;
+
+	// Timestamped so that file names sort oldest first.
+	let kid = chrono::Utc::now().format("%Y%m%d%H%M%S%6f").to_string();
+	let path = dir.join(format!("{kid}.pem"));
+	let pem = pem::encode_config(&pem::Pem::new("PRIVATE KEY", der), pem::EncodeConfig::new().set_line_ending(pem::LineEnding::LF));
+
+	// Renamed into place, so that others loading `keys_dir` never read half a key.
+	let partial = dir.join(format!("{kid}.partial"));
+
+	write_private(&partial, pem.as_bytes())
+	.and_then(|_| fs::rename(&partial, &path))
+	.map_err(|e| JwtKeyError::Io(path.display().to_string(), e))?;
+
+	let paths = key_paths(dir)?;
+	let mut retired = Vec::new();
+
+	for path in paths.iter().take(paths.len().saturating_sub(config.max_keys)) {
+		fs::remove_file(path).map_err(|e| JwtKeyError::Io(path.display().to_string(), e))?;
+		retired.push(kid_of(path));
+	}
+
+	Ok(RotatedKey { kid, retired })
+}
+
+fn algorithm_of(algorithm: JwtAlgorithm) -> Algorithm {
+	match algorithm {
+		JwtAlgorithm::HS256 => Algorithm::HS256,
+		JwtAlgorithm::RS256 => Algorithm::RS256,
+		JwtAlgorithm::EdDSA => Algorithm::EdDSA
+	}
+}
+
+#[cfg(unix)]
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+	use std::{ io::Write, os::unix::fs::OpenOptionsExt };
+
+	fs::OpenOptions::new().write(true).create_new(true).mode(0o600).open(path)?.write_all(contents)
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+	fs::write(path, contents)
+}
+
+fn kid_of(path: &Path) -> String {
+	path.file_stem().map(|d| d.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+// `*.pem` files of `dir`, oldest first. A missing directory has none.
+fn key_paths(dir: &Path) -> Result<Vec<PathBuf>, JwtKeyError> {
+	let entries = match fs::read_dir(dir) {
+		Ok(val) => val,
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+		Err(e) => return Err(JwtKeyError::Io(dir.display().to_string(), e))
+	};
+
+	let mut paths: Vec<PathBuf> = entries
+	.filter_map(|d| d.ok().map(|d| d.path()))
+	.filter(|d| d.extension().is_some_and(|ext| ext == "pem"))
+	.collect();
+
+	paths.sort();
+
+	Ok(paths)
+}
+
+fn load_keys(dir: &Path) -> Result<Vec<StoredKey>, JwtKeyError> {
+	key_paths(dir)?.iter().map(|path| {
+		let contents = fs::read_to_string(path).map_err(|e| JwtKeyError::Io(path.display().to_string(), e))?;
+
+		load_key(kid_of(path), &contents).map_err(|e| JwtKeyError::InvalidKey(path.display().to_string(), e))
+	}).collect()
+}
+
+// Accepts PKCS#8 Ed25519 or RSA keys, and PKCS#1 RSA keys.
+fn load_key(kid: String, contents: &str) -> Result<StoredKey, String> {
+	let pem = pem::parse(contents).map_err(|e| e.to_string())?;
+
+	let rsa_key = match pem.tag() {
+		"PRIVATE KEY" => match Ed25519KeyPair::from_pkcs8_maybe_unchecked(pem.contents()) {
+			Ok(key_pair) => {
+				let jwk = Jwk {
+					common: common_parameters(&kid, KeyAlgorithm::EdDSA),
+					algorithm: AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+						key_type: OctetKeyPairType::OctetKeyPair,
+						curve: EllipticCurve::Ed25519,
+						x: URL_SAFE_NO_PAD.encode(key_pair.public_key().as_ref())
+					})
+				};
+
+				return Ok(StoredKey {
+					kid,
+					algorithm: Algorithm::EdDSA,
+					encoding: EncodingKey::from_ed_der(pem.contents()),
+					jwk
+				});
+			},
+			Err(_) => RsaPrivateKey::from_pkcs8_der(pem.contents()).map_err(|e| e.to_string())?
+		},
+		"RSA PRIVATE KEY" => RsaPrivateKey::from_pkcs1_der(pem.contents()).map_err(|e| e.to_string())?,
+		tag => return Err(format!("unexpected PEM block {tag}"))
+	};
+
+	if rsa_key.size() * 8 < RSA_KEY_BITS {
+		return Err(format!("RSA keys need at least {RSA_KEY_BITS} bits"));
+	}
+
+	let pkcs1 = rsa_key.to_pkcs1_der().map_err(|e| e.to_string())?;
+
+	let jwk = Jwk {
+		common: common_parameters(&kid, KeyAlgorithm::RS256),
+		algorithm: AlgorithmParameters::RSA(RSAKeyParameters {
+			key_type: RSAKeyType::RSA,
+			n: URL_SAFE_NO_PAD.encode(rsa_key.n().to_bytes_be()),
+			e: URL_SAFE_NO_PAD.encode(rsa_key.e().to_bytes_be())
+		})
+	};
+
+	Ok(StoredKey {
+		kid,
+		algorithm: Algorithm::RS256,
+		encoding: EncodingKey::from_rsa_der(pkcs1.as_bytes()),
+		jwk
+	})
+}
+
+fn common_parameters(kid: &str, algorithm: KeyAlgorithm) -> CommonParameters {
+	CommonParameters {
+		public_key_use: Some(PublicKeyUse::Signature),
+		key_algorithm: Some(algorithm),
+		key_id: Some(kid.to_owned()),
+		..CommonParameters::default()
+	}
+}
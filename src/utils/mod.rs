@@ -1,2 +1,14 @@
-pub mod utils;
-pub mod router_gurard;
\ No newline at end of file
+pub mod router_gurard;
+pub mod api_scope;
+pub mod file_buckets;
+pub mod file_store;
+pub mod client_ip;
+pub mod jwt;
+pub mod oidc;
+pub mod password;
+pub mod password_reset_notifier;
+pub mod rate_limit;
+pub mod request_metrics;
+pub mod signed_url;
+pub mod totp;
+pub mod touch;
\ No newline at end of file
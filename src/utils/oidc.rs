@@ -0,0 +1,212 @@
+use async_trait::async_trait;
+use jsonwebtoken::{ decode, decode_header, jwk::{ Jwk, JwkSet }, Algorithm, DecodingKey, Validation };
+use serde::{ de::DeserializeOwned, Deserialize };
+use serde_json::{ Map, Value };
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+use crate::config::OidcConfig;
+use crate::service::ServiceError;
+
+// Asymmetric algorithms only; the client secret never verifies ID tokens.
+const ID_TOKEN_ALGORITHMS: &[Algorithm] = &[
+	Algorithm::RS256,
+	Algorithm::RS384,
+	Algorithm::RS512,
+	Algorithm::PS256,
+	Algorithm::PS384,
+	Algorithm::PS512,
+	Algorithm::ES256,
+	Algorithm::ES384,
+	Algorithm::EdDSA
+];
+
+// Who logged in at the provider, from a verified ID token.
+pub struct ExternalIdentity {
+	pub issuer: String,
+	pub subject: String,
+	// Every claim of the ID token, for the username and role mapping.
+	pub claims: Map<String, Value>
+}
+
+// Where users log in instead of with a local password. `OidcProvider` is used
+// when `oidc.enabled` is set; `AppState::with_identity_provider` installs
+// another.
+#[async_trait]
+pub trait IdentityProvider: Send + Sync {
+	// Where to send the browser. `code_challenge` is the S256 PKCE challenge.
+	async fn authorization_url(&self, state: &str, nonce: &str, code_challenge: &str) -> Result<String, ServiceError>;
+	// Redeems the code the provider sent back. The ID token must carry `nonce`.
+	async fn exchange(&self, code: &str, code_verifier: &str, nonce: &str) -> Result<ExternalIdentity, ServiceError>;
+}
+
+#[derive(Deserialize, Clone)]
+struct Discovery {
+	issuer: String,
+	authorization_endpoint: String,
+	token_endpoint: String,
+	jwks_uri: String
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+	id_token: Option<String>
+}
+
+#[derive(Deserialize)]
+struct TokenErrorResponse {
+	error: String,
+	error_description: Option<String>
+}
+
+fn unreachable_provider(e: reqwest::Error) -> ServiceError {
+	ServiceError::Internal(format!("The identity provider could not be reached: {e}"))
+}
+
+fn invalid_id_token(e: impl std::fmt::Display) -> ServiceError {
+	ServiceError::Unauthorized(format!("INVALID ID TOKEN: {e}"))
+}
+
+// An OpenID Connect provider, configured from its discovery document. The
+// document and the provider's keys are fetched at the first login and kept;
+// the keys are fetched again when a token names one that isn't known yet.
+pub struct OidcProvider {
+	config: OidcConfig,
+	http: reqwest::Client,
+	discovery: RwLock<Option<Discovery>>,
+	jwks: RwLock<Option<JwkSet>>
+}
+
+impl OidcProvider {
+	pub fn new(config: OidcConfig) -> Self {
+		let http = reqwest::Client::builder()
+		.timeout(Duration::from_secs(10))
+		.build()
+		.expect("Failed to build the OIDC HTTP client");
+
+		Self { config, http, discovery: RwLock::new(None), jwks: RwLock::new(None) }
+	}
+
+	async fn fetch<T: DeserializeOwned>(&self, url: &str) -> Result<T, ServiceError> {
+		self.http.get(url).send().await
+		.and_then(|d| d.error_for_status())
+		.map_err(unreachable_provider)?
+		.json::<T>().await
+		.map_err(unreachable_provider)
+	}
+
+	async fn discovery(&self) -> Result<Discovery, ServiceError> {
+		if let Some(val) = self.discovery.read().await.as_ref() {
+			return Ok(val.clone());
+		}
+
+		let url = format!("{}/.well-known/openid-configuration", self.config.issuer.trim_end_matches('/'));
+		let discovery: Discovery = self.fetch(&url).await?;
+
+		if discovery.issuer != self.config.issuer {
+			return Err(ServiceError::Internal(format!(
+				"The OIDC discovery document names issuer {}, expected {}.", discovery.issuer, self.config.issuer
+			)));
+		}
+
+		*self.discovery.write().await = Some(discovery.clone());
+
+		Ok(discovery)
+	}
+
+	async fn signing_key(&self, kid: Option<&str>, jwks_uri: &str) -> Result<Jwk, ServiceError> {
+		fn find(keys: &JwkSet, kid: Option<&str>) -> Option<Jwk> {
+			match kid {
+				Some(kid) => keys.find(kid).cloned(),
+				None if keys.keys.len() == 1 => keys.keys.first().cloned(),
+				None => None
+			}
+		}
+
+		if let Some(jwk) = self.jwks.read().await.as_ref().and_then(|d| find(d, kid)) {
+			return Ok(jwk);
+		}
+
+		let keys: JwkSet = self.fetch(jwks_uri).await?;
+		let jwk = find(&keys, kid);
+
+		*self.jwks.write().await = Some(keys);
+
+		jwk.ok_or_else(|| invalid_id_token("signed with an unknown key"))
+	}
+}
+
+#[async_trait]
+impl IdentityProvider for OidcProvider {
+	async fn authorization_url(&self, state: &str, nonce: &str, code_challenge: &str) -> Result<String, ServiceError> {
+		let discovery = self.discovery().await?;
+
+		let url = reqwest::Url::parse_with_params(&discovery.authorization_endpoint, &[
+			("response_type", "code"),
+			("client_id", &self.config.client_id),
+			("redirect_uri", &self.config.redirect_uri),
+			("scope", &self.config.scopes.join(" ")),
+			("state", state),
+			("nonce", nonce),
+			("code_challenge", code_challenge),
+			("code_challenge_method", "S256")
+		]).map_err(|e| ServiceError::Internal(format!("Invalid OIDC authorization endpoint: {e}")))?;
+
+		Ok(url.into())
+	}
+
+	async fn exchange(&self, code: &str, code_verifier: &str, nonce: &str) -> Result<ExternalIdentity, ServiceError> {
+		let discovery = self.discovery().await?;
+
+		let mut form = vec![
+			("grant_type", "authorization_code"),
+			("code", code),
+			("redirect_uri", &self.config.redirect_uri),
+			("client_id", &self.config.client_id),
+			("code_verifier", code_verifier)
+		];
+
+		if !self.config.client_secret.is_empty() {
+			form.push(("client_secret", &self.config.client_secret));
+		}
+
+		let response = self.http.post(&discovery.token_endpoint).form(&form).send().await.map_err(unreachable_provider)?;
+
+		if !response.status().is_success() {
+			let reason = match response.json::<TokenErrorResponse>().await {
+				Ok(val) => val.error_description.unwrap_or(val.error),
+				Err(e) => e.to_string()
+			};
+
+			return Err(ServiceError::Unauthorized(format!("The identity provider refused the login: {reason}")));
+		}
+
+		let id_token = response.json::<TokenResponse>().await.map_err(unreachable_provider)?
+		.id_token
+		.ok_or_else(|| invalid_id_token("the token response has none"))?;
+
+		let header = decode_header(&id_token).map_err(invalid_id_token)?;
+
+		if !ID_TOKEN_ALGORITHMS.contains(&header.alg) {
+			return Err(invalid_id_token(format!("{:?} is not accepted", header.alg)));
+		}
+
+		let jwk = self.signing_key(header.kid.as_deref(), &discovery.jwks_uri).await?;
+		let key = DecodingKey::from_jwk(&jwk).map_err(invalid_id_token)?;
+
+		let mut validation = Validation::new(header.alg);
+		validation.set_issuer(&[&self.config.issuer]);
+		validation.set_audience(&[&self.config.client_id]);
+		validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+		let claims = decode::<Map<String, Value>>(&id_token, &key, &validation).map_err(invalid_id_token)?.claims;
+
+		if claims.get("nonce").and_then(Value::as_str) != Some(nonce) {
+			return Err(invalid_id_token("nonce mismatch"));
+		}
+
+		let subject = claims.get("sub").and_then(Value::as_str).unwrap_or_default().to_owned();
+
+		Ok(ExternalIdentity { issuer: self.config.issuer.clone(), subject, claims })
+	}
+}
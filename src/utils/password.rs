@@ -0,0 +1,78 @@
+use argon2::{
+	password_hash::{ PasswordHash, PasswordHasher as _, PasswordVerifier, SaltString },
+	Algorithm, Argon2, Params, Version
+};
+use rand::rngs::OsRng;
+
+use crate::config::{ PasswordAlgorithm, PasswordConfig };
+use crate::service::ServiceError;
+
+// Hashes new passwords with the configured algorithm and verifies any hash
+// this application has ever stored (argon2 PHC strings and bcrypt).
+pub struct PasswordHasher {
+	algorithm: PasswordAlgorithm,
+	argon2_params: Params,
+	bcrypt_cost: u32
+}
+
+impl PasswordHasher {
+	pub fn from_config(config: &PasswordConfig) -> Self {
+		Self {
+			algorithm: config.algorithm,
+			argon2_params: Params::new(
+				config.argon2_memory_kib,
+				config.argon2_iterations,
+				config.argon2_parallelism,
+				None
+			).expect("argon2 parameters are checked by AppConfig::validate"),
+			bcrypt_cost: config.bcrypt_cost
+		}
+	}
+
+	fn argon2(&self) -> Argon2<'static> {
+		Argon2::new(Algorithm::Argon2id, Version::V0x13, self.argon2_params.clone())
+	}
+
+	pub fn hash(&self, password: &str) -> Result<String, ServiceError> {
+		match self.algorithm {
+			PasswordAlgorithm::Argon2id => self.argon2()
+			.hash_password(password.as_bytes(), &SaltString::generate(&mut OsRng))
+			.map(|d| d.to_string())
+			.map_err(|e| ServiceError::Internal(e.to_string())),
+			PasswordAlgorithm::Bcrypt => bcrypt::hash(password, self.bcrypt_cost)
+			.map_err(|e| ServiceError::Internal(e.to_string()))
+		}
+	}
+
+	// A malformed or unknown hash never matches.
+	pub fn verify(&self, password: &str, stored: &str) -> bool {
+		if stored.starts_with("$argon2") {
+			return PasswordHash::new(stored)
+			.is_ok_and(|d| Argon2::default().verify_password(password.as_bytes(), &d).is_ok());
+		}
+
+		bcrypt::verify(password, stored).unwrap_or(false)
+	}
+
+	// Whether `stored` was made with another algorithm or other parameters
+	// than new hashes are.
+	pub fn needs_rehash(&self, stored: &str) -> bool {
+		match self.algorithm {
+			PasswordAlgorithm::Argon2id => {
+				let Ok(hash) = PasswordHash::new(stored) else {
+					return true;
+				};
+
+				hash.algorithm != Algorithm::Argon2id.ident()
+				|| hash.version != Some(Version::V0x13.into())
+				|| Params::try_from(&hash).map_or(true, |d| {
+					d.m_cost() != self.argon2_params.m_cost()
+					|| d.t_cost() != self.argon2_params.t_cost()
+					|| d.p_cost() != self.argon2_params.p_cost()
+				})
+			},
+			PasswordAlgorithm::Bcrypt => !stored.starts_with("$2")
+			|| stored.get(4..6).and_then(|d| d.parse::<u32>().ok()) != Some(self.bcrypt_cost)
+		}
+	}
+}
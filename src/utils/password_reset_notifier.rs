@@ -0,0 +1,13 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+
+use crate::model::user_model::UserData;
+use crate::service::ServiceError;
+
+// Delivers a reset token requested through `/api/auth/forgot-password`, e.g.
+// by mail or SMS. Without one, such requests are only logged and the reset
+// has to be issued by an admin.
+#[async_trait]
+pub trait PasswordResetNotifier: Send + Sync {
+	async fn send(&self, user: &UserData, token: &str, expires_at: NaiveDateTime) -> Result<(), ServiceError>;
+}
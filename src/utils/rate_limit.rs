@@ -0,0 +1,246 @@
+use async_trait::async_trait;
+use axum::{
+	extract::{ Request, State },
+	http::{ HeaderMap, HeaderValue, Method },
+	middleware::Next,
+	response::{ IntoResponse, Response }
+};
+use std::{
+	collections::HashMap,
+	sync::{ Arc, Mutex },
+	time::Instant
+};
+
+use crate::config::{ RateLimitConfig, RateLimitRule };
+use crate::model::api_key_model::ApiKeyPrincipal;
+use crate::model::user_model::JwtClaims;
+use crate::service::ServiceError;
+use crate::utils::client_ip::client_ip;
+
+// Buckets that have refilled completely are dropped once the in-memory store
+// holds this many keys.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RateLimitGroup {
+	Login,
+	Search,
+	Uploads,
+	Writes
+}
+
+impl RateLimitGroup {
+	pub fn name(self) -> &'static str {
+		match self {
+			RateLimitGroup::Login => "login",
+			RateLimitGroup::Search => "search",
+			RateLimitGroup::Uploads => "uploads",
+			RateLimitGroup::Writes => "writes"
+		}
+	}
+}
+
+// A bucket of `burst` tokens refilled at `per_minute`.
+#[derive(Clone, Copy, Debug)]
+pub struct RateLimitPolicy {
+	pub burst: u32,
+	pub refill_per_second: f64
+}
+
+impl From<&RateLimitRule> for RateLimitPolicy {
+	fn from(rule: &RateLimitRule) -> Self {
+		Self {
+			burst: rule.burst,
+			refill_per_second: rule.per_minute as f64 / 60.0
+		}
+	}
+}
+
+pub struct RateLimitDecision {
+	pub allowed: bool,
+	pub remaining: u32,
+	// Seconds until the bucket is full again.
+	pub reset_seconds: u64,
+	// Seconds until the next token, when `allowed` is false.
+	pub retry_after_seconds: u64
+}
+
+// Where buckets live. `InMemoryRateLimitStore` is per process; a shared store
+// (e.g. Redis) lets replicas enforce one budget.
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+	// Refills the bucket at `key` and takes one token from it if there is one.
+	async fn acquire(&self, key: &str, policy: RateLimitPolicy) -> Result<RateLimitDecision, ServiceError>;
+}
+
+struct Bucket {
+	tokens: f64,
+	updated_at: Instant,
+	policy: RateLimitPolicy
+}
+
+#[derive(Default)]
+pub struct InMemoryRateLimitStore {
+	buckets: Mutex<HashMap<String, Bucket>>
+}
+
+fn refill(bucket: &mut Bucket, now: Instant) {
+	let policy = bucket.policy;
+	let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+
+	bucket.tokens = (bucket.tokens + elapsed * policy.refill_per_second).min(policy.burst as f64);
+	bucket.updated_at = now;
+}
+
+fn seconds_until(tokens: f64, policy: RateLimitPolicy) -> u64 {
+	if tokens <= 0.0 {
+		return 0;
+	}
+
+	if policy.refill_per_second <= 0.0 {
+		return u64::MAX;
+	}
+
+	(tokens / policy.refill_per_second).ceil() as u64
+}
+
+#[async_trait]
+impl RateLimitStore for InMemoryRateLimitStore {
+	async fn acquire(&self, key: &str, policy: RateLimitPolicy) -> Result<RateLimitDecision, ServiceError> {
+		let now = Instant::now();
+		let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+
+		if buckets.len() >= PRUNE_THRESHOLD {
+			buckets.retain(|_, bucket| {
+				refill(bucket, now);
+				bucket.tokens < bucket.policy.burst as f64
+			});
+		}
+
+		let bucket = buckets.entry(key.to_owned())
+		.or_insert(Bucket { tokens: policy.burst as f64, updated_at: now, policy });
+
+		bucket.policy = policy;
+		refill(bucket, now);
+
+		let allowed = bucket.tokens >= 1.0;
+
+		if allowed {
+			bucket.tokens -= 1.0;
+		}
+
+		Ok(RateLimitDecision {
+			allowed,
+			remaining: bucket.tokens.floor() as u32,
+			reset_seconds: seconds_until(policy.burst as f64 - bucket.tokens, policy),
+			retry_after_seconds: if allowed { 0 } else { seconds_until(1.0 - bucket.tokens, policy).max(1) }
+		})
+	}
+}
+
+// Shared by every route group; `group` hands out the middleware state for one.
+#[derive(Clone)]
+pub struct RateLimits {
+	store: Arc<dyn RateLimitStore>,
+	config: RateLimitConfig,
+	forwarded_hops: usize
+}
+
+impl RateLimits {
+	pub fn new(store: Arc<dyn RateLimitStore>, config: RateLimitConfig, forwarded_hops: usize) -> Self {
+		Self { store, config, forwarded_hops }
+	}
+
+	pub fn group(&self, group: RateLimitGroup) -> RateLimiter {
+		let rule = match group {
+			RateLimitGroup::Login => &self.config.login,
+			RateLimitGroup::Search => &self.config.search,
+			RateLimitGroup::Uploads => &self.config.uploads,
+			RateLimitGroup::Writes => &self.config.writes
+		};
+
+		RateLimiter {
+			group,
+			policy: (self.config.enabled && rule.burst > 0).then(|| RateLimitPolicy::from(rule)),
+			store: self.store.clone(),
+			forwarded_hops: self.forwarded_hops
+		}
+	}
+}
+
+#[derive(Clone)]
+pub struct RateLimiter {
+	group: RateLimitGroup,
+	// `None` when the group, or rate limiting as a whole, is switched off.
+	policy: Option<RateLimitPolicy>,
+	store: Arc<dyn RateLimitStore>,
+	forwarded_hops: usize
+}
+
+impl RateLimiter {
+	// Authenticated requests are counted per user or API key, others per
+	// client IP.
+	fn key(&self, req: &Request) -> String {
+		let group = self.group.name();
+
+		if let Some(claims) = req.extensions().get::<JwtClaims>() {
+			return format!("{group}:user:{}", claims.sub);
+		}
+
+		if let Some(principal) = req.extensions().get::<ApiKeyPrincipal>() {
+			return format!("{group}:key:{}", principal.key_id);
+		}
+
+		match client_ip(req.headers(), req.extensions(), self.forwarded_hops) {
+			Some(ip) => format!("{group}:ip:{ip}"),
+			None => format!("{group}:ip:unknown")
+		}
+	}
+}
+
+fn set_headers(headers: &mut HeaderMap, policy: RateLimitPolicy, decision: &RateLimitDecision) {
+	headers.insert("ratelimit-limit", HeaderValue::from(policy.burst));
+	headers.insert("ratelimit-remaining", HeaderValue::from(decision.remaining));
+	headers.insert("ratelimit-reset", HeaderValue::from(decision.reset_seconds));
+}
+
+// Route layer: `middleware::from_fn_with_state(limits.group(..), rate_limit)`.
+// Put it inside `auth_guard` so that authenticated requests are keyed by user.
+// The writes group only counts requests that change something.
+pub async fn rate_limit(State(limiter): State<RateLimiter>, req: Request, next: Next) -> Response {
+	let Some(policy) = limiter.policy else {
+		return next.run(req).await;
+	};
+
+	let is_read = matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+
+	if limiter.group == RateLimitGroup::Writes && is_read {
+		return next.run(req).await;
+	}
+
+	let key = limiter.key(&req);
+
+	// A failing shared store lets requests through rather than taking the API down.
+	let decision = match limiter.store.acquire(&key, policy).await {
+		Ok(val) => val,
+		Err(e) => {
+			tracing::warn!(error = %e, group = limiter.group.name(), "rate limit store failed");
+			return next.run(req).await;
+		}
+	};
+
+	let mut response = if decision.allowed {
+		next.run(req).await
+	} else {
+		metrics::counter!("http_rate_limited_total", "group" => limiter.group.name()).increment(1);
+
+		ServiceError::TooManyRequests(
+			"Too many requests, slow down.".to_owned(),
+			decision.retry_after_seconds
+		).into_response()
+	};
+
+	set_headers(response.headers_mut(), policy, &decision);
+
+	response
+}
@@ -0,0 +1,104 @@
+use axum::{
+	extract::{ MatchedPath, Request },
+	middleware::Next,
+	response::Response
+};
+use metrics::{ counter, gauge, histogram };
+use metrics_exporter_prometheus::{ Matcher, PrometheusBuilder, PrometheusHandle };
+use std::sync::OnceLock;
+use std::time::Instant;
+
+// Latency buckets in seconds for every `*_seconds` histogram.
+const LATENCY_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+static RECORDER: OnceLock<PrometheusHandle> = OnceLock::new();
+
+// The recorder is process-wide, so it is installed once and every `AppState`
+// (the tests build many) shares its handle.
+pub fn install_recorder() -> PrometheusHandle {
+	RECORDER.get_or_init(|| {
+		let recorder = PrometheusBuilder::new()
+		.set_buckets_for_metric(Matcher::Suffix("_seconds".to_owned()), LATENCY_BUCKETS)
+		.expect("latency buckets are not empty")
+		.build_recorder();
+
+		let handle = recorder.handle();
+
+		if let Err(e) = metrics::set_global_recorder(recorder) {
+			tracing::warn!(error = %e, "a metrics recorder was already installed");
+		}
+
+		handle
+	}).clone()
+}
+
+// Counts a request in `http_requests_in_flight` while alive. Dropping it
+// also covers requests whose future is dropped, e.g. when the client goes
+// away or a timeout fires, which never get to the code after `next.run`.
+struct InFlight(metrics::Gauge);
+
+impl InFlight {
+	fn start() -> Self {
+		let gauge = gauge!("http_requests_in_flight");
+		gauge.increment(1.0);
+
+		Self(gauge)
+	}
+}
+
+impl Drop for InFlight {
+	fn drop(&mut self) {
+		self.0.decrement(1.0);
+	}
+}
+
+// Counts requests and their latency by route template (never the raw path,
+// which would give every product id its own series) and status.
+pub async fn track_requests(req: Request, next: Next) -> Response {
+	let route = req.extensions().get::<MatchedPath>()
+	.map(|d| d.as_str().to_owned())
+	.unwrap_or_else(|| "unmatched".to_owned());
+	let method = req.method().to_string();
+	let start = Instant::now();
+
+	let in_flight = InFlight::start();
+	let response = next.run(req).await;
+
+	drop(in_flight);
+
+	let labels = [
+		("method", method),
+		("route", route),
+		("status", response.status().as_u16().to_string())
+	];
+
+	counter!("http_requests_total", &labels).increment(1);
+	histogram!("http_request_duration_seconds", &labels).record(start.elapsed().as_secs_f64());
+
+	response
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::{ future::Future, task::Context };
+
+	#[test]
+	fn dropped_requests_leave_the_in_flight_count() {
+		let recorder = PrometheusBuilder::new().build_recorder();
+		let handle = recorder.handle();
+
+		metrics::with_local_recorder(&recorder, || {
+			let mut request = Box::pin(async {
+				let _in_flight = InFlight::start();
+				std::future::pending::<()>().await
+			});
+
+			let _ = request.as_mut().poll(&mut Context::from_waker(&futures_util::task::noop_waker()));
+
+			assert!(handle.render().contains("http_requests_in_flight 1"));
+		});
+
+		assert!(handle.render().contains("http_requests_in_flight 0"));
+	}
+}
@@ -1,19 +1,28 @@
 use axum::{
 	body::Body,
+	extract::State,
 	http::{ Request, StatusCode },
 	middleware::Next,
 	response::{Response}
 };
 
-use jsonwebtoken::{ decode, DecodingKey, Validation };
 use serde_json::json;
-use crate::model::user_model::JwtClaims;
 
+use crate::service::{ api_key_service::ApiKeyService, auth_service::AuthService, ServiceError };
+use crate::telemetry::record_user_id;
+use crate::utils::api_scope::ApiScope;
 
-pub async fn auth_guard(req: Request<Body>, next: Next) -> Result<Response, (StatusCode, String)> {
-    dotenvy::dotenv().expect("Failed to load .env file.");
+pub const API_KEY_HEADER: &str = "x-api-key";
 
-	let jwt_secret = std::env::var("JWT_SECRET").unwrap();
+pub async fn auth_guard(
+	State(auth): State<AuthService>,
+	State(api_keys): State<ApiKeyService>,
+	mut req: Request<Body>,
+	next: Next
+) -> Result<Response, (StatusCode, String)> {
+	if !req.headers().contains_key("Authorization") && req.headers().contains_key(API_KEY_HEADER) {
+		return api_key_guard(api_keys, req, next).await;
+	}
 
 	let extracted_header_value = req.headers().get("Authorization");
 
@@ -27,15 +36,18 @@ pub async fn auth_guard(req: Request<Body>, next: Next) -> Result<Response, (Sta
 
 					match jwt_token {
 						Some(token) => {
-							let decoed_token = decode::<JwtClaims>(
-								token, 
-								&DecodingKey::from_secret(jwt_secret.as_ref()), 
-								&Validation::default()
-							);
+							match auth.authorize(token).await {
+								Ok(claims) => {
+									record_user_id(claims.sub);
+									req.extensions_mut().insert(claims);
 
-							match decoed_token {
-								Ok(_) => Ok(
-									next.run(req).await
+									Ok(next.run(req).await)
+								},
+								Err(ServiceError::Unauthorized(e)) => Err(
+									(
+										StatusCode::UNAUTHORIZED,
+										json!({ "success": false, "message": e }).to_string()
+									)
 								),
 								Err(e) => Err(
 									(
@@ -68,4 +80,54 @@ pub async fn auth_guard(req: Request<Body>, next: Next) -> Result<Response, (Sta
 			)
 		)
 	}
-}
\ No newline at end of file
+}
+
+// Integrations send `X-Api-Key` instead of a bearer token. The key is only
+// accepted by route groups that declare an `ApiScope`, and only if it was
+// granted that scope.
+async fn api_key_guard(
+	api_keys: ApiKeyService,
+	mut req: Request<Body>,
+	next: Next
+) -> Result<Response, (StatusCode, String)> {
+	let required = match req.extensions().get::<ApiScope>() {
+		Some(scope) => scope.required(req.method()),
+		None => return Err(
+			(
+				StatusCode::FORBIDDEN,
+				json!({ "success": false, "message": "API keys are not accepted here." }).to_string()
+			)
+		)
+	};
+
+	let key = req.headers().get(API_KEY_HEADER).and_then(|d| d.to_str().ok()).unwrap_or_default().to_owned();
+
+	match api_keys.authenticate(&key).await {
+		Ok(principal) => {
+			if !principal.scopes.contains(&required) {
+				return Err(
+					(
+						StatusCode::FORBIDDEN,
+						json!({ "success": false, "message": format!("The API key lacks the {required} scope.") }).to_string()
+					)
+				);
+			}
+
+			req.extensions_mut().insert(principal);
+
+			Ok(next.run(req).await)
+		},
+		Err(ServiceError::Unauthorized(e)) => Err(
+			(
+				StatusCode::UNAUTHORIZED,
+				json!({ "success": false, "message": e }).to_string()
+			)
+		),
+		Err(e) => Err(
+			(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				json!({ "success": false, "message": e.to_string() }).to_string()
+			)
+		)
+	}
+}
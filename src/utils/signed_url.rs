@@ -0,0 +1,41 @@
+use hmac::{ Hmac, Mac };
+use sha2::Sha256;
+
+use crate::config::UploadConfig;
+use crate::utils::file_store::file_url;
+
+type HmacSha256 = Hmac<Sha256>;
+
+fn mac_for(secret: &str, namespace: &str, file_name: &str, expires: i64) -> HmacSha256 {
+	let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
+	.expect("HMAC accepts keys of any length.");
+
+	mac.update(format!("{namespace}/{file_name}:{expires}").as_bytes());
+	mac
+}
+
+pub fn sign(config: &UploadConfig, namespace: &str, file_name: &str, expires: i64) -> String {
+	hex::encode(mac_for(&config.signing_secret, namespace, file_name, expires).finalize().into_bytes())
+}
+
+// Returns the signed URL together with its unix expiry timestamp.
+pub fn signed_file_url(config: &UploadConfig, namespace: &str, file_name: &str) -> (String, i64) {
+	let expires = chrono::Utc::now().timestamp() + config.signed_url_ttl_seconds;
+	let signature = sign(config, namespace, file_name, expires);
+
+	(
+		format!("{}?expires={expires}&signature={signature}", file_url(namespace, file_name)),
+		expires
+	)
+}
+
+pub fn verify(config: &UploadConfig, namespace: &str, file_name: &str, expires: i64, signature: &str) -> bool {
+	if expires < chrono::Utc::now().timestamp() {
+		return false;
+	}
+
+	match hex::decode(signature) {
+		Ok(bytes) => mac_for(&config.signing_secret, namespace, file_name, expires).verify_slice(&bytes).is_ok(),
+		Err(_) => false
+	}
+}
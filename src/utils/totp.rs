@@ -0,0 +1,114 @@
+use hmac::{ Hmac, Mac };
+use rand::RngCore;
+use sha1::Sha1;
+
+// RFC 6238 with the parameters every authenticator app defaults to.
+const STEP_SECONDS: i64 = 30;
+const DIGITS: u32 = 6;
+const SECRET_BYTES: usize = 20;
+// Codes from one step either side are accepted to absorb clock drift.
+const ALLOWED_DRIFT_STEPS: i64 = 1;
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+type HmacSha1 = Hmac<Sha1>;
+
+// RFC 4648 base32 without padding, as used in otpauth URIs.
+pub fn base32_encode(bytes: &[u8]) -> String {
+	let mut output = String::with_capacity(bytes.len().div_ceil(5) * 8);
+	let mut buffer: u32 = 0;
+	let mut bits = 0;
+
+	for byte in bytes {
+		buffer = (buffer << 8) | *byte as u32;
+		bits += 8;
+
+		while bits >= 5 {
+			bits -= 5;
+			output.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+		}
+	}
+
+	if bits > 0 {
+		output.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+	}
+
+	output
+}
+
+pub fn base32_decode(encoded: &str) -> Option<Vec<u8>> {
+	let mut output = Vec::with_capacity(encoded.len() * 5 / 8);
+	let mut buffer: u32 = 0;
+	let mut bits = 0;
+
+	for ch in encoded.trim_end_matches('=').chars().filter(|d| !d.is_whitespace()) {
+		let value = BASE32_ALPHABET.iter().position(|d| *d as char == ch.to_ascii_uppercase())? as u32;
+
+		buffer = (buffer << 5) | value;
+		bits += 5;
+
+		if bits >= 8 {
+			bits -= 8;
+			output.push((buffer >> bits) as u8);
+		}
+	}
+
+	Some(output)
+}
+
+// A new random secret, base32 encoded.
+pub fn generate_secret() -> String {
+	let mut bytes = [0u8; SECRET_BYTES];
+	rand::rngs::OsRng.fill_bytes(&mut bytes);
+
+	base32_encode(&bytes)
+}
+
+pub fn step_at(unix_seconds: i64) -> i64 {
+	unix_seconds.div_euclid(STEP_SECONDS)
+}
+
+pub fn code_at(secret: &[u8], step: i64) -> String {
+	let mut mac = HmacSha1::new_from_slice(secret).expect("HMAC accepts keys of any length.");
+	mac.update(&step.to_be_bytes());
+
+	let digest = mac.finalize().into_bytes();
+	let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+	let binary = u32::from_be_bytes([digest[offset], digest[offset + 1], digest[offset + 2], digest[offset + 3]]) & 0x7fff_ffff;
+
+	format!("{:0width$}", binary % 10u32.pow(DIGITS), width = DIGITS as usize)
+}
+
+// The step `code` belongs to, if it is valid around `unix_seconds` and newer
+// than `last_used_step`, so that an observed code can't be replayed.
+pub fn verify(secret: &str, code: &str, unix_seconds: i64, last_used_step: Option<i64>) -> Option<i64> {
+	let secret = base32_decode(secret)?;
+	let code = code.trim();
+
+	if code.len() != DIGITS as usize || !code.bytes().all(|d| d.is_ascii_digit()) {
+		return None;
+	}
+
+	let current = step_at(unix_seconds);
+
+	(current - ALLOWED_DRIFT_STEPS..=current + ALLOWED_DRIFT_STEPS)
+	.filter(|step| last_used_step.is_none_or(|last| *step > last))
+	.find(|step| code_at(&secret, *step) == code)
+}
+
+fn uri_encode(val: &str) -> String {
+	val.bytes().map(|d| match d {
+		b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (d as char).to_string(),
+		_ => format!("%{d:02X}")
+	}).collect()
+}
+
+// `otpauth://` URI for authenticator apps, usually shown as a QR code.
+pub fn otpauth_uri(issuer: &str, account: &str, secret: &str) -> String {
+	let issuer = uri_encode(issuer);
+
+	format!(
+		"otpauth://totp/{issuer}:{}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={DIGITS}&period={STEP_SECONDS}",
+		uri_encode(account)
+	)
+}
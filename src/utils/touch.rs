@@ -0,0 +1,11 @@
+use chrono::NaiveDateTime;
+
+// Usage timestamps such as `last_seen_at` are written at most this often,
+// not on every request.
+const TOUCH_INTERVAL_SECONDS: i64 = 60;
+
+// Whether a record last used at `last_used` (never, if `None`) is due to
+// have its timestamp written again.
+pub fn is_due(last_used: Option<NaiveDateTime>, now: NaiveDateTime) -> bool {
+	last_used.is_none_or(|d| (now - d).num_seconds() >= TOUCH_INTERVAL_SECONDS)
+}
@@ -1 +0,0 @@
-// pub const JWT_SECRET: &str = "a3budrspk2m";
\ No newline at end of file
@@ -0,0 +1,183 @@
+mod common;
+
+use axum::{ body::Body, http::{ header, Method, Request, StatusCode } };
+use sea_orm::{ sea_query::Expr, ColumnTrait, EntityTrait, QueryFilter };
+use serde_json::{ json, Value };
+
+use entity::api_key;
+
+use common::{ multipart_request, TestApp, TestResponse };
+
+async fn create_key(app: &TestApp, token: &str, body: Value) -> Value {
+	let response = app.post("/api/api-keys", token, body).await;
+
+	assert_eq!(response.status, StatusCode::CREATED);
+
+	response.json()
+}
+
+async fn with_key(app: &TestApp, method: Method, uri: &str, key: &str, body: Option<Value>) -> TestResponse {
+	let request = Request::builder()
+	.method(method)
+	.uri(uri)
+	.header(header::CONTENT_TYPE, "application/json")
+	.header("X-Api-Key", key)
+	.body(body.map(|d| Body::from(d.to_string())).unwrap_or_default())
+	.unwrap();
+
+	app.send(request).await
+}
+
+async fn upload_with_key(app: &TestApp, key: &str, fields: &[(&str, &str)], contents: &[u8]) -> TestResponse {
+	let mut request = multipart_request("/api/files/product", fields, &[("file", "tea.png", contents)]);
+
+	request.headers_mut().insert("X-Api-Key", key.parse().unwrap());
+
+	app.send(request).await
+}
+
+#[tokio::test]
+async fn keys_are_shown_once_and_stored_hashed() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+
+	let created = create_key(&app, &token, json!({ "name": "Warehouse sync", "scopes": ["product:read"] })).await;
+	let key = created["key"].as_str().unwrap();
+
+	assert!(key.starts_with("ak_"));
+	assert!(key.starts_with(created["data"]["prefix"].as_str().unwrap()));
+
+	let listed = app.get("/api/api-keys", &token).await.json();
+
+	assert_eq!(listed[0]["name"], "Warehouse sync");
+	assert_eq!(listed[0]["scopes"], json!(["product:read"]));
+	assert!(listed[0]["last_used_at"].is_null());
+	assert!(!listed.to_string().contains(key));
+
+	let row = api_key::Entity::find().one(&app.db).await.unwrap().unwrap();
+
+	assert_ne!(row.key_hash, key);
+	assert_eq!(row.key_hash.len(), 64);
+}
+
+#[tokio::test]
+async fn keys_are_limited_to_their_scopes() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+	let category_id = app.create_category(&token, "Drinks").await;
+
+	let key = create_key(&app, &token, json!({ "name": "Catalogue reader", "scopes": ["category:read", "product:read"] })).await
+	["key"].as_str().unwrap().to_owned();
+
+	assert_eq!(with_key(&app, Method::GET, "/api/category", &key, None).await.status, StatusCode::OK);
+	assert_eq!(with_key(&app, Method::GET, &format!("/api/category/{category_id}"), &key, None).await.status, StatusCode::OK);
+	// Searches are POSTed but only need the read scope.
+	assert_eq!(
+		with_key(&app, Method::POST, "/api/product/search", &key, Some(json!({ "term": "", "page": 1 }))).await.status,
+		StatusCode::OK
+	);
+
+	let denied = with_key(&app, Method::POST, "/api/category", &key, Some(json!({ "name": "Snacks" }))).await;
+
+	assert_eq!(denied.status, StatusCode::FORBIDDEN);
+	assert!(denied.json()["message"].as_str().unwrap().contains("category:write"));
+	assert_eq!(
+		with_key(&app, Method::DELETE, &format!("/api/product/{category_id}"), &key, None).await.status,
+		StatusCode::FORBIDDEN
+	);
+}
+
+#[tokio::test]
+async fn keys_are_refused_by_routes_without_a_scope() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+
+	let key = create_key(&app, &token, json!({ "name": "Everything", "scopes": ["category:read", "category:write", "product:read", "product:write", "file:write"] })).await
+	["key"].as_str().unwrap().to_owned();
+
+	assert_eq!(with_key(&app, Method::GET, "/api/user/many", &key, None).await.status, StatusCode::FORBIDDEN);
+	assert_eq!(with_key(&app, Method::GET, "/api/api-keys", &key, None).await.status, StatusCode::FORBIDDEN);
+	assert_eq!(with_key(&app, Method::GET, "/api/auth/me", &key, None).await.status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn gallery_uploads_need_the_product_write_scope() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+	let category_id = app.create_category(&token, "Drinks").await;
+	let product_id = app.create_product(&token, category_id, "Tea", "").await;
+	let product_field = product_id.to_string();
+	let gallery_fields = [("product_id", product_field.as_str())];
+
+	let files_only = create_key(&app, &token, json!({ "name": "Uploader", "scopes": ["file:write"] })).await
+	["key"].as_str().unwrap().to_owned();
+
+	assert_eq!(upload_with_key(&app, &files_only, &[], b"\x89PNG\r\n\x1a\nloose").await.status, StatusCode::OK);
+
+	let denied = upload_with_key(&app, &files_only, &gallery_fields, b"\x89PNG\r\n\x1a\ngallery").await;
+
+	assert_eq!(denied.status, StatusCode::BAD_REQUEST);
+	assert!(denied.json()["results"][0]["message"].as_str().unwrap().contains("product:write"));
+	assert_eq!(app.get(&format!("/api/product/{product_id}/images"), &token).await.json(), json!([]));
+
+	let catalogue = create_key(&app, &token, json!({ "name": "Catalogue", "scopes": ["file:write", "product:write"] })).await
+	["key"].as_str().unwrap().to_owned();
+
+	assert_eq!(upload_with_key(&app, &catalogue, &gallery_fields, b"\x89PNG\r\n\x1a\ngallery").await.status, StatusCode::OK);
+	assert_eq!(app.get(&format!("/api/product/{product_id}/images"), &token).await.json().as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn revoked_expired_and_unknown_keys_are_rejected() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+
+	let created = create_key(&app, &token, json!({ "name": "Till", "scopes": ["category:read"] })).await;
+	let key = created["key"].as_str().unwrap();
+
+	assert_eq!(with_key(&app, Method::GET, "/api/category", key, None).await.status, StatusCode::OK);
+	assert!(!app.get("/api/api-keys", &token).await.json()[0]["last_used_at"].is_null());
+
+	assert_eq!(app.delete(&format!("/api/api-keys/{}", created["data"]["id"]), &token).await.status, StatusCode::OK);
+	assert_eq!(with_key(&app, Method::GET, "/api/category", key, None).await.status, StatusCode::UNAUTHORIZED);
+	assert_eq!(
+		with_key(&app, Method::GET, "/api/category", "ak_0000000000000000000000000000000000000000", None).await.status,
+		StatusCode::UNAUTHORIZED
+	);
+
+	let expiring = create_key(&app, &token, json!({ "name": "Short lived", "scopes": ["category:read"], "expires_at": "2099-01-01T00:00:00" })).await;
+
+	assert_eq!(expiring["data"]["expires_at"], "2099-01-01T00:00:00");
+	assert_eq!(with_key(&app, Method::GET, "/api/category", expiring["key"].as_str().unwrap(), None).await.status, StatusCode::OK);
+
+	api_key::Entity::update_many()
+	.col_expr(api_key::Column::ExpiresAt, Expr::value(chrono::Utc::now().naive_utc() - chrono::Duration::minutes(1)))
+	.filter(api_key::Column::Id.eq(expiring["data"]["id"].as_i64().unwrap() as i32))
+	.exec(&app.db).await.unwrap();
+
+	assert_eq!(with_key(&app, Method::GET, "/api/category", expiring["key"].as_str().unwrap(), None).await.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn only_admins_manage_keys_with_known_scopes() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+	app.create_user("kasir", "kasir-password", "cashier").await;
+	let cashier_token = app.login_as("kasir", "kasir-password").await.json()["token"].as_str().unwrap().to_owned();
+
+	let body = json!({ "name": "Sync", "scopes": ["category:read"] });
+
+	assert_eq!(app.post("/api/api-keys", &cashier_token, body.clone()).await.status, StatusCode::FORBIDDEN);
+	assert_eq!(app.get("/api/api-keys", &cashier_token).await.status, StatusCode::FORBIDDEN);
+
+	for invalid in [
+		json!({ "name": "Sync", "scopes": ["stock:delete"] }),
+		json!({ "name": "Sync", "scopes": [] }),
+		json!({ "name": " ", "scopes": ["category:read"] }),
+		json!({ "name": "Sync", "scopes": ["category:read"], "expires_at": "2020-01-01T00:00:00" })
+	] {
+		assert_eq!(app.post("/api/api-keys", &token, invalid).await.status, StatusCode::BAD_REQUEST);
+	}
+
+	assert_eq!(app.delete("/api/api-keys/999", &token).await.status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,193 @@
+mod common;
+
+use axum::http::{ Method, StatusCode };
+use serde_json::{ json, Value };
+
+use common::{ TestApp, ADMIN_PASSWORD, ADMIN_USERNAME };
+use rust_axum_seaorm::model::user_model::JwtClaims;
+
+fn claims(app: &TestApp, token: &str) -> JwtClaims {
+	app.state.jwt.decode(token, &app.state.config.jwt.audience).unwrap()
+}
+
+#[tokio::test]
+async fn hello_world_is_public() {
+	let app = TestApp::spawn().await;
+
+	let response = app.request(Method::GET, "/api", None, None).await;
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(&response.body[..], b"Hello World");
+}
+
+#[tokio::test]
+async fn login_returns_user_and_token() {
+	let app = TestApp::spawn().await;
+	app.create_user(ADMIN_USERNAME, ADMIN_PASSWORD, "admin").await;
+
+	let response = app.login_as(ADMIN_USERNAME, ADMIN_PASSWORD).await;
+	let body = response.json();
+
+	assert_eq!(response.status, StatusCode::ACCEPTED);
+	assert_eq!(body["success"], true);
+	assert_eq!(body["data"]["username"], ADMIN_USERNAME);
+	assert_eq!(body["data"]["password"], "");
+	assert!(body["token"].as_str().is_some_and(|d| !d.is_empty()));
+}
+
+#[tokio::test]
+async fn login_rejects_wrong_password_and_unknown_user() {
+	let app = TestApp::spawn().await;
+	app.create_user(ADMIN_USERNAME, ADMIN_PASSWORD, "admin").await;
+
+	let wrong_password = app.login_as(ADMIN_USERNAME, "not-the-password").await;
+	let unknown_user = app.login_as("nobody", ADMIN_PASSWORD).await;
+
+	assert_eq!(wrong_password.status, StatusCode::UNAUTHORIZED);
+	assert_eq!(unknown_user.status, StatusCode::UNAUTHORIZED);
+	assert_eq!(wrong_password.json()["message"], unknown_user.json()["message"]);
+}
+
+#[tokio::test]
+async fn authenticated_reissues_token() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+
+	let response = app.request(Method::POST, "/api/auth/authenticated", Some(&token), None).await;
+	let body = response.json();
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(body["data"]["username"], ADMIN_USERNAME);
+	assert!(body["token"].as_str().is_some_and(|d| !d.is_empty()));
+}
+
+#[tokio::test]
+async fn authenticated_rejects_missing_and_invalid_tokens() {
+	let app = TestApp::spawn().await;
+
+	let missing = app.request(Method::POST, "/api/auth/authenticated", None, None).await;
+	let invalid = app.request(Method::POST, "/api/auth/authenticated", Some("not-a-jwt"), None).await;
+
+	assert_eq!(missing.status, StatusCode::UNAUTHORIZED);
+	assert_eq!(invalid.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn guarded_routes_require_a_token() {
+	let app = TestApp::spawn().await;
+
+	let response = app.request(Method::GET, "/api/category", None, None).await;
+
+	assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn change_password_replaces_the_old_password() {
+	let app = TestApp::spawn().await;
+	let user_id = app.create_user("cashier", "old-password", "cashier").await;
+
+	let response = app.request(Method::POST, "/api/auth/change-password", None, Some(json!({
+		"id": user_id,
+		"old_password": "old-password",
+		"new_password": "new-password"
+	}))).await;
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(app.login_as("cashier", "old-password").await.status, StatusCode::UNAUTHORIZED);
+	assert_eq!(app.login_as("cashier", "new-password").await.status, StatusCode::ACCEPTED);
+}
+
+#[tokio::test]
+async fn change_password_checks_the_old_password() {
+	let app = TestApp::spawn().await;
+	let user_id = app.create_user("cashier", "old-password", "cashier").await;
+
+	let wrong_old = app.request(Method::POST, "/api/auth/change-password", None, Some(json!({
+		"id": user_id,
+		"old_password": "guess",
+		"new_password": "new-password"
+	}))).await;
+
+	let unknown_user = app.request(Method::POST, "/api/auth/change-password", None, Some(json!({
+		"id": user_id + 100,
+		"old_password": "old-password",
+		"new_password": "new-password"
+	}))).await;
+
+	assert_eq!(wrong_old.status, StatusCode::BAD_REQUEST);
+	assert_eq!(unknown_user.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn tokens_carry_only_standard_claims_and_the_role() {
+	let app = TestApp::spawn().await;
+	let id = app.create_user(ADMIN_USERNAME, ADMIN_PASSWORD, "admin").await;
+
+	let token = app.login_as(ADMIN_USERNAME, ADMIN_PASSWORD).await.json()["token"].as_str().unwrap().to_owned();
+	let payload: Value = app.state.jwt.decode(&token, &app.state.config.jwt.audience).unwrap();
+
+	let mut names: Vec<&str> = payload.as_object().unwrap().keys().map(|d| d.as_str()).collect();
+	names.sort();
+
+	assert_eq!(names, ["aud", "exp", "iat", "iss", "jti", "role", "sid", "sub", "token_version"]);
+	assert_eq!(payload["sub"], id.to_string());
+	assert_eq!(payload["role"], "admin");
+
+	let other = app.login_as(ADMIN_USERNAME, ADMIN_PASSWORD).await.json()["token"].as_str().unwrap().to_owned();
+
+	assert_ne!(claims(&app, &token).jti, claims(&app, &other).jti);
+}
+
+#[tokio::test]
+async fn me_returns_the_current_profile() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+
+	let response = app.get("/api/auth/me", &token).await;
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(response.json()["data"]["username"], ADMIN_USERNAME);
+	assert_eq!(response.json()["data"]["password"], "");
+
+	let id = claims(&app, &token).sub;
+	app.put(&format!("/api/user/{id}"), &token, json!({ "full_name": "Renamed Admin" })).await;
+
+	assert_eq!(app.get("/api/auth/me", &token).await.json()["data"]["full_name"], "Renamed Admin");
+	assert_eq!(app.request(Method::GET, "/api/auth/me", None, None).await.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn role_changes_revoke_existing_tokens() {
+	let app = TestApp::spawn().await;
+	let admin_token = app.login().await;
+	let id = app.create_user("supervisor", "supervisor-password", "admin").await;
+	let token = app.login_as("supervisor", "supervisor-password").await.json()["token"].as_str().unwrap().to_owned();
+
+	let unlock = json!({ "username": "kasir" });
+
+	assert_eq!(app.post("/api/auth/unlock", &token, unlock.clone()).await.status, StatusCode::OK);
+
+	app.put(&format!("/api/user/{id}"), &admin_token, json!({ "role": "cashier" })).await;
+
+	assert_eq!(app.post("/api/auth/unlock", &token, unlock).await.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn tokens_for_another_issuer_or_audience_are_rejected() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+
+	let mut foreign_issuer = claims(&app, &token);
+	foreign_issuer.iss = "someone-else".to_owned();
+
+	let mut foreign_audience = claims(&app, &token);
+	foreign_audience.aud = "another-api".to_owned();
+
+	for forged in [foreign_issuer, foreign_audience] {
+		let forged = app.state.jwt.encode(&forged).unwrap();
+
+		assert_eq!(app.get("/api/category", &forged).await.status, StatusCode::UNAUTHORIZED);
+	}
+
+	assert_eq!(app.get("/api/category", &token).await.status, StatusCode::OK);
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use common::TestApp;
+
+#[tokio::test]
+async fn create_and_list_categories_sorted_by_name() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+
+	for name in ["Snacks", "Drinks", "Bakery"] {
+		let response = app.post("/api/category", &token, json!({ "name": name })).await;
+
+		assert_eq!(response.status, StatusCode::ACCEPTED);
+		assert_eq!(response.json()["success"], true);
+	}
+
+	let response = app.get("/api/category", &token).await;
+	let names: Vec<String> = response.json().as_array().unwrap().iter()
+	.map(|d| d["name"].as_str().unwrap().to_owned())
+	.collect();
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(names, ["Bakery", "Drinks", "Snacks"]);
+}
+
+#[tokio::test]
+async fn find_first_returns_category_or_not_found() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+	let id = app.create_category(&token, "Drinks").await;
+
+	let found = app.get(&format!("/api/category/{id}"), &token).await;
+	let missing = app.get(&format!("/api/category/{}", id + 100), &token).await;
+
+	assert_eq!(found.status, StatusCode::OK);
+	assert_eq!(found.json()["name"], "Drinks");
+	assert_eq!(missing.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn update_renames_category() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+	let id = app.create_category(&token, "Drinks").await;
+
+	let response = app.put(&format!("/api/category/{id}"), &token, json!({ "name": "Beverages" })).await;
+	let missing = app.put(&format!("/api/category/{}", id + 100), &token, json!({ "name": "Nope" })).await;
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(app.get(&format!("/api/category/{id}"), &token).await.json()["name"], "Beverages");
+	assert_eq!(missing.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn delete_removes_category() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+	let id = app.create_category(&token, "Drinks").await;
+
+	let response = app.delete(&format!("/api/category/{id}"), &token).await;
+	let again = app.delete(&format!("/api/category/{id}"), &token).await;
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(again.status, StatusCode::NOT_FOUND);
+	assert_eq!(app.get(&format!("/api/category/{id}"), &token).await.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn search_paginate_matches_case_insensitively() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+
+	for i in 0..12 {
+		app.post("/api/category", &token, json!({ "name": format!("Drink {i:02}") })).await;
+	}
+
+	app.post("/api/category", &token, json!({ "name": "Bakery" })).await;
+
+	let first = app.post("/api/category/search-paginate", &token, json!({ "term": "drink", "page": 1 })).await.json();
+	let second = app.post("/api/category/search-paginate", &token, json!({ "term": "drink", "page": 2 })).await.json();
+
+	assert_eq!(first["paginate"]["count"], 12);
+	assert_eq!(first["data"].as_array().unwrap().len(), 10);
+	assert_eq!(first["data"][0]["name"], "Drink 00");
+	assert_eq!(second["data"].as_array().unwrap().len(), 2);
+	assert_eq!(second["paginate"]["current_page"], 2);
+}
+
+#[tokio::test]
+async fn search_paginate_treats_wildcards_literally() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+
+	app.post("/api/category", &token, json!({ "name": "100% Juice" })).await;
+	app.post("/api/category", &token, json!({ "name": "1000 Snacks" })).await;
+
+	let body = app.post("/api/category/search-paginate", &token, json!({ "term": "100%", "page": 1 })).await.json();
+
+	assert_eq!(body["paginate"]["count"], 1);
+	assert_eq!(body["data"][0]["name"], "100% Juice");
+}
@@ -0,0 +1,296 @@
+// Shared harness for the integration tests. Every `TestApp` gets its own
+// database and upload directory and drives the router in-process.
+//
+// Tests run against a temporary SQLite file by default. Set
+// `TEST_DATABASE_URL` to a Postgres URL to run them against a fresh schema
+// per test instead; the schema is dropped when the `TestApp` goes away.
+#![allow(dead_code)]
+
+use axum::{
+	body::{ Body, Bytes },
+	http::{ header, HeaderMap, Method, Request, StatusCode },
+	Router
+};
+use http_body_util::BodyExt;
+use migration::{ Migrator, MigratorTrait };
+use sea_orm::{ ConnectOptions, ConnectionTrait, Database, DatabaseConnection };
+use serde_json::{ json, Value };
+use tempfile::TempDir;
+use tower::ServiceExt;
+
+use rust_axum_seaorm::{ app, config::AppConfig, model::user_model::UserCreateBody, state::AppState };
+
+pub const ADMIN_USERNAME: &str = "admin";
+pub const ADMIN_PASSWORD: &str = "admin-password";
+
+const MULTIPART_BOUNDARY: &str = "----rust-axum-seaorm-test";
+
+pub struct TestApp {
+	pub router: Router,
+	pub state: AppState,
+	pub db: DatabaseConnection,
+	pub upload_dir: TempDir,
+	// (server URL, schema) of the per-test Postgres schema.
+	postgres: Option<(String, String)>
+}
+
+pub struct TestResponse {
+	pub status: StatusCode,
+	pub headers: HeaderMap,
+	pub body: Bytes
+}
+
+impl TestResponse {
+	pub fn json(&self) -> Value {
+		serde_json::from_slice(&self.body)
+		.unwrap_or_else(|e| panic!("response is not JSON ({e}): {}", String::from_utf8_lossy(&self.body)))
+	}
+}
+
+async fn connect(url: &str, schema: Option<&str>) -> DatabaseConnection {
+	let mut options = ConnectOptions::new(url.to_owned());
+	options.max_connections(5).sqlx_logging(false);
+
+	if let Some(schema) = schema {
+		options.set_schema_search_path(schema.to_owned());
+	}
+
+	Database::connect(options).await.expect("Failed to Connect to the test Database")
+}
+
+impl TestApp {
+	pub async fn spawn() -> Self {
+		Self::spawn_with(|_| {}).await
+	}
+
+	// Like `spawn`, with a hook to adjust the configuration before the state is built.
+	pub async fn spawn_with(configure: impl FnOnce(&mut AppConfig)) -> Self {
+		let upload_dir = tempfile::tempdir().expect("Failed to create upload directory");
+
+		let (db, url, postgres) = match std::env::var("TEST_DATABASE_URL").ok().filter(|d| !d.is_empty()) {
+			Some(url) => {
+				let schema = format!("test_{}", uuid::Uuid::new_v4().simple());
+
+				connect(&url, None).await
+				.execute_unprepared(&format!("CREATE SCHEMA \"{schema}\"")).await
+				.expect("Failed to create test schema");
+
+				(connect(&url, Some(&schema)).await, url.clone(), Some((url, schema)))
+			},
+			None => {
+				let url = format!("sqlite://{}/test.db?mode=rwc", upload_dir.path().display());
+
+				(connect(&url, None).await, url, None)
+			}
+		};
+
+		Migrator::up(&db, None).await.expect("Failed to run migrations");
+
+		let mut config = AppConfig::default();
+		config.database.url = url;
+		config.jwt.keys_dir = upload_dir.path().join("keys").display().to_string();
+		config.uploads.signing_secret = "test-signing-secret".to_owned();
+		config.uploads.root = upload_dir.path().join("uploads").display().to_string();
+
+		configure(&mut config);
+
+		let state = AppState::new(db.clone(), config);
+
+		Self {
+			router: app(state.clone()),
+			state,
+			db,
+			upload_dir,
+			postgres
+		}
+	}
+
+	pub fn is_postgres(&self) -> bool {
+		self.postgres.is_some()
+	}
+
+	pub async fn send(&self, request: Request<Body>) -> TestResponse {
+		let response = self.router.clone().oneshot(request).await.expect("Router failed");
+
+		let status = response.status();
+		let headers = response.headers().clone();
+		let body = response.into_body().collect().await.expect("Failed to read body").to_bytes();
+
+		TestResponse { status, headers, body }
+	}
+
+	pub async fn request(&self, method: Method, uri: &str, token: Option<&str>, body: Option<Value>) -> TestResponse {
+		let mut builder = Request::builder().method(method).uri(uri);
+
+		if let Some(token) = token {
+			builder = builder.header(header::AUTHORIZATION, format!("Bearer {token}"));
+		}
+
+		let request = match body {
+			Some(body) => builder
+			.header(header::CONTENT_TYPE, "application/json")
+			.body(Body::from(body.to_string())),
+			None => builder.body(Body::empty())
+		};
+
+		self.send(request.unwrap()).await
+	}
+
+	pub async fn get(&self, uri: &str, token: &str) -> TestResponse {
+		self.request(Method::GET, uri, Some(token), None).await
+	}
+
+	pub async fn post(&self, uri: &str, token: &str, body: Value) -> TestResponse {
+		self.request(Method::POST, uri, Some(token), Some(body)).await
+	}
+
+	pub async fn put(&self, uri: &str, token: &str, body: Value) -> TestResponse {
+		self.request(Method::PUT, uri, Some(token), Some(body)).await
+	}
+
+	pub async fn delete(&self, uri: &str, token: &str) -> TestResponse {
+		self.request(Method::DELETE, uri, Some(token), None).await
+	}
+
+	// Sends a multipart request. `fields` are written first, in order, followed
+	// by `files` as (field name, file name, contents).
+	pub async fn upload(
+		&self,
+		uri: &str,
+		token: &str,
+		fields: &[(&str, &str)],
+		files: &[(&str, &str, &[u8])]
+	) -> TestResponse {
+		let mut request = multipart_request(uri, fields, files);
+
+		request.headers_mut().insert(header::AUTHORIZATION, format!("Bearer {token}").parse().unwrap());
+
+		self.send(request).await
+	}
+
+	pub async fn create_user(&self, username: &str, password: &str, role: &str) -> i32 {
+		self.state.users.create(UserCreateBody {
+			username: username.to_owned(),
+			password: password.to_owned(),
+			full_name: format!("{username} full name"),
+			address: "Test Street 1".to_owned(),
+			phone_number: "0800000000".to_owned(),
+			role: role.to_owned(),
+			photo: String::new()
+		}).await.expect("Failed to create user").id
+	}
+
+	pub async fn login_as(&self, username: &str, password: &str) -> TestResponse {
+		self.request(
+			Method::POST,
+			"/api/auth/login",
+			None,
+			Some(json!({ "username": username, "password": password }))
+		).await
+	}
+
+	// Creates the admin user and returns a bearer token for it.
+	pub async fn login(&self) -> String {
+		self.create_user(ADMIN_USERNAME, ADMIN_PASSWORD, "admin").await;
+
+		let response = self.login_as(ADMIN_USERNAME, ADMIN_PASSWORD).await;
+
+		assert_eq!(response.status, StatusCode::ACCEPTED, "login failed: {:?}", response.body);
+
+		response.json()["token"].as_str().expect("login response has no token").to_owned()
+	}
+
+	pub async fn create_category(&self, token: &str, name: &str) -> i32 {
+		let response = self.post("/api/category", token, json!({ "name": name })).await;
+
+		assert_eq!(response.status, StatusCode::ACCEPTED);
+
+		let categories = self.get("/api/category", token).await.json();
+
+		categories.as_array().unwrap().iter()
+		.find(|d| d["name"] == name)
+		.and_then(|d| d["id"].as_i64())
+		.expect("created category is not listed") as i32
+	}
+
+	// Uploads distinct content to the product bucket; returns the stored file name.
+	pub async fn upload_product_file(&self, token: &str, name: &str) -> String {
+		let mut contents = b"\x89PNG\r\n\x1a\n".to_vec();
+		contents.extend_from_slice(name.as_bytes());
+
+		let response = self.upload("/api/files/product", token, &[], &[("file", name, &contents)]).await;
+
+		assert_eq!(response.status, StatusCode::OK);
+
+		response.json()["results"][0]["file"]["file_name"].as_str().unwrap().to_owned()
+	}
+
+	pub async fn create_product(&self, token: &str, category_id: i32, name: &str, image: &str) -> i32 {
+		let response = self.post("/api/product", token, json!({
+			"name": name,
+			"description": format!("{name} description"),
+			"purchase_price": 1000,
+			"selling_price": 1500,
+			"stock": 10,
+			"discount": 0,
+			"image": image,
+			"category_id": category_id
+		})).await;
+
+		assert_eq!(response.status, StatusCode::ACCEPTED);
+
+		let products = self.post("/api/product/search", token, json!({ "term": name, "page": 1 })).await.json();
+
+		products["data"].as_array().unwrap().iter()
+		.find(|d| d["name"] == name)
+		.and_then(|d| d["id"].as_i64())
+		.expect("created product is not listed") as i32
+	}
+}
+
+impl Drop for TestApp {
+	fn drop(&mut self) {
+		let Some((url, schema)) = self.postgres.take() else {
+			return;
+		};
+
+		// `Drop` can't await, so the schema is removed from a helper thread with its own runtime.
+		let _ = std::thread::spawn(move || {
+			let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+
+			runtime.block_on(async {
+				let db = connect(&url, None).await;
+				let _ = db.execute_unprepared(&format!("DROP SCHEMA IF EXISTS \"{schema}\" CASCADE")).await;
+				let _ = db.close().await;
+			});
+		}).join();
+	}
+}
+
+// An unauthenticated multipart POST; see `TestApp::upload`.
+pub fn multipart_request(uri: &str, fields: &[(&str, &str)], files: &[(&str, &str, &[u8])]) -> Request<Body> {
+	let mut body: Vec<u8> = Vec::new();
+
+	for (name, value) in fields {
+		body.extend_from_slice(format!(
+			"--{MULTIPART_BOUNDARY}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n"
+		).as_bytes());
+	}
+
+	for (name, file_name, contents) in files {
+		body.extend_from_slice(format!(
+			"--{MULTIPART_BOUNDARY}\r\nContent-Disposition: form-data; name=\"{name}\"; filename=\"{file_name}\"\r\nContent-Type: application/octet-stream\r\n\r\n"
+		).as_bytes());
+		body.extend_from_slice(contents);
+		body.extend_from_slice(b"\r\n");
+	}
+
+	body.extend_from_slice(format!("--{MULTIPART_BOUNDARY}--\r\n").as_bytes());
+
+	Request::builder()
+	.method(Method::POST)
+	.uri(uri)
+	.header(header::CONTENT_TYPE, format!("multipart/form-data; boundary={MULTIPART_BOUNDARY}"))
+	.body(Body::from(body))
+	.unwrap()
+}
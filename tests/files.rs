@@ -0,0 +1,382 @@
+mod common;
+
+use axum::http::{ header, Method, StatusCode };
+use sea_orm::ConnectionTrait;
+use serde_json::{ json, Value };
+
+use common::TestApp;
+use rust_axum_seaorm::utils::file_buckets::{ FileBucket, FileBuckets };
+
+const PNG: &[u8] = b"\x89PNG\r\n\x1a\nnot really a png";
+const OTHER_PNG: &[u8] = b"\x89PNG\r\n\x1a\nanother fake png";
+
+fn first_file(body: &Value) -> &Value {
+	&body["results"][0]["file"]
+}
+
+#[tokio::test]
+async fn upload_then_download_public_file() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+
+	let response = app.upload("/api/files/user", &token, &[], &[("file", "avatar.png", PNG)]).await;
+	let body = response.json();
+	let file = first_file(&body);
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(body["uploaded"], 1);
+	assert_eq!(file["content_type"], "image/png");
+	assert_eq!(file["duplicate"], false);
+	assert!(file["file_name"].as_str().unwrap().ends_with(".png"));
+
+	let download = app.request(Method::GET, file["url"].as_str().unwrap(), None, None).await;
+
+	assert_eq!(download.status, StatusCode::OK);
+	assert_eq!(download.headers[header::CONTENT_TYPE], "image/png");
+	assert_eq!(&download.body[..], PNG);
+}
+
+#[tokio::test]
+async fn svg_files_are_only_served_as_downloads() {
+	let app = TestApp::spawn_with(|config| {
+		config.uploads.buckets.push(FileBucket {
+			name: "icon".to_owned(),
+			directory: None,
+			allowed_extensions: vec!["png".to_owned(), "svg".to_owned()],
+			max_file_size: 1024,
+			default_file: None
+		});
+	}).await;
+	let token = app.login().await;
+
+	let svg = b"<svg xmlns=\"http://www.w3.org/2000/svg\"><script>alert(1)</script></svg>";
+	let body = app.upload("/api/files/icon", &token, &[], &[("file", "x.svg", svg), ("file", "y.png", PNG)]).await.json();
+
+	let icon = app.request(Method::GET, body["results"][0]["file"]["url"].as_str().unwrap(), None, None).await;
+	let image = app.request(Method::GET, body["results"][1]["file"]["url"].as_str().unwrap(), None, None).await;
+
+	assert_eq!(icon.headers[header::CONTENT_DISPOSITION], "attachment");
+	assert_eq!(image.headers[header::CONTENT_DISPOSITION], "inline");
+
+	for download in [icon, image] {
+		assert_eq!(download.headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+		assert_eq!(download.headers[header::CONTENT_SECURITY_POLICY], "sandbox");
+	}
+}
+
+#[tokio::test]
+async fn uploading_the_same_content_is_deduplicated() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+
+	let first = app.upload("/api/files/user", &token, &[], &[("file", "a.png", PNG)]).await.json();
+	let second = app.upload("/api/files/user", &token, &[], &[("file", "b.png", PNG)]).await.json();
+
+	assert_eq!(first_file(&second)["duplicate"], true);
+	assert_eq!(first_file(&first)["id"], first_file(&second)["id"]);
+}
+
+#[tokio::test]
+async fn duplicates_must_keep_the_visibility_of_the_stored_content() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+
+	app.upload("/api/files/user", &token, &[], &[("file", "a.png", PNG)]).await;
+	app.upload("/api/files/user", &token, &[("visibility", "private")], &[("file", "b.png", OTHER_PNG)]).await;
+
+	let private_copy = app.upload("/api/files/user", &token, &[("visibility", "private")], &[("file", "c.png", PNG)]).await;
+	let public_copy = app.upload("/api/files/user", &token, &[], &[("file", "d.png", OTHER_PNG)]).await;
+
+	for response in [private_copy, public_copy] {
+		let body = response.json();
+
+		assert_eq!(response.status, StatusCode::BAD_REQUEST);
+		assert_eq!(body["results"][0]["success"], false);
+		assert!(body["results"][0]["message"].as_str().unwrap().contains("different visibility"));
+		assert!(body["results"][0]["file"].is_null());
+	}
+}
+
+#[tokio::test]
+async fn batch_upload_reports_each_file() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+
+	let response = app.upload("/api/files/user", &token, &[], &[
+		("file", "a.png", PNG),
+		("file", "notes.exe", b"MZ"),
+		("file", "b.png", OTHER_PNG)
+	]).await;
+	let body = response.json();
+
+	assert_eq!(response.status, StatusCode::MULTI_STATUS);
+	assert_eq!(body["uploaded"], 2);
+	assert_eq!(body["failed"], 1);
+	assert_eq!(body["results"][1]["success"], false);
+	assert_eq!(body["results"][1]["original_name"], "notes.exe");
+}
+
+#[tokio::test]
+async fn upload_rejects_oversized_files_and_empty_requests() {
+	let app = TestApp::spawn_with(|config| {
+		for bucket in config.uploads.buckets.iter_mut() {
+			bucket.max_file_size = 8;
+		}
+	}).await;
+	let token = app.login().await;
+
+	let oversized = app.upload("/api/files/user", &token, &[], &[("file", "a.png", PNG)]).await;
+	let empty = app.upload("/api/files/user", &token, &[("note", "nothing")], &[]).await;
+
+	assert_eq!(oversized.status, StatusCode::BAD_REQUEST);
+	assert_eq!(oversized.json()["failed"], 1);
+	assert_eq!(empty.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn unknown_bucket_is_not_found() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+
+	let upload = app.upload("/api/files/nope", &token, &[], &[("file", "a.png", PNG)]).await;
+	let download = app.request(Method::GET, "/api/files/nope/image/a.png", None, None).await;
+
+	assert_eq!(upload.status, StatusCode::NOT_FOUND);
+	assert_eq!(download.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn missing_file_falls_back_to_the_bucket_placeholder() {
+	let app = TestApp::spawn().await;
+	let placeholder_dir = app.upload_dir.path().join("uploads/user");
+
+	std::fs::create_dir_all(&placeholder_dir).unwrap();
+	std::fs::write(placeholder_dir.join("default_user.png"), b"placeholder").unwrap();
+
+	let fallback = app.request(Method::GET, "/api/files/user/image/missing.png", None, None).await;
+	let hidden = app.request(Method::GET, "/api/files/user/image/.upload-x", None, None).await;
+
+	assert_eq!(fallback.status, StatusCode::OK);
+	assert_eq!(&fallback.body[..], b"placeholder");
+	assert_eq!(hidden.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn private_files_need_a_valid_signature() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+
+	let body = app.upload("/api/files/user", &token, &[("visibility", "private")], &[("file", "id-card.png", PNG)]).await.json();
+	let file = first_file(&body);
+	let file_name = file["file_name"].as_str().unwrap();
+
+	assert_eq!(file["is_private"], true);
+	assert!(file["url"].as_str().unwrap().contains("signature="));
+
+	let unsigned = app.request(Method::GET, &format!("/api/files/user/image/{file_name}"), None, None).await;
+	let tampered = app.request(
+		Method::GET,
+		&format!("/api/files/user/image/{file_name}?expires=9999999999&signature=00"),
+		None,
+		None
+	).await;
+
+	let signed = app.request(Method::POST, &format!("/api/files/user/sign/{file_name}"), Some(&token), None).await.json();
+	let download = app.request(Method::GET, signed["url"].as_str().unwrap(), None, None).await;
+
+	assert_eq!(unsigned.status, StatusCode::FORBIDDEN);
+	assert_eq!(tampered.status, StatusCode::FORBIDDEN);
+	assert_eq!(download.status, StatusCode::OK);
+	assert_eq!(&download.body[..], PNG);
+}
+
+#[tokio::test]
+async fn downloads_fail_closed_when_the_file_index_is_unavailable() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+
+	let body = app.upload("/api/files/user", &token, &[("visibility", "private")], &[("file", "a.png", PNG)]).await.json();
+	let file_name = first_file(&body)["file_name"].as_str().unwrap().to_owned();
+
+	app.db.execute_unprepared("DROP TABLE stored_file").await.unwrap();
+
+	let download = app.request(Method::GET, &format!("/api/files/user/image/{file_name}"), None, None).await;
+
+	assert_eq!(download.status, StatusCode::INTERNAL_SERVER_ERROR);
+	assert_ne!(&download.body[..], PNG);
+}
+
+#[tokio::test]
+async fn visibility_can_be_changed() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+
+	let body = app.upload("/api/files/user", &token, &[], &[("file", "a.png", PNG)]).await.json();
+	let file_name = first_file(&body)["file_name"].as_str().unwrap().to_owned();
+	let uri = format!("/api/files/user/image/{file_name}");
+
+	let response = app.put(&format!("/api/files/user/visibility/{file_name}"), &token, json!({ "is_private": true })).await;
+	let missing = app.put("/api/files/user/visibility/missing.png", &token, json!({ "is_private": true })).await;
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(missing.status, StatusCode::NOT_FOUND);
+	assert_eq!(app.request(Method::GET, &uri, None, None).await.status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn only_admins_sign_files_or_change_their_visibility() {
+	let app = TestApp::spawn().await;
+	let admin_token = app.login().await;
+	app.create_user("budi", "budi-password", "cashier").await;
+	let token = app.login_as("budi", "budi-password").await.json()["token"].as_str().unwrap().to_owned();
+
+	let body = app.upload("/api/files/user", &admin_token, &[("visibility", "private")], &[("file", "id-card.png", PNG)]).await.json();
+	let file_name = first_file(&body)["file_name"].as_str().unwrap().to_owned();
+
+	let signed = app.request(Method::POST, &format!("/api/files/user/sign/{file_name}"), Some(&token), None).await;
+	let changed = app.put(&format!("/api/files/user/visibility/{file_name}"), &token, json!({ "is_private": false })).await;
+
+	assert_eq!(signed.status, StatusCode::FORBIDDEN);
+	assert_eq!(changed.status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn shared_files_keep_their_visibility() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+	let budi = app.create_user("budi", "budi-password", "cashier").await;
+	let siti = app.create_user("siti", "siti-password", "cashier").await;
+
+	let body = app.upload("/api/files/user", &token, &[], &[("file", "a.png", PNG)]).await.json();
+	let file_name = first_file(&body)["file_name"].as_str().unwrap().to_owned();
+	let uri = format!("/api/files/user/visibility/{file_name}");
+
+	app.put(&format!("/api/user/{budi}"), &token, json!({ "photo": file_name })).await;
+
+	assert_eq!(app.put(&uri, &token, json!({ "is_private": false })).await.status, StatusCode::OK);
+
+	app.put(&format!("/api/user/{siti}"), &token, json!({ "photo": file_name })).await;
+
+	assert_eq!(app.put(&uri, &token, json!({ "is_private": true })).await.status, StatusCode::CONFLICT);
+	assert_eq!(app.request(Method::GET, &format!("/api/files/user/image/{file_name}"), None, None).await.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn buckets_sharing_a_directory_are_found() {
+	let app = TestApp::spawn().await;
+	let mut config = (*app.state.config).clone();
+	config.uploads.buckets.push(FileBucket {
+		name: "avatar".to_owned(),
+		directory: Some(format!("{}/user", config.uploads.root)),
+		allowed_extensions: Vec::new(),
+		max_file_size: 1024,
+		default_file: None
+	});
+
+	assert_eq!(FileBuckets::from_config(&config.uploads).shared_directory(), Some(format!("{}/user", config.uploads.root)));
+}
+
+#[tokio::test]
+async fn delete_removes_file_but_keeps_the_placeholder() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+
+	let body = app.upload("/api/files/user", &token, &[], &[("file", "a.png", PNG)]).await.json();
+	let file_name = first_file(&body)["file_name"].as_str().unwrap().to_owned();
+
+	let response = app.delete(&format!("/api/files/user/delete/{file_name}"), &token).await;
+	let again = app.delete(&format!("/api/files/user/delete/{file_name}"), &token).await;
+	let placeholder = app.delete("/api/files/user/delete/default_user.png", &token).await;
+	let download = app.request(Method::GET, &format!("/api/files/user/image/{file_name}"), None, None).await;
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(again.status, StatusCode::NOT_FOUND);
+	assert_eq!(placeholder.status, StatusCode::NOT_FOUND);
+	assert_eq!(download.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn files_in_use_by_products_are_not_deleted() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+	let category_id = app.create_category(&token, "Drinks").await;
+	let product_id = app.create_product(&token, category_id, "Tea", "").await;
+
+	let body = app.upload("/api/files/product", &token, &[("product_id", &product_id.to_string())], &[("file", "tea.png", PNG)]).await.json();
+	let file_name = first_file(&body)["file_name"].as_str().unwrap().to_owned();
+	let image_id = body["results"][0]["image"]["id"].as_i64().unwrap();
+
+	let in_use = app.delete(&format!("/api/files/product/delete/{file_name}"), &token).await;
+	let download = app.request(Method::GET, &format!("/api/files/product/image/{file_name}"), None, None).await;
+
+	assert_eq!(in_use.status, StatusCode::CONFLICT);
+	assert_eq!(&download.body[..], PNG);
+
+	app.delete(&format!("/api/product/{product_id}/images/{image_id}"), &token).await;
+
+	assert_eq!(app.delete(&format!("/api/files/product/delete/{file_name}"), &token).await.status, StatusCode::OK);
+	assert!(!app.upload_dir.path().join("product").join(&file_name).exists());
+}
+
+#[tokio::test]
+async fn photos_shared_by_deduplication_stay_while_another_user_has_them() {
+	let app = TestApp::spawn().await;
+	let admin_token = app.login().await;
+	let siti = app.create_user("siti", "siti-password", "cashier").await;
+	let budi = app.create_user("budi", "budi-password", "cashier").await;
+
+	let mut file_names = Vec::new();
+
+	for (id, username) in [(siti, "siti"), (budi, "budi")] {
+		let token = app.login_as(username, &format!("{username}-password")).await.json()["token"].as_str().unwrap().to_owned();
+		let body = app.upload("/api/files/user", &token, &[], &[("file", "me.png", PNG)]).await.json();
+		let file_name = first_file(&body)["file_name"].as_str().unwrap().to_owned();
+
+		app.put(&format!("/api/user/{id}"), &admin_token, json!({ "photo": file_name })).await;
+		file_names.push(file_name);
+	}
+
+	assert_eq!(file_names[0], file_names[1]);
+
+	let file_name = &file_names[0];
+
+	// Siti drops her photo; Budi's is the same object.
+	app.put(&format!("/api/user/{siti}"), &admin_token, json!({ "photo": "" })).await;
+
+	let in_use = app.delete(&format!("/api/files/user/delete/{file_name}"), &admin_token).await;
+	let download = app.request(Method::GET, &format!("/api/files/user/image/{file_name}"), None, None).await;
+
+	assert_eq!(in_use.status, StatusCode::CONFLICT);
+	assert_eq!(&download.body[..], PNG);
+
+	app.put(&format!("/api/user/{budi}"), &admin_token, json!({ "photo": "" })).await;
+
+	assert_eq!(app.delete(&format!("/api/files/user/delete/{file_name}"), &admin_token).await.status, StatusCode::OK);
+	assert!(!app.upload_dir.path().join("user").join(file_name).exists());
+}
+
+#[tokio::test]
+async fn product_uploads_join_the_gallery() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+	let category_id = app.create_category(&token, "Drinks").await;
+	let product_id = app.create_product(&token, category_id, "Tea", "").await;
+
+	let response = app.upload(
+		"/api/files/product",
+		&token,
+		&[("product_id", &product_id.to_string()), ("alt_text", "Tea cup")],
+		&[("file", "tea.png", PNG)]
+	).await;
+	let body = response.json();
+
+	let unknown = app.upload("/api/files/product", &token, &[("product_id", "9999")], &[("file", "x.png", OTHER_PNG)]).await;
+	let images = app.get(&format!("/api/product/{product_id}/images"), &token).await.json();
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(body["results"][0]["image"]["alt_text"], "Tea cup");
+	assert_eq!(body["results"][0]["image"]["is_primary"], true);
+	assert_eq!(unknown.status, StatusCode::BAD_REQUEST);
+	assert_eq!(images.as_array().unwrap().len(), 1);
+	assert_eq!(images[0]["file_name"], first_file(&body)["file_name"]);
+}
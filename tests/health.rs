@@ -0,0 +1,54 @@
+mod common;
+
+use axum::http::{ Method, StatusCode };
+use migration::{ Migrator, MigratorTrait };
+
+use common::TestApp;
+
+#[tokio::test]
+async fn liveness_needs_no_token() {
+	let app = TestApp::spawn().await;
+
+	let response = app.request(Method::GET, "/health/live", None, None).await;
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(response.json()["status"], "ok");
+}
+
+#[tokio::test]
+async fn ready_when_database_is_reachable_and_migrated() {
+	let app = TestApp::spawn().await;
+
+	let response = app.request(Method::GET, "/health/ready", None, None).await;
+	let body = response.json();
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(body["status"], "ready");
+	assert_eq!(body["database"], "ok");
+	assert_eq!(body["pending_migrations"], 0);
+}
+
+#[tokio::test]
+async fn not_ready_with_pending_migrations() {
+	let app = TestApp::spawn().await;
+	Migrator::down(&app.db, Some(1)).await.unwrap();
+
+	let response = app.request(Method::GET, "/health/ready", None, None).await;
+	let body = response.json();
+
+	assert_eq!(response.status, StatusCode::SERVICE_UNAVAILABLE);
+	assert_eq!(body["status"], "unavailable");
+	assert_eq!(body["pending_migrations"], 1);
+}
+
+#[tokio::test]
+async fn not_ready_once_shutdown_begins() {
+	let app = TestApp::spawn().await;
+	app.state.health.begin_shutdown();
+
+	let response = app.request(Method::GET, "/health/ready", None, None).await;
+
+	assert_eq!(response.status, StatusCode::SERVICE_UNAVAILABLE);
+	assert_eq!(response.json()["shutting_down"], true);
+	assert_eq!(app.request(Method::GET, "/health/live", None, None).await.status, StatusCode::OK);
+}
@@ -0,0 +1,205 @@
+mod common;
+
+use axum::http::{ header, Method, StatusCode };
+use jsonwebtoken::{ decode_header, jwk::{ AlgorithmParameters, JwkSet }, Algorithm, DecodingKey, EncodingKey, Header, Validation };
+use serde_json::Value;
+
+use common::TestApp;
+use rust_axum_seaorm::{ config::{ AppConfig, JwtAlgorithm, JwtConfig }, state::AppState, utils::jwt };
+
+async fn jwks(app: &TestApp) -> JwkSet {
+	let response = app.request(Method::GET, "/.well-known/jwks.json", None, None).await;
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(response.headers[header::CONTENT_TYPE], "application/json");
+
+	serde_json::from_slice(&response.body).unwrap()
+}
+
+// What another service verifying our tokens would check.
+fn validation(app: &TestApp, algorithm: Algorithm) -> Validation {
+	let mut validation = Validation::new(algorithm);
+	validation.set_audience(&[&app.state.config.jwt.audience]);
+	validation.set_issuer(&[&app.state.config.jwt.issuer]);
+
+	validation
+}
+
+// Rebuilds the app with an adjusted configuration, as a restart would.
+fn restart(app: &mut TestApp, configure: impl FnOnce(&mut AppConfig)) {
+	let mut config = (*app.state.config).clone();
+	configure(&mut config);
+
+	app.state = AppState::new(app.db.clone(), config);
+	app.router = rust_axum_seaorm::app(app.state.clone());
+}
+
+#[tokio::test]
+async fn tokens_verify_against_the_published_keys() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+
+	let header = decode_header(&token).unwrap();
+	let keys = jwks(&app).await;
+
+	assert_eq!(header.alg, Algorithm::EdDSA);
+	assert_eq!(keys.keys.len(), 1);
+
+	let jwk = keys.find(header.kid.as_deref().unwrap()).expect("the signing key is published");
+	let claims = jsonwebtoken::decode::<Value>(&token, &DecodingKey::from_jwk(jwk).unwrap(), &validation(&app, Algorithm::EdDSA))
+	.unwrap()
+	.claims;
+
+	assert_eq!(claims["role"], "admin");
+	// Only the public half goes out.
+	assert!(serde_json::to_value(jwk).unwrap().get("d").is_none());
+}
+
+#[tokio::test]
+async fn rotation_keeps_earlier_tokens_valid() {
+	let mut app = TestApp::spawn().await;
+	let old_token = app.login().await;
+
+	let rotated = jwt::rotate(&app.state.config.jwt).unwrap();
+
+	assert!(rotated.retired.is_empty());
+
+	restart(&mut app, |_| {});
+
+	let new_token = app.login().await;
+
+	assert_eq!(decode_header(&new_token).unwrap().kid.as_deref(), Some(rotated.kid.as_str()));
+	assert_ne!(decode_header(&old_token).unwrap().kid, decode_header(&new_token).unwrap().kid);
+	assert_eq!(jwks(&app).await.keys.len(), 2);
+	assert_eq!(app.get("/api/category", &old_token).await.status, StatusCode::OK);
+	assert_eq!(app.get("/api/category", &new_token).await.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn retired_keys_no_longer_verify() {
+	let mut app = TestApp::spawn_with(|config| {
+		config.jwt.max_keys = 1;
+	}).await;
+	let old_token = app.login().await;
+	let old_kid = decode_header(&old_token).unwrap().kid.unwrap();
+
+	let rotated = jwt::rotate(&app.state.config.jwt).unwrap();
+
+	assert_eq!(rotated.retired, vec![old_kid]);
+
+	restart(&mut app, |_| {});
+
+	assert_eq!(app.get("/api/category", &old_token).await.status, StatusCode::UNAUTHORIZED);
+	assert_eq!(app.get("/api/category", &app.login().await).await.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn rs256_keys_are_loaded_from_pem() {
+	let app = TestApp::spawn_with(|config| {
+		config.jwt.algorithm = JwtAlgorithm::RS256;
+
+		std::fs::create_dir_all(&config.jwt.keys_dir).unwrap();
+		std::fs::copy("tests/fixtures/jwt-rs256.pem", format!("{}/fixture.pem", config.jwt.keys_dir)).unwrap();
+	}).await;
+	let token = app.login().await;
+
+	let header = decode_header(&token).unwrap();
+	let keys = jwks(&app).await;
+
+	assert_eq!(header.alg, Algorithm::RS256);
+	assert_eq!(header.kid.as_deref(), Some("fixture"));
+	assert!(matches!(keys.keys[0].algorithm, AlgorithmParameters::RSA(_)));
+	assert!(jsonwebtoken::decode::<Value>(&token, &DecodingKey::from_jwk(&keys.keys[0]).unwrap(), &validation(&app, Algorithm::RS256)).is_ok());
+}
+
+#[tokio::test]
+async fn public_keys_cannot_be_used_as_hmac_secrets() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+
+	let kid = decode_header(&token).unwrap().kid;
+	let jwk = jwks(&app).await.keys.remove(0);
+	let public_key = match &jwk.algorithm {
+		AlgorithmParameters::OctetKeyPair(val) => val.x.clone(),
+		_ => unreachable!()
+	};
+
+	let claims = jsonwebtoken::decode::<Value>(&token, &DecodingKey::from_jwk(&jwk).unwrap(), &validation(&app, Algorithm::EdDSA))
+	.unwrap()
+	.claims;
+
+	let mut header = Header::new(Algorithm::HS256);
+	header.kid = kid;
+
+	let forged = jsonwebtoken::encode(&header, &claims, &EncodingKey::from_secret(public_key.as_bytes())).unwrap();
+
+	assert_eq!(app.get("/api/category", &forged).await.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn hs256_tokens_survive_the_switch_until_the_cutoff() {
+	let mut app = TestApp::spawn_with(|config| {
+		config.jwt.algorithm = JwtAlgorithm::HS256;
+		config.jwt.secret = "test-jwt-secret".to_owned();
+	}).await;
+	let legacy_token = app.login().await;
+
+	assert_eq!(decode_header(&legacy_token).unwrap().alg, Algorithm::HS256);
+	assert!(jwks(&app).await.keys.is_empty());
+
+	// Switching alone stops accepting the secret.
+	restart(&mut app, |config| config.jwt.algorithm = JwtAlgorithm::EdDSA);
+
+	assert_eq!(decode_header(&app.login().await).unwrap().alg, Algorithm::EdDSA);
+	assert_eq!(app.get("/api/category", &legacy_token).await.status, StatusCode::UNAUTHORIZED);
+
+	restart(&mut app, |config| config.jwt.accept_hs256_until = Some(chrono::Utc::now() + chrono::Duration::days(1)));
+
+	assert_eq!(app.get("/api/category", &legacy_token).await.status, StatusCode::OK);
+
+	restart(&mut app, |config| config.jwt.accept_hs256_until = Some(chrono::Utc::now() - chrono::Duration::seconds(1)));
+
+	assert_eq!(app.get("/api/category", &legacy_token).await.status, StatusCode::UNAUTHORIZED);
+}
+
+#[test]
+fn hs256_tokens_only_pass_as_access_tokens() {
+	let keys_dir = tempfile::tempdir().unwrap();
+	let config = JwtConfig {
+		secret: "test-jwt-secret".to_owned(),
+		accept_hs256_until: Some(chrono::Utc::now() + chrono::Duration::days(1)),
+		keys_dir: keys_dir.path().display().to_string(),
+		..JwtConfig::default()
+	};
+	let keys = jwt::JwtKeys::from_config(&config).unwrap();
+
+	let sign = |audience: &str| {
+		let claims = serde_json::json!({
+			"sub": "1",
+			"exp": chrono::Utc::now().timestamp() + 60,
+			"iss": config.issuer,
+			"aud": audience
+		});
+
+		jsonwebtoken::encode(&Header::new(Algorithm::HS256), &claims, &EncodingKey::from_secret(config.secret.as_bytes())).unwrap()
+	};
+
+	assert!(keys.decode::<Value>(&sign(&config.audience), &config.audience).is_ok());
+	// A 2FA challenge forged with the old secret gets nowhere.
+	assert!(keys.decode::<Value>(&sign("two-factor-challenge"), "two-factor-challenge").is_err());
+}
+
+#[test]
+fn replicas_booting_together_share_one_generated_key() {
+	let keys_dir = tempfile::tempdir().unwrap();
+	let config = JwtConfig { keys_dir: keys_dir.path().display().to_string(), ..JwtConfig::default() };
+
+	let replicas: Vec<_> = (0..4).map(|_| {
+		let config = config.clone();
+		std::thread::spawn(move || jwt::JwtKeys::from_config(&config).unwrap())
+	}).collect();
+
+	for replica in replicas {
+		assert_eq!(replica.join().unwrap().jwks().keys.len(), 1);
+	}
+}
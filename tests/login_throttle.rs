@@ -0,0 +1,219 @@
+mod common;
+
+use axum::{
+	body::Body,
+	http::{ header, Method, Request, StatusCode }
+};
+use sea_orm::{ ActiveModelTrait, ActiveValue::Set, ColumnTrait, EntityTrait, QueryFilter };
+use serde_json::json;
+
+use common::{ TestApp, TestResponse, ADMIN_PASSWORD, ADMIN_USERNAME };
+use entity::{ login_attempt, user };
+
+async fn login_from(app: &TestApp, ip: &str, username: &str, password: &str) -> TestResponse {
+	let request = Request::builder()
+	.method(Method::POST)
+	.uri("/api/auth/login")
+	.header(header::CONTENT_TYPE, "application/json")
+	.header("x-forwarded-for", format!("{ip}, 10.0.0.1"))
+	.body(Body::from(json!({ "username": username, "password": password }).to_string()))
+	.unwrap();
+
+	app.send(request).await
+}
+
+fn retry_after(response: &TestResponse) -> u64 {
+	response.headers[header::RETRY_AFTER].to_str().unwrap().parse().unwrap()
+}
+
+#[tokio::test]
+async fn backs_off_after_the_free_attempts() {
+	let app = TestApp::spawn_with(|config| {
+		config.login_throttle.free_attempts = 2;
+		config.login_throttle.backoff_base_seconds = 30;
+	}).await;
+	app.create_user(ADMIN_USERNAME, ADMIN_PASSWORD, "admin").await;
+
+	assert_eq!(app.login_as(ADMIN_USERNAME, "wrong").await.status, StatusCode::UNAUTHORIZED);
+	assert_eq!(app.login_as(ADMIN_USERNAME, "wrong").await.status, StatusCode::UNAUTHORIZED);
+
+	// Even the right password waits out the backoff.
+	let response = app.login_as(ADMIN_USERNAME, ADMIN_PASSWORD).await;
+
+	assert_eq!(response.status, StatusCode::TOO_MANY_REQUESTS);
+	assert!((1..=30).contains(&retry_after(&response)));
+}
+
+#[tokio::test]
+async fn locks_out_after_max_failures() {
+	let app = TestApp::spawn_with(|config| {
+		config.login_throttle.free_attempts = 100;
+		config.login_throttle.max_failures = 3;
+		config.login_throttle.lockout_seconds = 600;
+	}).await;
+	app.create_user(ADMIN_USERNAME, ADMIN_PASSWORD, "admin").await;
+
+	for _ in 0..3 {
+		assert_eq!(app.login_as(ADMIN_USERNAME, "wrong").await.status, StatusCode::UNAUTHORIZED);
+	}
+
+	let response = app.login_as(ADMIN_USERNAME, ADMIN_PASSWORD).await;
+
+	assert_eq!(response.status, StatusCode::TOO_MANY_REQUESTS);
+	assert!(retry_after(&response) > 590);
+}
+
+#[tokio::test]
+async fn unknown_usernames_are_throttled_like_real_ones() {
+	let app = TestApp::spawn_with(|config| {
+		config.login_throttle.free_attempts = 100;
+		config.login_throttle.max_failures = 2;
+	}).await;
+	app.create_user(ADMIN_USERNAME, ADMIN_PASSWORD, "admin").await;
+
+	for username in [ADMIN_USERNAME, "ghost"] {
+		app.login_as(username, "wrong").await;
+		app.login_as(username, "wrong").await;
+	}
+
+	let known = app.login_as(ADMIN_USERNAME, "wrong").await;
+	let unknown = app.login_as("ghost", "wrong").await;
+
+	assert_eq!(known.status, StatusCode::TOO_MANY_REQUESTS);
+	assert_eq!(unknown.status, StatusCode::TOO_MANY_REQUESTS);
+	assert_eq!(known.json()["message"], unknown.json()["message"]);
+}
+
+#[tokio::test]
+async fn successful_login_resets_the_username_count() {
+	let app = TestApp::spawn_with(|config| {
+		config.login_throttle.free_attempts = 100;
+		config.login_throttle.max_failures = 3;
+	}).await;
+	app.create_user(ADMIN_USERNAME, ADMIN_PASSWORD, "admin").await;
+
+	app.login_as(ADMIN_USERNAME, "wrong").await;
+	app.login_as(ADMIN_USERNAME, "wrong").await;
+	assert_eq!(app.login_as(ADMIN_USERNAME, ADMIN_PASSWORD).await.status, StatusCode::ACCEPTED);
+	app.login_as(ADMIN_USERNAME, "wrong").await;
+	app.login_as(ADMIN_USERNAME, "wrong").await;
+
+	assert_eq!(app.login_as(ADMIN_USERNAME, ADMIN_PASSWORD).await.status, StatusCode::ACCEPTED);
+}
+
+#[tokio::test]
+async fn locks_out_an_ip_guessing_across_usernames() {
+	let app = TestApp::spawn_with(|config| {
+		config.server.trust_forwarded_for = true;
+		config.server.trusted_proxies = 2;
+		config.login_throttle.free_attempts = 100;
+		config.login_throttle.ip_max_failures = 2;
+	}).await;
+	app.create_user(ADMIN_USERNAME, ADMIN_PASSWORD, "admin").await;
+
+	login_from(&app, "203.0.113.7", "alice", "guess").await;
+	login_from(&app, "203.0.113.7", "bob", "guess").await;
+
+	let same_ip = login_from(&app, "203.0.113.7", ADMIN_USERNAME, ADMIN_PASSWORD).await;
+	let other_ip = login_from(&app, "198.51.100.2", ADMIN_USERNAME, ADMIN_PASSWORD).await;
+
+	assert_eq!(same_ip.status, StatusCode::TOO_MANY_REQUESTS);
+	assert_eq!(other_ip.status, StatusCode::ACCEPTED);
+}
+
+#[tokio::test]
+async fn spoofed_forwarded_for_entries_do_not_change_the_ip() {
+	let app = TestApp::spawn_with(|config| {
+		config.server.trust_forwarded_for = true;
+		config.login_throttle.free_attempts = 100;
+		config.login_throttle.ip_max_failures = 2;
+	}).await;
+	app.create_user(ADMIN_USERNAME, ADMIN_PASSWORD, "admin").await;
+
+	// The client makes up the left entry each time; the proxy appends the real one.
+	login_from(&app, "192.0.2.1", "alice", "guess").await;
+	login_from(&app, "192.0.2.2", "bob", "guess").await;
+
+	let spoofed = login_from(&app, "192.0.2.3", ADMIN_USERNAME, ADMIN_PASSWORD).await;
+
+	assert_eq!(spoofed.status, StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn admin_can_unlock_a_locked_username() {
+	let app = TestApp::spawn_with(|config| {
+		config.login_throttle.free_attempts = 100;
+		config.login_throttle.max_failures = 2;
+	}).await;
+	let admin_token = app.login().await;
+	app.create_user("cashier", "cashier-password", "cashier").await;
+
+	app.login_as("cashier", "wrong").await;
+	app.login_as("cashier", "wrong").await;
+	assert_eq!(app.login_as("cashier", "cashier-password").await.status, StatusCode::TOO_MANY_REQUESTS);
+
+	let response = app.post("/api/auth/unlock", &admin_token, json!({ "username": "cashier" })).await;
+
+	assert_eq!(response.status, StatusCode::OK);
+
+	let cashier = app.login_as("cashier", "cashier-password").await;
+	let cashier_token = cashier.json()["token"].as_str().unwrap().to_owned();
+
+	assert_eq!(cashier.status, StatusCode::ACCEPTED);
+
+	let forbidden = app.post("/api/auth/unlock", &cashier_token, json!({ "username": ADMIN_USERNAME })).await;
+	let empty = app.post("/api/auth/unlock", &admin_token, json!({})).await;
+
+	assert_eq!(forbidden.status, StatusCode::FORBIDDEN);
+	assert_eq!(empty.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn malformed_stored_hash_is_a_failed_login() {
+	let app = TestApp::spawn().await;
+	let id = app.create_user(ADMIN_USERNAME, ADMIN_PASSWORD, "admin").await;
+
+	let mut model: user::ActiveModel = user::Entity::find_by_id(id).one(&app.db).await.unwrap().unwrap().into();
+	model.password = Set("not-a-bcrypt-hash".to_owned());
+	model.update(&app.db).await.unwrap();
+
+	assert_eq!(app.login_as(ADMIN_USERNAME, ADMIN_PASSWORD).await.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn change_password_guesses_are_throttled() {
+	let app = TestApp::spawn_with(|config| {
+		config.login_throttle.free_attempts = 100;
+		config.login_throttle.max_failures = 2;
+	}).await;
+	let id = app.create_user("cashier", "old-password", "cashier").await;
+
+	let guess = |password: &'static str| app.request(Method::POST, "/api/auth/change-password", None, Some(json!({
+		"id": id,
+		"old_password": password,
+		"new_password": "new-password"
+	})));
+
+	assert_eq!(guess("one").await.status, StatusCode::BAD_REQUEST);
+	assert_eq!(guess("two").await.status, StatusCode::BAD_REQUEST);
+	assert_eq!(guess("old-password").await.status, StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn concurrent_failures_are_all_counted() {
+	let app = TestApp::spawn_with(|config| {
+		config.login_throttle.free_attempts = 100;
+		config.login_throttle.max_failures = 100;
+		config.login_throttle.ip_max_failures = 100;
+	}).await;
+
+	let failures = futures_util::future::join_all((0..10).map(|_| app.login_as("nobody", "wrong"))).await;
+
+	assert!(failures.iter().all(|d| d.status == StatusCode::UNAUTHORIZED));
+
+	let attempt = login_attempt::Entity::find()
+	.filter(login_attempt::Column::Key.eq("nobody"))
+	.one(&app.db).await.unwrap().unwrap();
+
+	assert_eq!(attempt.failures, 10);
+}
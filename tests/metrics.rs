@@ -0,0 +1,94 @@
+mod common;
+
+use axum::http::{ Method, StatusCode };
+
+use common::{ TestApp, ADMIN_USERNAME };
+
+const PNG: &[u8] = b"\x89PNG\r\n\x1a\nmetrics";
+
+// Metrics are off by default.
+async fn spawn() -> TestApp {
+	TestApp::spawn_with(|config| config.metrics.enabled = true).await
+}
+
+// The recorder is shared by every test in this binary, so assertions only
+// rely on counters having grown by at least what the test itself did.
+async fn scrape(app: &TestApp) -> String {
+	let response = app.request(Method::GET, "/metrics", None, None).await;
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert!(response.headers["content-type"].to_str().unwrap().starts_with("text/plain"));
+
+	String::from_utf8(response.body.to_vec()).unwrap()
+}
+
+fn metric_value(exposition: &str, series: &str) -> f64 {
+	exposition.lines()
+	.find_map(|d| d.strip_prefix(series).and_then(|rest| rest.strip_prefix(' ')))
+	.map(|d| d.parse().unwrap())
+	.unwrap_or(0.0)
+}
+
+#[tokio::test]
+async fn requests_are_counted_by_route_template_and_status() {
+	let app = spawn().await;
+	let token = app.login().await;
+
+	app.get("/api/category/4040", &token).await;
+	app.get("/api/category/4041", &token).await;
+
+	let exposition = scrape(&app).await;
+	let series = r#"http_requests_total{method="GET",route="/api/category/{id}",status="404"}"#;
+
+	assert!(metric_value(&exposition, series) >= 2.0, "{exposition}");
+	assert!(!exposition.contains("/api/category/4040"));
+	assert!(exposition.contains(r#"http_request_duration_seconds_bucket{method="GET",route="/api/category/{id}",status="404",le="0.005"}"#));
+	assert!(exposition.contains("http_requests_in_flight"));
+
+	// Pool gauges need the pool's driver feature, which the SQLite test database doesn't enable.
+	if app.is_postgres() {
+		assert!(exposition.contains(r#"db_pool_connections{state="in_use"}"#), "{exposition}");
+		assert!(exposition.contains("db_pool_max_connections"));
+	}
+}
+
+#[tokio::test]
+async fn login_outcomes_are_counted() {
+	let app = spawn().await;
+
+	let before = scrape(&app).await;
+	app.login().await;
+	app.login_as(ADMIN_USERNAME, "wrong-password").await;
+	let after = scrape(&app).await;
+
+	for outcome in ["success", "failure"] {
+		let series = format!(r#"auth_login_attempts_total{{outcome="{outcome}"}}"#);
+
+		assert!(metric_value(&after, &series) >= metric_value(&before, &series) + 1.0, "{after}");
+	}
+}
+
+#[tokio::test]
+async fn upload_bytes_are_counted_per_bucket() {
+	let app = spawn().await;
+	let token = app.login().await;
+
+	let series = r#"file_upload_bytes_total{bucket="product"}"#;
+	let before = metric_value(&scrape(&app).await, series);
+
+	let response = app.upload("/api/files/product", &token, &[], &[("file", "photo.png", PNG)]).await;
+	assert_eq!(response.status, StatusCode::OK);
+
+	let after = metric_value(&scrape(&app).await, series);
+
+	assert!(after >= before + PNG.len() as f64);
+}
+
+#[tokio::test]
+async fn metrics_are_off_by_default() {
+	let app = TestApp::spawn().await;
+
+	let response = app.request(Method::GET, "/metrics", None, None).await;
+
+	assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
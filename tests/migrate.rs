@@ -0,0 +1,67 @@
+mod common;
+
+use migration::{ Migrator, MigratorTrait };
+use sea_orm::ConnectionTrait;
+
+use rust_axum_seaorm::migrate::{ self, MigrateError };
+use common::TestApp;
+
+async fn pending(app: &TestApp) -> usize {
+	Migrator::get_pending_migrations(&app.db).await.unwrap().len()
+}
+
+#[tokio::test]
+async fn auto_migrate_applies_pending_migrations() {
+	let app = TestApp::spawn_with(|config| config.database.auto_migrate = true).await;
+	Migrator::down(&app.db, Some(2)).await.unwrap();
+
+	let applied = migrate::prepare(&app.db, &app.state.config.database).await.unwrap();
+
+	assert_eq!(applied.len(), 2);
+	assert_eq!(pending(&app).await, 0);
+}
+
+#[tokio::test]
+async fn pending_migrations_are_left_alone_by_default() {
+	let app = TestApp::spawn().await;
+	Migrator::down(&app.db, Some(1)).await.unwrap();
+
+	let applied = migrate::prepare(&app.db, &app.state.config.database).await.unwrap();
+
+	assert!(applied.is_empty());
+	assert_eq!(pending(&app).await, 1);
+}
+
+#[tokio::test]
+async fn refuses_a_schema_migrated_by_a_newer_build() {
+	let app = TestApp::spawn_with(|config| config.database.auto_migrate = true).await;
+
+	app.db.execute_unprepared(
+		"INSERT INTO seaql_migrations (version, applied_at) VALUES ('m29990101_000000_from_the_future', 0)"
+	).await.unwrap();
+
+	match migrate::prepare(&app.db, &app.state.config.database).await {
+		Err(MigrateError::SchemaAhead(names)) => assert_eq!(names, vec!["m29990101_000000_from_the_future"]),
+		other => panic!("expected SchemaAhead, got {other:?}")
+	}
+}
+
+// Replicas booting together: the lock lets one migrate while the other waits
+// and then finds nothing pending. Needs Postgres, SQLite takes no lock.
+#[tokio::test]
+async fn concurrent_startups_migrate_once() {
+	let app = TestApp::spawn_with(|config| config.database.auto_migrate = true).await;
+
+	if !app.is_postgres() {
+		return;
+	}
+
+	Migrator::down(&app.db, None).await.unwrap();
+
+	let config = &app.state.config.database;
+	let (first, second) = tokio::join!(migrate::prepare(&app.db, config), migrate::prepare(&app.db, config));
+	let applied = first.unwrap().len() + second.unwrap().len();
+
+	assert_eq!(applied, Migrator::migrations().len());
+	assert_eq!(pending(&app).await, 0);
+}
@@ -0,0 +1,335 @@
+mod common;
+
+use axum::{
+	body::Body,
+	extract::{ Form, Query, State },
+	http::{ header, Method, Request, StatusCode },
+	response::IntoResponse,
+	routing::{ get, post },
+	Json, Router
+};
+use base64::{ engine::general_purpose::URL_SAFE_NO_PAD, Engine };
+use serde_json::{ json, Value };
+use sha2::{ Digest, Sha256 };
+use std::{ collections::HashMap, sync::{ Arc, Mutex } };
+use tempfile::TempDir;
+
+use common::{ TestApp, TestResponse };
+use rust_axum_seaorm::{ config::{ JwtConfig, OidcConfig, OidcRoleMapping }, utils::jwt::JwtKeys };
+
+const CLIENT_ID: &str = "pos";
+const REDIRECT_URI: &str = "http://localhost/api/auth/oidc/callback";
+
+struct PendingCode {
+	code_challenge: String,
+	nonce: String,
+	claims: Value
+}
+
+// A minimal OpenID Connect provider on a local port: discovery, an
+// authorization endpoint that logs in whoever `identity` holds, a token
+// endpoint that checks the PKCE verifier, and its JWKS.
+#[derive(Clone)]
+struct MockIdp {
+	url: String,
+	keys: Arc<JwtKeys>,
+	identity: Arc<Mutex<Value>>,
+	// `aud` of the ID tokens it issues.
+	audience: Arc<Mutex<String>>,
+	codes: Arc<Mutex<HashMap<String, PendingCode>>>
+}
+
+impl MockIdp {
+	async fn start(keys_dir: &TempDir) -> Self {
+		let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let url = format!("http://{}", listener.local_addr().unwrap());
+
+		let keys = JwtKeys::from_config(&JwtConfig {
+			keys_dir: keys_dir.path().display().to_string(),
+			issuer: url.clone(),
+			audience: CLIENT_ID.to_owned(),
+			..JwtConfig::default()
+		}).unwrap();
+
+		let idp = Self {
+			url,
+			keys: Arc::new(keys),
+			identity: Arc::new(Mutex::new(Value::Null)),
+			audience: Arc::new(Mutex::new(CLIENT_ID.to_owned())),
+			codes: Arc::default()
+		};
+
+		let router = Router::new()
+		.route("/.well-known/openid-configuration", get(discovery))
+		.route("/authorize", get(authorize))
+		.route("/token", post(token))
+		.route("/jwks", get(|State(idp): State<MockIdp>| async move { Json(idp.keys.jwks().clone()) }))
+		.with_state(idp.clone());
+
+		tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+		idp
+	}
+}
+
+async fn discovery(State(idp): State<MockIdp>) -> Json<Value> {
+	Json(json!({
+		"issuer": idp.url,
+		"authorization_endpoint": format!("{}/authorize", idp.url),
+		"token_endpoint": format!("{}/token", idp.url),
+		"jwks_uri": format!("{}/jwks", idp.url)
+	}))
+}
+
+async fn authorize(State(idp): State<MockIdp>, Query(query): Query<HashMap<String, String>>) -> impl IntoResponse {
+	assert_eq!(query["response_type"], "code");
+	assert_eq!(query["client_id"], CLIENT_ID);
+	assert_eq!(query["redirect_uri"], REDIRECT_URI);
+	assert_eq!(query["code_challenge_method"], "S256");
+	assert!(query["scope"].split(' ').any(|d| d == "openid"));
+
+	let code = uuid::Uuid::new_v4().to_string();
+
+	idp.codes.lock().unwrap().insert(code.clone(), PendingCode {
+		code_challenge: query["code_challenge"].clone(),
+		nonce: query["nonce"].clone(),
+		claims: idp.identity.lock().unwrap().clone()
+	});
+
+	(StatusCode::FOUND, [(header::LOCATION, format!("{REDIRECT_URI}?code={code}&state={}", query["state"]))])
+}
+
+async fn token(State(idp): State<MockIdp>, Form(form): Form<HashMap<String, String>>) -> impl IntoResponse {
+	let pending = idp.codes.lock().unwrap().remove(&form["code"]);
+
+	let pending = match pending {
+		Some(val) if URL_SAFE_NO_PAD.encode(Sha256::digest(form["code_verifier"].as_bytes())) == val.code_challenge
+		&& form["redirect_uri"] == REDIRECT_URI => val,
+		_ => return (StatusCode::BAD_REQUEST, Json(json!({ "error": "invalid_grant" })))
+	};
+
+	let now = chrono::Utc::now().timestamp();
+	let mut claims = pending.claims;
+	claims["iss"] = json!(idp.url);
+	claims["aud"] = json!(*idp.audience.lock().unwrap());
+	claims["iat"] = json!(now);
+	claims["exp"] = json!(now + 300);
+	claims["nonce"] = json!(pending.nonce);
+
+	(StatusCode::OK, Json(json!({
+		"access_token": "opaque",
+		"token_type": "Bearer",
+		"id_token": idp.keys.encode(&claims).unwrap()
+	})))
+}
+
+fn oidc_config(idp: &MockIdp) -> OidcConfig {
+	OidcConfig {
+		enabled: true,
+		issuer: idp.url.clone(),
+		client_id: CLIENT_ID.to_owned(),
+		redirect_uri: REDIRECT_URI.to_owned(),
+		role_mapping: vec![
+			OidcRoleMapping { value: "pos-admins".to_owned(), role: "admin".to_owned() },
+			OidcRoleMapping { value: "pos-staff".to_owned(), role: "cashier".to_owned() }
+		],
+		..OidcConfig::default()
+	}
+}
+
+// Follows the browser through the provider and back; returns the callback
+// URI and the state cookie the browser got from `/login`.
+async fn authorize_at_idp(app: &TestApp, idp: &MockIdp, identity: Value) -> (String, String) {
+	*idp.identity.lock().unwrap() = identity;
+
+	let started = app.request(Method::GET, "/api/auth/oidc/login", None, None).await;
+
+	assert_eq!(started.status, StatusCode::SEE_OTHER);
+
+	let authorization_url = started.headers[header::LOCATION].to_str().unwrap().to_owned();
+
+	assert_eq!(started.json()["authorization_url"], authorization_url);
+
+	let set_cookie = started.headers[header::SET_COOKIE].to_str().unwrap();
+
+	assert!(set_cookie.contains("HttpOnly"));
+	assert!(set_cookie.contains("SameSite=Lax"));
+
+	let cookie = set_cookie.split(';').next().unwrap().to_owned();
+
+	let browser = reqwest::Client::builder().redirect(reqwest::redirect::Policy::none()).build().unwrap();
+	let back = browser.get(&authorization_url).send().await.unwrap();
+	let callback = back.headers()[header::LOCATION].to_str().unwrap().strip_prefix("http://localhost").unwrap().to_owned();
+
+	(callback, cookie)
+}
+
+async fn callback(app: &TestApp, uri: &str, cookie: Option<&str>) -> TestResponse {
+	let mut request = Request::builder().method(Method::GET).uri(uri);
+
+	if let Some(val) = cookie {
+		request = request.header(header::COOKIE, val);
+	}
+
+	app.send(request.body(Body::empty()).unwrap()).await
+}
+
+async fn oidc_login(app: &TestApp, idp: &MockIdp, identity: Value) -> TestResponse {
+	let (uri, cookie) = authorize_at_idp(app, idp, identity).await;
+
+	callback(app, &uri, Some(&cookie)).await
+}
+
+#[tokio::test]
+async fn provisioned_users_take_their_role_from_the_provider() {
+	let keys_dir = tempfile::tempdir().unwrap();
+	let idp = MockIdp::start(&keys_dir).await;
+	let app = TestApp::spawn_with(|config| {
+		config.oidc = OidcConfig { auto_provision: true, ..oidc_config(&idp) };
+	}).await;
+
+	let identity = json!({ "sub": "u-1", "preferred_username": "siti", "name": "Siti Aminah", "groups": ["staff", "pos-admins"] });
+	let response = oidc_login(&app, &idp, identity).await;
+
+	assert_eq!(response.status, StatusCode::ACCEPTED);
+
+	let body = response.json();
+	let token = body["token"].as_str().unwrap();
+	let me = app.get("/api/auth/me", token).await.json();
+
+	assert_eq!(me["data"]["username"], "siti");
+	assert_eq!(me["data"]["full_name"], "Siti Aminah");
+	assert_eq!(me["data"]["role"], "admin");
+
+	// The same subject logs in as the same user; no mapped group leaves the role alone.
+	let again = oidc_login(&app, &idp, json!({ "sub": "u-1", "preferred_username": "siti", "groups": [] })).await.json();
+
+	assert_eq!(again["data"]["id"], body["data"]["id"]);
+	assert_eq!(again["data"]["role"], "admin");
+
+	let demoted = oidc_login(&app, &idp, json!({ "sub": "u-1", "preferred_username": "siti", "groups": "pos-staff" })).await.json();
+
+	assert_eq!(demoted["data"]["role"], "cashier");
+	// Earlier tokens follow the new role too.
+	assert_eq!(app.get("/api/api-keys", token).await.status, StatusCode::FORBIDDEN);
+	// Local login keeps working next to it.
+	assert_eq!(app.get("/api/category", &app.login().await).await.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn identities_link_to_existing_users_by_verified_email() {
+	let keys_dir = tempfile::tempdir().unwrap();
+	let idp = MockIdp::start(&keys_dir).await;
+	let app = TestApp::spawn_with(|config| config.oidc = OidcConfig { link_existing_users: true, ..oidc_config(&idp) }).await;
+	let id = app.create_user("budi@example.com", "budi-password", "cashier").await;
+
+	let unknown = oidc_login(&app, &idp, json!({ "sub": "u-2", "preferred_username": "joko" })).await;
+
+	assert_eq!(unknown.status, StatusCode::FORBIDDEN);
+
+	// A username or an unverified address anyone could claim at the provider.
+	let claimed = oidc_login(&app, &idp, json!({ "sub": "u-4", "preferred_username": "budi@example.com" })).await;
+
+	assert_eq!(claimed.status, StatusCode::FORBIDDEN);
+
+	let unverified = json!({ "sub": "u-4", "preferred_username": "budi", "email": "budi@example.com", "email_verified": false });
+
+	assert_eq!(oidc_login(&app, &idp, unverified).await.status, StatusCode::FORBIDDEN);
+
+	let verified = json!({ "sub": "u-3", "preferred_username": "budi", "email": "budi@example.com", "email_verified": true });
+	let linked = oidc_login(&app, &idp, verified).await;
+
+	assert_eq!(linked.status, StatusCode::ACCEPTED);
+	assert_eq!(linked.json()["data"]["id"], id);
+
+	// Once linked, the subject decides, not the claims.
+	let renamed = oidc_login(&app, &idp, json!({ "sub": "u-3", "preferred_username": "budi.santoso" })).await;
+
+	assert_eq!(renamed.json()["data"]["id"], id);
+}
+
+#[tokio::test]
+async fn identities_are_not_linked_to_existing_users_by_default() {
+	let keys_dir = tempfile::tempdir().unwrap();
+	let idp = MockIdp::start(&keys_dir).await;
+	let app = TestApp::spawn_with(|config| config.oidc = OidcConfig { auto_provision: true, ..oidc_config(&idp) }).await;
+	app.create_user("budi@example.com", "budi-password", "cashier").await;
+
+	let verified = json!({ "sub": "u-3", "preferred_username": "budi@example.com", "email": "budi@example.com", "email_verified": true });
+
+	assert_eq!(oidc_login(&app, &idp, verified).await.status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn login_state_is_single_use() {
+	let keys_dir = tempfile::tempdir().unwrap();
+	let idp = MockIdp::start(&keys_dir).await;
+	let app = TestApp::spawn_with(|config| config.oidc = OidcConfig { auto_provision: true, ..oidc_config(&idp) }).await;
+
+	let (uri, cookie) = authorize_at_idp(&app, &idp, json!({ "sub": "u-1", "preferred_username": "siti" })).await;
+
+	assert_eq!(callback(&app, &uri, Some(&cookie)).await.status, StatusCode::ACCEPTED);
+	assert_eq!(callback(&app, &uri, Some(&cookie)).await.status, StatusCode::BAD_REQUEST);
+	assert_eq!(
+		callback(&app, "/api/auth/oidc/callback?code=abc&state=forged", Some("oidc_state=forged")).await.status,
+		StatusCode::BAD_REQUEST
+	);
+	assert_eq!(callback(&app, "/api/auth/oidc/callback?error=access_denied", None).await.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn logins_only_finish_in_the_browser_that_started_them() {
+	let keys_dir = tempfile::tempdir().unwrap();
+	let idp = MockIdp::start(&keys_dir).await;
+	let app = TestApp::spawn_with(|config| config.oidc = OidcConfig { auto_provision: true, ..oidc_config(&idp) }).await;
+
+	// An attacker's own callback link, opened in a victim's browser.
+	let (uri, cookie) = authorize_at_idp(&app, &idp, json!({ "sub": "u-6", "preferred_username": "mallory" })).await;
+	let (_, victim_cookie) = authorize_at_idp(&app, &idp, json!({ "sub": "u-1", "preferred_username": "siti" })).await;
+
+	assert_eq!(callback(&app, &uri, None).await.status, StatusCode::BAD_REQUEST);
+	assert_eq!(callback(&app, &uri, Some(&victim_cookie)).await.status, StatusCode::BAD_REQUEST);
+	// Neither used the state up.
+	assert_eq!(callback(&app, &uri, Some(&cookie)).await.status, StatusCode::ACCEPTED);
+}
+
+#[tokio::test]
+async fn roles_requiring_two_factor_answer_it_after_the_provider() {
+	let keys_dir = tempfile::tempdir().unwrap();
+	let idp = MockIdp::start(&keys_dir).await;
+	let app = TestApp::spawn_with(|config| {
+		config.oidc = OidcConfig { auto_provision: true, ..oidc_config(&idp) };
+		config.two_factor.required_roles = vec!["admin".to_owned()];
+	}).await;
+
+	let admin = oidc_login(&app, &idp, json!({ "sub": "u-1", "preferred_username": "siti", "groups": "pos-admins" })).await;
+
+	assert_eq!(admin.status, StatusCode::OK);
+	assert_eq!(admin.json()["enrollment_required"], true);
+	assert!(admin.json().get("token").is_none());
+
+	let cashier = oidc_login(&app, &idp, json!({ "sub": "u-2", "preferred_username": "budi", "groups": "pos-staff" })).await;
+
+	assert_eq!(cashier.status, StatusCode::ACCEPTED);
+}
+
+#[tokio::test]
+async fn id_tokens_for_another_client_are_rejected() {
+	let keys_dir = tempfile::tempdir().unwrap();
+	let idp = MockIdp::start(&keys_dir).await;
+	let app = TestApp::spawn_with(|config| config.oidc = OidcConfig { auto_provision: true, ..oidc_config(&idp) }).await;
+
+	*idp.audience.lock().unwrap() = "another-client".to_owned();
+
+	let response = oidc_login(&app, &idp, json!({ "sub": "u-1", "preferred_username": "siti" })).await;
+
+	assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+	assert!(response.json()["message"].as_str().unwrap().contains("INVALID ID TOKEN"));
+}
+
+#[tokio::test]
+async fn oidc_login_is_off_unless_configured() {
+	let app = TestApp::spawn().await;
+
+	assert_eq!(app.request(Method::GET, "/api/auth/oidc/login", None, None).await.status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use axum::http::{ Method, StatusCode };
+
+use common::TestApp;
+
+#[tokio::test]
+async fn openapi_document_lists_every_route() {
+	let app = TestApp::spawn().await;
+
+	let response = app.request(Method::GET, "/api/openapi.json", None, None).await;
+	let spec = response.json();
+	let paths = spec["paths"].as_object().unwrap();
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert!(spec["openapi"].as_str().unwrap().starts_with("3.1"));
+
+	for (path, method) in [
+		("/api/auth/login", "post"),
+		("/api/auth/authenticated", "post"),
+		("/api/auth/me", "get"),
+		("/api/auth/sessions", "get"),
+		("/api/auth/sessions", "delete"),
+		("/api/auth/sessions/{id}", "delete"),
+		("/api/auth/change-password", "post"),
+		("/api/auth/login/2fa", "post"),
+		("/api/auth/2fa/enroll", "post"),
+		("/api/auth/2fa/confirm", "post"),
+		("/api/auth/2fa/disable", "post"),
+		("/api/auth/2fa/recovery-codes", "post"),
+		("/api/auth/password-reset", "post"),
+		("/api/auth/forgot-password", "post"),
+		("/api/auth/reset-password", "post"),
+		("/api/auth/oidc/login", "get"),
+		("/api/auth/oidc/callback", "get"),
+		("/.well-known/jwks.json", "get"),
+		("/api/category", "get"),
+		("/api/category", "post"),
+		("/api/category/search-paginate", "post"),
+		("/api/category/{id}", "get"),
+		("/api/category/{id}", "put"),
+		("/api/category/{id}", "delete"),
+		("/api/product/search", "post"),
+		("/api/product", "post"),
+		("/api/product/{id}", "put"),
+		("/api/product/{id}", "delete"),
+		("/api/product/{id}/images", "get"),
+		("/api/product/{id}/images", "post"),
+		("/api/product/{id}/images/reorder", "put"),
+		("/api/product/{id}/images/{image_id}/primary", "put"),
+		("/api/product/{id}/images/{image_id}", "delete"),
+		("/api/user/many", "get"),
+		("/api/user", "post"),
+		("/api/user/{id}", "put"),
+		("/api/user/{id}", "delete"),
+		("/api/user/{id}/sessions", "delete"),
+		("/api/api-keys", "get"),
+		("/api/api-keys", "post"),
+		("/api/api-keys/{id}", "delete"),
+		("/api/files/{bucket}", "post"),
+		("/api/files/{bucket}/image/{filename}", "get"),
+		("/api/files/{bucket}/delete/{filename}", "delete"),
+		("/api/files/{bucket}/sign/{filename}", "post"),
+		("/api/files/{bucket}/visibility/{filename}", "put")
+	] {
+		assert!(paths.get(path).and_then(|d| d.get(method)).is_some(), "{method} {path} is undocumented");
+	}
+}
+
+#[tokio::test]
+async fn openapi_document_describes_bearer_auth_and_models() {
+	let app = TestApp::spawn().await;
+
+	let spec = app.request(Method::GET, "/api/openapi.json", None, None).await.json();
+	let schemas = &spec["components"]["schemas"];
+
+	assert_eq!(spec["components"]["securitySchemes"]["bearer_auth"]["scheme"], "bearer");
+	assert_eq!(spec["paths"]["/api/category"]["get"]["security"][0]["bearer_auth"], serde_json::json!([]));
+	assert_eq!(spec["components"]["securitySchemes"]["api_key"]["name"], "X-Api-Key");
+	assert_eq!(spec["paths"]["/api/category"]["post"]["security"][1]["api_key"], serde_json::json!(["category:write"]));
+	assert!(spec["paths"]["/api/auth/login"]["post"].get("security").is_none());
+	assert!(schemas["ProductPaginate"].is_object());
+	assert!(schemas["JwtClaims"].is_object());
+	assert_eq!(schemas["LoginBody"]["example"]["username"], "admin");
+}
+
+#[tokio::test]
+async fn redoc_page_is_served() {
+	let app = TestApp::spawn().await;
+
+	let response = app.request(Method::GET, "/api/docs", None, None).await;
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert!(String::from_utf8_lossy(&response.body).contains("Redoc.init"));
+}
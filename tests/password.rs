@@ -0,0 +1,110 @@
+mod common;
+
+use axum::http::{ Method, StatusCode };
+use sea_orm::{ ActiveModelTrait, ActiveValue::Set, EntityTrait };
+use serde_json::json;
+
+use common::{ TestApp, ADMIN_PASSWORD, ADMIN_USERNAME };
+use entity::user;
+use rust_axum_seaorm::config::PasswordAlgorithm;
+
+async fn stored_hash(app: &TestApp, id: i32) -> String {
+	user::Entity::find_by_id(id).one(&app.db).await.unwrap().unwrap().password
+}
+
+async fn change_password(app: &TestApp, id: i32, old_password: &str, new_password: &str) -> StatusCode {
+	app.request(Method::POST, "/api/auth/change-password", None, Some(json!({
+		"id": id,
+		"old_password": old_password,
+		"new_password": new_password
+	}))).await.status
+}
+
+#[tokio::test]
+async fn new_passwords_are_hashed_with_argon2id() {
+	let app = TestApp::spawn().await;
+	let id = app.create_user(ADMIN_USERNAME, ADMIN_PASSWORD, "admin").await;
+
+	assert!(stored_hash(&app, id).await.starts_with("$argon2id$v=19$m=19456,t=2,p=1$"));
+	assert_eq!(app.login_as(ADMIN_USERNAME, ADMIN_PASSWORD).await.status, StatusCode::ACCEPTED);
+}
+
+#[tokio::test]
+async fn short_and_breached_passwords_are_refused() {
+	let app = TestApp::spawn_with(|config| {
+		config.password.breached_list_path = "config/breached-passwords.txt".to_owned();
+	}).await;
+	let token = app.login().await;
+
+	for password in ["short", "Password123"] {
+		let response = app.post("/api/user", &token, json!({
+			"username": "kasir",
+			"password": password,
+			"full_name": "Kasir Satu",
+			"address": "Jl. Merdeka 1",
+			"phone_number": "0811111111",
+			"role": "cashier",
+			"photo": ""
+		})).await;
+
+		assert_eq!(response.status, StatusCode::BAD_REQUEST, "{password} was accepted");
+	}
+
+	let id = app.create_user("kasir", "kasir-password", "cashier").await;
+
+	assert_eq!(change_password(&app, id, "kasir-password", "letmein1").await, StatusCode::BAD_REQUEST);
+	// Updating a user doesn't take a password at all.
+	assert_eq!(app.put(&format!("/api/user/{id}"), &token, json!({ "password": "password1234" })).await.status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn recent_passwords_cannot_be_reused() {
+	let app = TestApp::spawn_with(|config| {
+		config.password.history_size = 3;
+	}).await;
+	let id = app.create_user("kasir", "first-password", "cashier").await;
+
+	assert_eq!(change_password(&app, id, "first-password", "first-password").await, StatusCode::BAD_REQUEST);
+	assert_eq!(change_password(&app, id, "first-password", "second-password").await, StatusCode::OK);
+	assert_eq!(change_password(&app, id, "second-password", "third-password").await, StatusCode::OK);
+	assert_eq!(change_password(&app, id, "third-password", "first-password").await, StatusCode::BAD_REQUEST);
+
+	// Only the last three count.
+	assert_eq!(change_password(&app, id, "third-password", "fourth-password").await, StatusCode::OK);
+	assert_eq!(change_password(&app, id, "fourth-password", "first-password").await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn legacy_bcrypt_hashes_are_upgraded_at_login() {
+	let app = TestApp::spawn().await;
+	let id = app.create_user(ADMIN_USERNAME, ADMIN_PASSWORD, "admin").await;
+
+	let mut model: user::ActiveModel = user::Entity::find_by_id(id).one(&app.db).await.unwrap().unwrap().into();
+	model.password = Set(bcrypt::hash(ADMIN_PASSWORD, 4).unwrap());
+	model.update(&app.db).await.unwrap();
+
+	assert_eq!(app.login_as(ADMIN_USERNAME, "wrong-password").await.status, StatusCode::UNAUTHORIZED);
+	assert!(stored_hash(&app, id).await.starts_with("$2"));
+
+	assert_eq!(app.login_as(ADMIN_USERNAME, ADMIN_PASSWORD).await.status, StatusCode::ACCEPTED);
+	assert!(stored_hash(&app, id).await.starts_with("$argon2id$"));
+	assert_eq!(app.login_as(ADMIN_USERNAME, ADMIN_PASSWORD).await.status, StatusCode::ACCEPTED);
+}
+
+#[tokio::test]
+async fn changed_parameters_are_applied_at_login() {
+	let app = TestApp::spawn_with(|config| {
+		config.password.algorithm = PasswordAlgorithm::Bcrypt;
+		config.password.bcrypt_cost = 5;
+	}).await;
+	let id = app.create_user(ADMIN_USERNAME, ADMIN_PASSWORD, "admin").await;
+
+	assert!(stored_hash(&app, id).await.starts_with("$2b$05$"));
+
+	let mut model: user::ActiveModel = user::Entity::find_by_id(id).one(&app.db).await.unwrap().unwrap().into();
+	model.password = Set(bcrypt::hash(ADMIN_PASSWORD, 4).unwrap());
+	model.update(&app.db).await.unwrap();
+
+	assert_eq!(app.login_as(ADMIN_USERNAME, ADMIN_PASSWORD).await.status, StatusCode::ACCEPTED);
+	assert!(stored_hash(&app, id).await.starts_with("$2b$05$"));
+}
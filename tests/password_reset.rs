@@ -0,0 +1,180 @@
+mod common;
+
+use async_trait::async_trait;
+use axum::http::{ Method, StatusCode };
+use chrono::NaiveDateTime;
+use sea_orm::{ ActiveModelTrait, ActiveValue::Set, EntityTrait };
+use serde_json::json;
+use std::sync::{ Arc, Mutex };
+
+use common::{ TestApp, TestResponse };
+use entity::password_reset_token;
+use rust_axum_seaorm::{
+	model::user_model::UserData,
+	service::ServiceError,
+	utils::password_reset_notifier::PasswordResetNotifier
+};
+
+async fn issue(app: &TestApp, admin_token: &str, user_id: i32) -> TestResponse {
+	app.post("/api/auth/password-reset", admin_token, json!({ "user_id": user_id })).await
+}
+
+async fn reset(app: &TestApp, token: &str, new_password: &str) -> StatusCode {
+	app.request(
+		Method::POST,
+		"/api/auth/reset-password",
+		None,
+		Some(json!({ "token": token, "new_password": new_password }))
+	).await.status
+}
+
+async fn login_token(app: &TestApp, username: &str, password: &str) -> String {
+	app.login_as(username, password).await.json()["token"].as_str().unwrap().to_owned()
+}
+
+#[tokio::test]
+async fn reset_replaces_the_password_and_revokes_sessions() {
+	let app = TestApp::spawn().await;
+	let admin_token = app.login().await;
+	let id = app.create_user("kasir", "kasir-password", "cashier").await;
+	let old_session = login_token(&app, "kasir", "kasir-password").await;
+
+	let response = issue(&app, &admin_token, id).await;
+
+	assert_eq!(response.status, StatusCode::CREATED);
+
+	let token = response.json()["token"].as_str().unwrap().to_owned();
+
+	assert_eq!(reset(&app, &token, "brand-new-password").await, StatusCode::OK);
+
+	assert_eq!(app.get("/api/category", &old_session).await.status, StatusCode::UNAUTHORIZED);
+	assert_eq!(
+		app.request(Method::POST, "/api/auth/authenticated", Some(&old_session), None).await.status,
+		StatusCode::UNAUTHORIZED
+	);
+
+	assert_eq!(app.login_as("kasir", "kasir-password").await.status, StatusCode::UNAUTHORIZED);
+
+	let new_session = login_token(&app, "kasir", "brand-new-password").await;
+
+	assert_eq!(app.get("/api/category", &new_session).await.status, StatusCode::OK);
+	// Other users keep their sessions.
+	assert_eq!(app.get("/api/category", &admin_token).await.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn tokens_are_single_use() {
+	let app = TestApp::spawn().await;
+	let admin_token = app.login().await;
+	let id = app.create_user("kasir", "kasir-password", "cashier").await;
+
+	let first = issue(&app, &admin_token, id).await.json()["token"].as_str().unwrap().to_owned();
+	let second = issue(&app, &admin_token, id).await.json()["token"].as_str().unwrap().to_owned();
+
+	assert_eq!(reset(&app, &first, "brand-new-password").await, StatusCode::OK);
+	assert_eq!(reset(&app, &first, "another-new-password").await, StatusCode::BAD_REQUEST);
+	// Using one token retires the others too.
+	assert_eq!(reset(&app, &second, "another-new-password").await, StatusCode::BAD_REQUEST);
+	assert_eq!(reset(&app, "not-a-token", "another-new-password").await, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn rejected_passwords_do_not_use_up_the_token() {
+	let app = TestApp::spawn().await;
+	let admin_token = app.login().await;
+	let id = app.create_user("kasir", "kasir-password", "cashier").await;
+
+	let token = issue(&app, &admin_token, id).await.json()["token"].as_str().unwrap().to_owned();
+
+	assert_eq!(reset(&app, &token, "short").await, StatusCode::BAD_REQUEST);
+	assert_eq!(reset(&app, &token, "kasir-password").await, StatusCode::BAD_REQUEST);
+	assert_eq!(reset(&app, &token, "brand-new-password").await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn expired_tokens_are_rejected() {
+	let app = TestApp::spawn().await;
+	let admin_token = app.login().await;
+	let id = app.create_user("kasir", "kasir-password", "cashier").await;
+
+	let token = issue(&app, &admin_token, id).await.json()["token"].as_str().unwrap().to_owned();
+
+	let mut model: password_reset_token::ActiveModel = password_reset_token::Entity::find().one(&app.db).await.unwrap().unwrap().into();
+	model.expires_at = Set(chrono::Utc::now().naive_utc() - chrono::Duration::minutes(1));
+	model.update(&app.db).await.unwrap();
+
+	assert_eq!(reset(&app, &token, "brand-new-password").await, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn only_admins_issue_tokens() {
+	let app = TestApp::spawn().await;
+	let admin_token = app.login().await;
+	let id = app.create_user("kasir", "kasir-password", "cashier").await;
+	let cashier_token = login_token(&app, "kasir", "kasir-password").await;
+
+	assert_eq!(issue(&app, &cashier_token, id).await.status, StatusCode::FORBIDDEN);
+	assert_eq!(issue(&app, &admin_token, 9999).await.status, StatusCode::NOT_FOUND);
+}
+
+#[derive(Default)]
+struct Outbox {
+	sent: Mutex<Vec<(String, String)>>
+}
+
+#[async_trait]
+impl PasswordResetNotifier for Outbox {
+	async fn send(&self, user: &UserData, token: &str, _expires_at: NaiveDateTime) -> Result<(), ServiceError> {
+		self.sent.lock().unwrap().push((user.username.clone(), token.to_owned()));
+		Ok(())
+	}
+}
+
+#[tokio::test]
+async fn forgot_password_goes_through_the_notifier() {
+	let mut app = TestApp::spawn().await;
+	app.create_user("kasir", "kasir-password", "cashier").await;
+
+	let outbox = Arc::new(Outbox::default());
+	app.router = rust_axum_seaorm::app(app.state.clone().with_password_reset_notifier(outbox.clone()));
+
+	let forgot = |username: &'static str| app.request(
+		Method::POST,
+		"/api/auth/forgot-password",
+		None,
+		Some(json!({ "username": username }))
+	);
+
+	let unknown = forgot("nobody").await;
+	let known = forgot("kasir").await;
+
+	assert_eq!(unknown.status, StatusCode::ACCEPTED);
+	assert_eq!(known.status, StatusCode::ACCEPTED);
+	assert_eq!(unknown.json()["message"], known.json()["message"]);
+
+	// Delivery runs in the background.
+	for _ in 0..50 {
+		if !outbox.sent.lock().unwrap().is_empty() {
+			break;
+		}
+
+		tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+	}
+
+	let (username, token) = outbox.sent.lock().unwrap().first().cloned().expect("no reset token was sent");
+
+	assert_eq!(username, "kasir");
+	assert_eq!(outbox.sent.lock().unwrap().len(), 1);
+	assert_eq!(reset(&app, &token, "brand-new-password").await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn forgot_password_without_a_notifier_sends_nothing() {
+	let app = TestApp::spawn().await;
+	app.create_user("kasir", "kasir-password", "cashier").await;
+
+	let response = app.request(Method::POST, "/api/auth/forgot-password", None, Some(json!({ "username": "kasir" }))).await;
+
+	assert_eq!(response.status, StatusCode::ACCEPTED);
+	assert!(password_reset_token::Entity::find().one(&app.db).await.unwrap().is_none());
+}
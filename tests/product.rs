@@ -0,0 +1,119 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use common::TestApp;
+
+#[tokio::test]
+async fn search_includes_category_and_gallery_summary() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+	let category_id = app.create_category(&token, "Drinks").await;
+
+	app.create_product(&token, category_id, "Tea", "").await;
+	let coffee_id = app.create_product(&token, category_id, "Coffee", "").await;
+
+	let coffee_png = app.upload_product_file(&token, "coffee.png").await;
+
+	app.post(&format!("/api/product/{coffee_id}/images"), &token, json!({ "file_name": coffee_png })).await;
+
+	let response = app.post("/api/product/search", &token, json!({ "term": "", "page": 1 })).await;
+	let body = response.json();
+	let products = body["data"].as_array().unwrap();
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(products.len(), 2);
+	assert_eq!(products[0]["name"], "Coffee");
+	assert_eq!(products[0]["category"]["name"], "Drinks");
+	assert_eq!(products[0]["image_count"], 1);
+	assert_eq!(products[0]["primary_image_url"], format!("/api/files/product/image/{coffee_png}"));
+	assert_eq!(products[1]["image_count"], 0);
+	assert!(products[1]["primary_image_url"].is_null());
+	assert_eq!(body["paginate"]["count"], 2);
+}
+
+#[tokio::test]
+async fn search_filters_by_name_case_insensitively() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+	let category_id = app.create_category(&token, "Drinks").await;
+
+	app.create_product(&token, category_id, "Tea", "").await;
+	app.create_product(&token, category_id, "Coffee", "").await;
+
+	let body = app.post("/api/product/search", &token, json!({ "term": "cOF", "page": 1 })).await.json();
+
+	assert_eq!(body["data"].as_array().unwrap().len(), 1);
+	assert_eq!(body["data"][0]["name"], "Coffee");
+}
+
+#[tokio::test]
+async fn search_falls_back_to_legacy_image() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+	let category_id = app.create_category(&token, "Drinks").await;
+
+	app.create_product(&token, category_id, "Tea", "tea.png").await;
+
+	let body = app.post("/api/product/search", &token, json!({ "term": "Tea", "page": 1 })).await.json();
+
+	assert_eq!(body["data"][0]["image"], "tea.png");
+	assert_eq!(body["data"][0]["primary_image_url"], "/api/files/product/image/tea.png");
+}
+
+#[tokio::test]
+async fn update_changes_only_given_fields() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+	let category_id = app.create_category(&token, "Drinks").await;
+	let id = app.create_product(&token, category_id, "Tea", "").await;
+
+	let response = app.put(&format!("/api/product/{id}"), &token, json!({ "stock": 3 })).await;
+	let body = app.post("/api/product/search", &token, json!({ "term": "Tea", "page": 1 })).await.json();
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(body["data"][0]["stock"], 3);
+	assert_eq!(body["data"][0]["selling_price"], 1500);
+}
+
+#[tokio::test]
+async fn update_unknown_product_returns_not_found() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+
+	let response = app.put("/api/product/9999", &token, json!({ "stock": 3 })).await;
+
+	assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn delete_removes_product() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+	let category_id = app.create_category(&token, "Drinks").await;
+	let id = app.create_product(&token, category_id, "Tea", "").await;
+
+	let response = app.delete(&format!("/api/product/{id}"), &token).await;
+	let again = app.delete(&format!("/api/product/{id}"), &token).await;
+	let body = app.post("/api/product/search", &token, json!({ "term": "", "page": 1 })).await.json();
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(again.status, StatusCode::NOT_FOUND);
+	assert!(body["data"].as_array().unwrap().is_empty());
+}
+
+// The only test in this binary that scrapes, so the process-wide gauge isn't raced.
+#[tokio::test]
+async fn low_stock_products_are_exposed_as_a_gauge() {
+	let app = TestApp::spawn_with(|config| config.metrics.low_stock_threshold = 20).await;
+	let token = app.login().await;
+	let category_id = app.create_category(&token, "Drinks").await;
+
+	app.create_product(&token, category_id, "Tea", "").await;
+	app.create_product(&token, category_id, "Coffee", "").await;
+
+	let exposition = app.state.metrics.render().await;
+
+	assert!(exposition.lines().any(|d| d == "products_low_stock 2"), "{exposition}");
+}
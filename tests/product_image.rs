@@ -0,0 +1,139 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{ json, Value };
+
+use common::TestApp;
+
+async fn gallery(app: &TestApp, token: &str, product_id: i32) -> Vec<Value> {
+	app.get(&format!("/api/product/{product_id}/images"), token).await.json().as_array().unwrap().clone()
+}
+
+async fn add_image(app: &TestApp, token: &str, product_id: i32, file_name: &str) -> i64 {
+	let stored = app.upload_product_file(token, file_name).await;
+	let response = app.post(
+		&format!("/api/product/{product_id}/images"),
+		token,
+		json!({ "file_name": stored, "alt_text": file_name })
+	).await;
+
+	assert_eq!(response.status, StatusCode::ACCEPTED);
+
+	response.json()["data"]["id"].as_i64().unwrap()
+}
+
+#[tokio::test]
+async fn first_image_becomes_primary_and_positions_increase() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+	let category_id = app.create_category(&token, "Drinks").await;
+	let product_id = app.create_product(&token, category_id, "Tea", "").await;
+
+	add_image(&app, &token, product_id, "a.png").await;
+	add_image(&app, &token, product_id, "b.png").await;
+
+	let images = gallery(&app, &token, product_id).await;
+	let file_name = images[0]["file_name"].as_str().unwrap();
+
+	assert_eq!(images.len(), 2);
+	assert_eq!(images[0]["alt_text"], "a.png");
+	assert_eq!(images[0]["is_primary"], true);
+	assert_eq!(images[0]["url"], format!("/api/files/product/image/{file_name}"));
+	assert_eq!(images[1]["position"], 1);
+	assert_eq!(images[1]["is_primary"], false);
+}
+
+#[tokio::test]
+async fn gallery_of_unknown_product_is_not_found() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+
+	let list = app.get("/api/product/9999/images", &token).await;
+	let add = app.post("/api/product/9999/images", &token, json!({ "file_name": "a.png" })).await;
+
+	assert_eq!(list.status, StatusCode::NOT_FOUND);
+	assert_eq!(add.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn only_uploaded_product_files_can_be_added() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+	let category_id = app.create_category(&token, "Drinks").await;
+	let product_id = app.create_product(&token, category_id, "Tea", "").await;
+	let uri = format!("/api/product/{product_id}/images");
+
+	let avatar = app.upload("/api/files/user", &token, &[], &[("file", "a.png", b"\x89PNG\r\n\x1a\navatar")]).await.json();
+
+	let unknown = app.post(&uri, &token, json!({ "file_name": "missing.png" })).await;
+	let other_bucket = app.post(&uri, &token, json!({ "file_name": avatar["results"][0]["file"]["file_name"] })).await;
+
+	assert_eq!(unknown.status, StatusCode::UNPROCESSABLE_ENTITY);
+	assert_eq!(other_bucket.status, StatusCode::UNPROCESSABLE_ENTITY);
+	assert!(gallery(&app, &token, product_id).await.is_empty());
+}
+
+#[tokio::test]
+async fn reorder_requires_every_image_once() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+	let category_id = app.create_category(&token, "Drinks").await;
+	let product_id = app.create_product(&token, category_id, "Tea", "").await;
+
+	let a = add_image(&app, &token, product_id, "a.png").await;
+	let b = add_image(&app, &token, product_id, "b.png").await;
+	let uri = format!("/api/product/{product_id}/images/reorder");
+
+	let partial = app.put(&uri, &token, json!({ "image_ids": [a] })).await;
+	let response = app.put(&uri, &token, json!({ "image_ids": [b, a] })).await;
+	let images = gallery(&app, &token, product_id).await;
+
+	assert_eq!(partial.status, StatusCode::BAD_REQUEST);
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(images[0]["id"], b);
+	assert_eq!(images[1]["id"], a);
+}
+
+#[tokio::test]
+async fn set_primary_moves_the_flag_and_syncs_product_image() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+	let category_id = app.create_category(&token, "Drinks").await;
+	let product_id = app.create_product(&token, category_id, "Tea", "").await;
+
+	add_image(&app, &token, product_id, "a.png").await;
+	let b = add_image(&app, &token, product_id, "b.png").await;
+
+	let response = app.put(&format!("/api/product/{product_id}/images/{b}/primary"), &token, json!({})).await;
+	let missing = app.put(&format!("/api/product/{product_id}/images/9999/primary"), &token, json!({})).await;
+
+	let primary: Vec<Value> = gallery(&app, &token, product_id).await.into_iter().filter(|d| d["is_primary"] == true).collect();
+	let product = app.post("/api/product/search", &token, json!({ "term": "Tea", "page": 1 })).await.json();
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(missing.status, StatusCode::NOT_FOUND);
+	assert_eq!(primary.len(), 1);
+	assert_eq!(primary[0]["id"], b);
+	assert_eq!(product["data"][0]["image"], primary[0]["file_name"]);
+}
+
+#[tokio::test]
+async fn deleting_the_primary_image_promotes_the_next_one() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+	let category_id = app.create_category(&token, "Drinks").await;
+	let product_id = app.create_product(&token, category_id, "Tea", "").await;
+
+	let a = add_image(&app, &token, product_id, "a.png").await;
+	let b = add_image(&app, &token, product_id, "b.png").await;
+
+	let response = app.delete(&format!("/api/product/{product_id}/images/{a}"), &token).await;
+	let again = app.delete(&format!("/api/product/{product_id}/images/{a}"), &token).await;
+	let images = gallery(&app, &token, product_id).await;
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(again.status, StatusCode::NOT_FOUND);
+	assert_eq!(images.len(), 1);
+	assert_eq!(images[0]["id"], b);
+	assert_eq!(images[0]["is_primary"], true);
+}
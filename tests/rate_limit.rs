@@ -0,0 +1,111 @@
+mod common;
+
+use async_trait::async_trait;
+use axum::http::{ header, Method, StatusCode };
+use serde_json::json;
+use std::sync::Arc;
+
+use common::{ TestApp, TestResponse, ADMIN_PASSWORD, ADMIN_USERNAME };
+use rust_axum_seaorm::{
+	config::RateLimitRule,
+	service::ServiceError,
+	utils::rate_limit::{ RateLimitDecision, RateLimitPolicy, RateLimitStore }
+};
+
+fn header_value(response: &TestResponse, name: &str) -> u64 {
+	response.headers[name].to_str().unwrap().parse().unwrap()
+}
+
+#[tokio::test]
+async fn login_burst_is_enforced_with_retry_after() {
+	let app = TestApp::spawn_with(|config| config.rate_limit.login = RateLimitRule { burst: 2, per_minute: 1 }).await;
+	app.create_user(ADMIN_USERNAME, ADMIN_PASSWORD, "admin").await;
+
+	let first = app.login_as(ADMIN_USERNAME, ADMIN_PASSWORD).await;
+	let second = app.login_as(ADMIN_USERNAME, ADMIN_PASSWORD).await;
+	let third = app.login_as(ADMIN_USERNAME, ADMIN_PASSWORD).await;
+
+	assert_eq!(first.status, StatusCode::ACCEPTED);
+	assert_eq!(header_value(&first, "ratelimit-limit"), 2);
+	assert_eq!(header_value(&first, "ratelimit-remaining"), 1);
+	assert_eq!(header_value(&second, "ratelimit-remaining"), 0);
+
+	assert_eq!(third.status, StatusCode::TOO_MANY_REQUESTS);
+	assert!((1..=60).contains(&header_value(&third, header::RETRY_AFTER.as_str())));
+	assert!(header_value(&third, "ratelimit-reset") > 60);
+	assert_eq!(third.json()["success"], false);
+}
+
+#[tokio::test]
+async fn authenticated_requests_are_counted_per_user() {
+	let app = TestApp::spawn_with(|config| config.rate_limit.writes = RateLimitRule { burst: 1, per_minute: 1 }).await;
+	let admin = app.login().await;
+	app.create_user("cashier", "cashier-password", "cashier").await;
+	let cashier = app.login_as("cashier", "cashier-password").await.json()["token"].as_str().unwrap().to_owned();
+
+	assert_eq!(app.post("/api/category", &admin, json!({ "name": "Drinks" })).await.status, StatusCode::ACCEPTED);
+	assert_eq!(app.post("/api/category", &admin, json!({ "name": "Snacks" })).await.status, StatusCode::TOO_MANY_REQUESTS);
+	assert_eq!(app.post("/api/category", &cashier, json!({ "name": "Snacks" })).await.status, StatusCode::ACCEPTED);
+}
+
+#[tokio::test]
+async fn writes_group_does_not_count_reads() {
+	let app = TestApp::spawn_with(|config| config.rate_limit.writes = RateLimitRule { burst: 1, per_minute: 1 }).await;
+	let token = app.login().await;
+
+	for _ in 0..3 {
+		let response = app.get("/api/category", &token).await;
+
+		assert_eq!(response.status, StatusCode::OK);
+		assert!(!response.headers.contains_key("ratelimit-limit"));
+	}
+}
+
+#[tokio::test]
+async fn groups_have_separate_budgets() {
+	let app = TestApp::spawn_with(|config| config.rate_limit.search = RateLimitRule { burst: 1, per_minute: 1 }).await;
+	let token = app.login().await;
+	let search = json!({ "term": "", "page": 1 });
+
+	assert_eq!(app.post("/api/category/search-paginate", &token, search.clone()).await.status, StatusCode::OK);
+	assert_eq!(app.post("/api/product/search", &token, search.clone()).await.status, StatusCode::TOO_MANY_REQUESTS);
+	assert_eq!(app.post("/api/category", &token, json!({ "name": "Drinks" })).await.status, StatusCode::ACCEPTED);
+}
+
+#[tokio::test]
+async fn disabled_rate_limiting_adds_no_headers() {
+	let app = TestApp::spawn_with(|config| {
+		config.rate_limit.enabled = false;
+		config.rate_limit.login = RateLimitRule { burst: 1, per_minute: 1 };
+	}).await;
+
+	app.login_as("nobody", "guess").await;
+	let response = app.login_as("nobody", "guess").await;
+
+	assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+	assert!(!response.headers.contains_key("ratelimit-limit"));
+}
+
+// Stands in for a shared store such as Redis.
+struct DenyAll;
+
+#[async_trait]
+impl RateLimitStore for DenyAll {
+	async fn acquire(&self, _key: &str, _policy: RateLimitPolicy) -> Result<RateLimitDecision, ServiceError> {
+		Ok(RateLimitDecision { allowed: false, remaining: 0, reset_seconds: 30, retry_after_seconds: 7 })
+	}
+}
+
+#[tokio::test]
+async fn store_is_pluggable() {
+	let mut app = TestApp::spawn().await;
+	app.router = rust_axum_seaorm::app(app.state.clone().with_rate_limit_store(Arc::new(DenyAll)));
+
+	let response = app.request(Method::POST, "/api/auth/login", None, Some(json!({
+		"username": ADMIN_USERNAME,
+		"password": ADMIN_PASSWORD
+	}))).await;
+
+	assert_eq!(response.status, StatusCode::TOO_MANY_REQUESTS);
+	assert_eq!(header_value(&response, header::RETRY_AFTER.as_str()), 7);
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use axum::{ body::Body, http::{ Method, Request, StatusCode } };
+
+use common::TestApp;
+
+#[tokio::test]
+async fn request_id_is_generated_and_echoed() {
+	let app = TestApp::spawn().await;
+
+	let response = app.request(Method::GET, "/api", None, None).await;
+	let request_id = response.headers["x-request-id"].to_str().unwrap();
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(request_id.len(), 36);
+}
+
+#[tokio::test]
+async fn client_request_id_is_propagated_into_error_bodies() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+
+	let response = app.send(
+		Request::builder()
+		.uri("/api/category/9999")
+		.header("authorization", format!("Bearer {token}"))
+		.header("x-request-id", "till-7-receipt-42")
+		.body(Body::empty())
+		.unwrap()
+	).await;
+	let body = response.json();
+
+	assert_eq!(response.status, StatusCode::NOT_FOUND);
+	assert_eq!(response.headers["x-request-id"], "till-7-receipt-42");
+	assert_eq!(body["request_id"], "till-7-receipt-42");
+	assert_eq!(body["success"], false);
+}
+
+#[tokio::test]
+async fn guard_rejections_carry_the_request_id() {
+	let app = TestApp::spawn().await;
+
+	let response = app.request(Method::GET, "/api/category", None, None).await;
+	let request_id = response.headers["x-request-id"].to_str().unwrap().to_owned();
+
+	assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+	assert_eq!(response.json()["request_id"], request_id);
+}
+
+#[tokio::test]
+async fn successful_bodies_are_left_alone() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+
+	let response = app.get("/api/category", &token).await;
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert!(response.json().is_array());
+}
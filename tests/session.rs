@@ -0,0 +1,120 @@
+mod common;
+
+use axum::{ body::Body, http::{ header, Method, Request, StatusCode } };
+use serde_json::{ json, Value };
+
+use common::{ TestApp, ADMIN_PASSWORD, ADMIN_USERNAME };
+
+async fn login_from(app: &TestApp, username: &str, password: &str, user_agent: &str) -> String {
+	let request = Request::builder()
+	.method(Method::POST)
+	.uri("/api/auth/login")
+	.header(header::CONTENT_TYPE, "application/json")
+	.header(header::USER_AGENT, user_agent)
+	.body(Body::from(json!({ "username": username, "password": password }).to_string()))
+	.unwrap();
+
+	let response = app.send(request).await;
+
+	assert_eq!(response.status, StatusCode::ACCEPTED);
+
+	response.json()["token"].as_str().unwrap().to_owned()
+}
+
+async fn sessions(app: &TestApp, token: &str) -> Vec<Value> {
+	let response = app.get("/api/auth/sessions", token).await;
+
+	assert_eq!(response.status, StatusCode::OK);
+
+	response.json().as_array().unwrap().clone()
+}
+
+#[tokio::test]
+async fn logins_are_listed_as_sessions() {
+	let app = TestApp::spawn().await;
+	app.create_user(ADMIN_USERNAME, ADMIN_PASSWORD, "admin").await;
+
+	let laptop = login_from(&app, ADMIN_USERNAME, ADMIN_PASSWORD, "Laptop Browser").await;
+	login_from(&app, ADMIN_USERNAME, ADMIN_PASSWORD, "Phone App").await;
+
+	let listed = sessions(&app, &laptop).await;
+
+	assert_eq!(listed.len(), 2);
+
+	let current: Vec<&Value> = listed.iter().filter(|d| d["current"] == true).collect();
+
+	assert_eq!(current.len(), 1);
+	assert_eq!(current[0]["user_agent"], "Laptop Browser");
+	assert!(listed.iter().any(|d| d["user_agent"] == "Phone App" && d["current"] == false));
+}
+
+#[tokio::test]
+async fn a_revoked_session_stops_its_tokens() {
+	let app = TestApp::spawn().await;
+	app.create_user(ADMIN_USERNAME, ADMIN_PASSWORD, "admin").await;
+
+	let laptop = login_from(&app, ADMIN_USERNAME, ADMIN_PASSWORD, "Laptop Browser").await;
+	let phone = login_from(&app, ADMIN_USERNAME, ADMIN_PASSWORD, "Phone App").await;
+
+	// A refreshed token belongs to the same session.
+	let refreshed = app.request(Method::POST, "/api/auth/authenticated", Some(&phone), None).await.json()["token"]
+	.as_str().unwrap().to_owned();
+
+	assert_eq!(sessions(&app, &laptop).await.len(), 2);
+
+	let phone_id = sessions(&app, &laptop).await.into_iter().find(|d| d["current"] == false).unwrap()["id"].as_i64().unwrap();
+
+	assert_eq!(app.delete(&format!("/api/auth/sessions/{phone_id}"), &laptop).await.status, StatusCode::OK);
+
+	assert_eq!(app.get("/api/category", &phone).await.status, StatusCode::UNAUTHORIZED);
+	assert_eq!(app.get("/api/category", &refreshed).await.status, StatusCode::UNAUTHORIZED);
+	assert_eq!(app.get("/api/category", &laptop).await.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn sessions_of_other_users_cannot_be_revoked() {
+	let app = TestApp::spawn().await;
+	let admin_token = app.login().await;
+	app.create_user("kasir", "kasir-password", "cashier").await;
+	let cashier_token = login_from(&app, "kasir", "kasir-password", "Till").await;
+
+	let admin_session = sessions(&app, &admin_token).await[0]["id"].as_i64().unwrap();
+
+	assert_eq!(app.delete(&format!("/api/auth/sessions/{admin_session}"), &cashier_token).await.status, StatusCode::NOT_FOUND);
+	assert_eq!(app.get("/api/category", &admin_token).await.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn revoking_all_can_spare_the_current_session() {
+	let app = TestApp::spawn().await;
+	app.create_user(ADMIN_USERNAME, ADMIN_PASSWORD, "admin").await;
+
+	let laptop = login_from(&app, ADMIN_USERNAME, ADMIN_PASSWORD, "Laptop Browser").await;
+	let phone = login_from(&app, ADMIN_USERNAME, ADMIN_PASSWORD, "Phone App").await;
+
+	assert_eq!(app.delete("/api/auth/sessions?except_current=true", &laptop).await.status, StatusCode::OK);
+	assert_eq!(app.get("/api/category", &phone).await.status, StatusCode::UNAUTHORIZED);
+	assert_eq!(sessions(&app, &laptop).await.len(), 1);
+
+	assert_eq!(app.delete("/api/auth/sessions", &laptop).await.status, StatusCode::OK);
+	assert_eq!(app.get("/api/category", &laptop).await.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn admins_can_end_every_session_of_a_user() {
+	let app = TestApp::spawn().await;
+	let admin_token = app.login().await;
+	let id = app.create_user("kasir", "kasir-password", "cashier").await;
+	let cashier_token = login_from(&app, "kasir", "kasir-password", "Till").await;
+
+	let admin_id = app.state.jwt.decode::<Value>(&admin_token, &app.state.config.jwt.audience).unwrap()["sub"]
+	.as_str().unwrap().to_owned();
+
+	assert_eq!(app.delete(&format!("/api/user/{admin_id}/sessions"), &cashier_token).await.status, StatusCode::FORBIDDEN);
+	assert_eq!(app.delete(&format!("/api/user/{id}/sessions"), &admin_token).await.status, StatusCode::OK);
+
+	assert_eq!(app.get("/api/category", &cashier_token).await.status, StatusCode::UNAUTHORIZED);
+	assert_eq!(app.get("/api/category", &admin_token).await.status, StatusCode::OK);
+	// Logging in again opens a new session.
+	assert_eq!(app.get("/api/category", &login_from(&app, "kasir", "kasir-password", "Till").await).await.status, StatusCode::OK);
+}
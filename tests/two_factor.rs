@@ -0,0 +1,245 @@
+mod common;
+
+use axum::http::{ Method, StatusCode };
+use sea_orm::{ ActiveModelTrait, ActiveValue::Set, EntityTrait };
+use serde_json::{ json, Value };
+
+use common::{ TestApp, ADMIN_PASSWORD, ADMIN_USERNAME };
+use entity::user;
+use rust_axum_seaorm::utils::totp;
+
+// The code of the step `offset` steps from now. Each successful check uses up
+// its step, so consecutive logins in a test take increasing offsets.
+fn code(secret: &str, offset: i64) -> String {
+	let step = totp::step_at(chrono::Utc::now().timestamp()) + offset;
+
+	totp::code_at(&totp::base32_decode(secret).unwrap(), step)
+}
+
+// Enrols the bearer and returns the secret and the confirmation response.
+async fn enroll(app: &TestApp, token: &str) -> (String, Value) {
+	let response = app.request(Method::POST, "/api/auth/2fa/enroll", Some(token), None).await;
+
+	assert_eq!(response.status, StatusCode::OK, "{:?}", response.body);
+
+	let secret = response.json()["secret"].as_str().unwrap().to_owned();
+	let confirmed = app.post("/api/auth/2fa/confirm", token, json!({ "code": code(&secret, -1) })).await;
+
+	assert_eq!(confirmed.status, StatusCode::OK, "{:?}", confirmed.body);
+
+	(secret, confirmed.json())
+}
+
+async fn challenge(app: &TestApp) -> String {
+	let response = app.login_as(ADMIN_USERNAME, ADMIN_PASSWORD).await;
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(response.json()["two_factor_required"], true);
+
+	response.json()["challenge_token"].as_str().unwrap().to_owned()
+}
+
+async fn verify_login(app: &TestApp, challenge_token: &str, code: &str) -> StatusCode {
+	app.request(
+		Method::POST,
+		"/api/auth/login/2fa",
+		None,
+		Some(json!({ "challenge_token": challenge_token, "code": code }))
+	).await.status
+}
+
+#[tokio::test]
+async fn enrolment_returns_an_otpauth_uri() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+
+	let response = app.request(Method::POST, "/api/auth/2fa/enroll", Some(&token), None).await;
+	let body = response.json();
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert!(body["otpauth_uri"].as_str().unwrap().starts_with("otpauth://totp/Rust%20Axum%20SeaORM:admin?secret="));
+	assert_eq!(totp::base32_decode(body["secret"].as_str().unwrap()).unwrap().len(), 20);
+}
+
+#[tokio::test]
+async fn login_needs_a_code_once_enabled() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+	let (secret, confirmed) = enroll(&app, &token).await;
+
+	assert_eq!(confirmed["recovery_codes"].as_array().unwrap().len(), 10);
+	assert!(confirmed.get("token").is_none());
+
+	let challenge_token = challenge(&app).await;
+
+	// The challenge is not a bearer token.
+	assert_ne!(app.get("/api/user/many", &challenge_token).await.status, StatusCode::OK);
+	assert_eq!(verify_login(&app, &challenge_token, "000000").await, StatusCode::UNAUTHORIZED);
+
+	let response = app.request(
+		Method::POST,
+		"/api/auth/login/2fa",
+		None,
+		Some(json!({ "challenge_token": challenge_token, "code": code(&secret, 0) }))
+	).await;
+
+	assert_eq!(response.status, StatusCode::ACCEPTED);
+
+	let session = response.json()["token"].as_str().unwrap().to_owned();
+
+	assert_eq!(app.get("/api/user/many", &session).await.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn codes_cannot_be_replayed() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+	let (secret, _) = enroll(&app, &token).await;
+
+	let challenge_token = challenge(&app).await;
+	let current = code(&secret, 0);
+
+	assert_eq!(verify_login(&app, &challenge_token, &current).await, StatusCode::ACCEPTED);
+	assert_eq!(verify_login(&app, &challenge_token, &current).await, StatusCode::UNAUTHORIZED);
+	// Nor can an older one, once a newer step was used.
+	assert_eq!(verify_login(&app, &challenge_token, &code(&secret, -1)).await, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn challenges_are_single_use() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+	let (secret, confirmed) = enroll(&app, &token).await;
+
+	let challenge_token = challenge(&app).await;
+	let recovery_code = confirmed["recovery_codes"][0].as_str().unwrap();
+
+	assert_eq!(verify_login(&app, &challenge_token, &code(&secret, 0)).await, StatusCode::ACCEPTED);
+	// A valid, unused factor doesn't revive a redeemed challenge.
+	assert_eq!(verify_login(&app, &challenge_token, recovery_code).await, StatusCode::UNAUTHORIZED);
+	assert_eq!(verify_login(&app, &challenge(&app).await, recovery_code).await, StatusCode::ACCEPTED);
+}
+
+#[tokio::test]
+async fn recovery_codes_work_once() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+	let (_, confirmed) = enroll(&app, &token).await;
+
+	let recovery_code = confirmed["recovery_codes"][0].as_str().unwrap().to_uppercase();
+	let challenge_token = challenge(&app).await;
+
+	assert_eq!(verify_login(&app, &challenge_token, &recovery_code).await, StatusCode::ACCEPTED);
+	assert_eq!(verify_login(&app, &challenge_token, &recovery_code).await, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn regenerating_recovery_codes_retires_the_old_ones() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+	let (secret, confirmed) = enroll(&app, &token).await;
+
+	let regenerated = app.post("/api/auth/2fa/recovery-codes", &token, json!({ "code": code(&secret, 0) })).await;
+
+	assert_eq!(regenerated.status, StatusCode::OK);
+	assert_ne!(regenerated.json()["recovery_codes"], confirmed["recovery_codes"]);
+
+	let challenge_token = challenge(&app).await;
+	let old_code = confirmed["recovery_codes"][0].as_str().unwrap();
+
+	assert_eq!(verify_login(&app, &challenge_token, old_code).await, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn disabling_restores_password_only_login() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+	let (secret, _) = enroll(&app, &token).await;
+
+	assert_eq!(app.post("/api/auth/2fa/disable", &token, json!({ "code": "123456" })).await.status, StatusCode::BAD_REQUEST);
+	assert_eq!(app.post("/api/auth/2fa/disable", &token, json!({ "code": code(&secret, 0) })).await.status, StatusCode::OK);
+
+	assert_eq!(app.login_as(ADMIN_USERNAME, ADMIN_PASSWORD).await.status, StatusCode::ACCEPTED);
+}
+
+#[tokio::test]
+async fn required_roles_enrol_before_logging_in() {
+	let app = TestApp::spawn_with(|config| {
+		config.two_factor.required_roles = vec!["admin".to_owned()];
+	}).await;
+	app.create_user(ADMIN_USERNAME, ADMIN_PASSWORD, "admin").await;
+	app.create_user("kasir", "kasir-password", "kasir").await;
+
+	// Other roles are unaffected.
+	assert_eq!(app.login_as("kasir", "kasir-password").await.status, StatusCode::ACCEPTED);
+
+	let response = app.login_as(ADMIN_USERNAME, ADMIN_PASSWORD).await;
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(response.json()["enrollment_required"], true);
+
+	let challenge_token = response.json()["challenge_token"].as_str().unwrap().to_owned();
+
+	// An enrolment challenge can't finish a login by itself.
+	assert_eq!(verify_login(&app, &challenge_token, "123456").await, StatusCode::UNAUTHORIZED);
+
+	let (secret, confirmed) = enroll(&app, &challenge_token).await;
+	let session = confirmed["token"].as_str().expect("confirmation finishes the login").to_owned();
+
+	assert_eq!(confirmed["data"]["username"], ADMIN_USERNAME);
+	assert_eq!(app.get("/api/user/many", &session).await.status, StatusCode::OK);
+
+	// Nor can the role turn it off again.
+	let response = app.post("/api/auth/2fa/disable", &session, json!({ "code": code(&secret, 0) })).await;
+
+	assert_eq!(response.status, StatusCode::FORBIDDEN);
+}
+
+async fn set_role(app: &TestApp, id: i32, role: &str) {
+	let mut model: user::ActiveModel = user::Entity::find_by_id(id).one(&app.db).await.unwrap().unwrap().into();
+	model.role = Set(role.to_owned());
+	model.update(&app.db).await.unwrap();
+}
+
+async fn start_enrolment(app: &TestApp, token: &str) -> StatusCode {
+	app.request(Method::POST, "/api/auth/2fa/enroll", Some(token), None).await.status
+}
+
+#[tokio::test]
+async fn enrolment_challenges_lapse_once_they_no_longer_apply() {
+	let app = TestApp::spawn_with(|config| {
+		config.two_factor.required_roles = vec!["admin".to_owned()];
+	}).await;
+	let id = app.create_user(ADMIN_USERNAME, ADMIN_PASSWORD, "admin").await;
+
+	let first = app.login_as(ADMIN_USERNAME, ADMIN_PASSWORD).await.json()["challenge_token"].as_str().unwrap().to_owned();
+	let second = app.login_as(ADMIN_USERNAME, ADMIN_PASSWORD).await.json()["challenge_token"].as_str().unwrap().to_owned();
+
+	// Not required for the role anymore.
+	set_role(&app, id, "cashier").await;
+
+	assert_eq!(start_enrolment(&app, &first).await, StatusCode::UNAUTHORIZED);
+
+	set_role(&app, id, "admin").await;
+	enroll(&app, &first).await;
+
+	// Already enrolled, and the first challenge is spent.
+	assert_eq!(start_enrolment(&app, &first).await, StatusCode::UNAUTHORIZED);
+	assert_eq!(start_enrolment(&app, &second).await, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn wrong_codes_count_as_failed_logins() {
+	let app = TestApp::spawn_with(|config| {
+		config.login_throttle.free_attempts = 100;
+		config.login_throttle.max_failures = 2;
+	}).await;
+	let token = app.login().await;
+	let (secret, _) = enroll(&app, &token).await;
+
+	let challenge_token = challenge(&app).await;
+
+	assert_eq!(verify_login(&app, &challenge_token, "000000").await, StatusCode::UNAUTHORIZED);
+	assert_eq!(verify_login(&app, &challenge_token, "000000").await, StatusCode::UNAUTHORIZED);
+	assert_eq!(verify_login(&app, &challenge_token, &code(&secret, 0)).await, StatusCode::TOO_MANY_REQUESTS);
+}
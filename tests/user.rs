@@ -0,0 +1,122 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use common::{ TestApp, ADMIN_USERNAME };
+
+fn user_body(username: &str, password: &str) -> serde_json::Value {
+	json!({
+		"username": username,
+		"password": password,
+		"full_name": "Kasir Satu",
+		"address": "Jl. Merdeka 1",
+		"phone_number": "0811111111",
+		"role": "cashier",
+		"photo": ""
+	})
+}
+
+#[tokio::test]
+async fn find_many_hides_password_hashes() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+
+	let response = app.get("/api/user/many", &token).await;
+	let users = response.json();
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(users[0]["username"], ADMIN_USERNAME);
+	assert_eq!(users[0]["password"], "");
+}
+
+#[tokio::test]
+async fn create_user_can_log_in() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+
+	let response = app.post("/api/user", &token, user_body("kasir", "kasir-password")).await;
+
+	assert_eq!(response.status, StatusCode::ACCEPTED);
+	assert_eq!(app.login_as("kasir", "kasir-password").await.status, StatusCode::ACCEPTED);
+}
+
+#[tokio::test]
+async fn update_changes_the_profile_but_not_the_password() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+	let id = app.create_user("kasir", "kasir-password", "cashier").await;
+
+	let response = app.put(&format!("/api/user/{id}"), &token, json!({ "full_name": "Kasir Dua" })).await;
+	let with_password = app.put(&format!("/api/user/{id}"), &token, json!({
+		"full_name": "Kasir Tiga",
+		"password": "another-password"
+	})).await;
+
+	let users = app.get("/api/user/many", &token).await.json();
+	let kasir = users.as_array().unwrap().iter().find(|d| d["id"] == id).unwrap();
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(with_password.status, StatusCode::UNPROCESSABLE_ENTITY);
+	assert_eq!(kasir["full_name"], "Kasir Dua");
+	assert_eq!(kasir["username"], "kasir");
+	assert_eq!(app.login_as("kasir", "kasir-password").await.status, StatusCode::ACCEPTED);
+	assert_eq!(app.login_as("kasir", "another-password").await.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn only_admins_manage_users() {
+	let app = TestApp::spawn().await;
+	let id = app.create_user("kasir", "kasir-password", "cashier").await;
+	let token = app.login_as("kasir", "kasir-password").await.json()["token"].as_str().unwrap().to_owned();
+
+	let create = app.post("/api/user", &token, user_body("kasir2", "kasir-password")).await;
+	let update = app.put(&format!("/api/user/{id}"), &token, json!({ "role": "admin" })).await;
+	let delete = app.delete(&format!("/api/user/{id}"), &token).await;
+
+	assert_eq!(create.status, StatusCode::FORBIDDEN);
+	assert_eq!(update.status, StatusCode::FORBIDDEN);
+	assert_eq!(delete.status, StatusCode::FORBIDDEN);
+	assert_eq!(app.get("/api/auth/me", &token).await.json()["data"]["role"], "cashier");
+}
+
+#[tokio::test]
+async fn role_changes_end_the_users_sessions() {
+	let app = TestApp::spawn().await;
+	let admin = app.login().await;
+	let id = app.create_user("kasir", "kasir-password", "cashier").await;
+	let token = app.login_as("kasir", "kasir-password").await.json()["token"].as_str().unwrap().to_owned();
+
+	app.put(&format!("/api/user/{id}"), &admin, json!({ "full_name": "Kasir Dua" })).await;
+
+	assert_eq!(app.get("/api/auth/me", &token).await.status, StatusCode::OK);
+
+	app.put(&format!("/api/user/{id}"), &admin, json!({ "role": "supervisor" })).await;
+
+	assert_eq!(app.get("/api/auth/me", &token).await.status, StatusCode::UNAUTHORIZED);
+	assert_eq!(app.get("/api/auth/me", &admin).await.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn update_and_delete_unknown_user_return_not_found() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+
+	let update = app.put("/api/user/9999", &token, json!({ "full_name": "Nobody" })).await;
+	let delete = app.delete("/api/user/9999", &token).await;
+
+	assert_eq!(update.status, StatusCode::NOT_FOUND);
+	assert_eq!(delete.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn delete_removes_user() {
+	let app = TestApp::spawn().await;
+	let token = app.login().await;
+	let id = app.create_user("kasir", "kasir-password", "cashier").await;
+
+	let response = app.delete(&format!("/api/user/{id}"), &token).await;
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(app.login_as("kasir", "kasir-password").await.status, StatusCode::UNAUTHORIZED);
+}